@@ -0,0 +1,613 @@
+//! `fracvault`: operator CLI for the FracVault program.
+//!
+//! Every command builds its instructions with `fracvault-client`, signs them with the configured
+//! keypair and waits for confirmation. Point `--url` at a local test validator (the default) or
+//! any cluster the program is deployed to.
+
+mod output;
+
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use fracvault_client::accounts::{self, fetch, fetch_config};
+use fracvault_client::instructions as ix;
+use fracvault_client::program::{
+    self, DecayCurve, DutchAuctionParams, Listing, ListingStatus,
+    Presale, PresaleProof, Proposal, SoftCloseParams, Vote,
+};
+use fracvault_client::rpc::{RpcClient, DEFAULT_URL};
+use fracvault_client::{pda, read_keypair_file, ClientError, Instruction, Keypair, Pubkey, Signer};
+
+#[derive(Parser)]
+#[command(name = "fracvault", version, about = "Operate FracVault listings from the command line")]
+struct Cli {
+    /// JSON-RPC endpoint.
+    #[arg(long, short = 'u', global = true, env = "FRACVAULT_URL", default_value = DEFAULT_URL)]
+    url: String,
+
+    /// Keypair that signs and pays for transactions (defaults to the Solana CLI keypair).
+    #[arg(long, short = 'k', global = true, env = "FRACVAULT_KEYPAIR")]
+    keypair: Option<PathBuf>,
+
+    /// Commitment to read at and confirm transactions to.
+    #[arg(long, global = true, default_value = "confirmed")]
+    commitment: String,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Initialize, update or inspect the global config.
+    #[command(subcommand)]
+    Config(ConfigCommand),
+    /// Create, show or list listings.
+    #[command(subcommand)]
+    Listing(ListingCommand),
+    /// Buy bps of a listing.
+    Contribute {
+        listing: Pubkey,
+        #[arg(long)]
+        bps: u16,
+        /// Settle the purchase in the same transaction if this contribution completes the raise.
+        #[arg(long)]
+        execute: bool,
+        #[command(flatten)]
+        presale: PresaleProofArgs,
+    },
+    /// Refund the signer's contribution to a failed listing.
+    Refund { listing: Pubkey },
+    /// Refund every outstanding contribution to a failed listing.
+    CrankRefunds { listing: Pubkey },
+    /// Claim the signer's rebate from an auction that cleared below its entry price.
+    ClaimRebate { listing: Pubkey },
+    /// Execute the purchase of a funded listing.
+    Execute { listing: Pubkey },
+    /// Return the NFT of a failed listing to its seller.
+    Reclaim { listing: Pubkey },
+    /// Close a fully refunded listing and return its rent to the seller.
+    Close { listing: Pubkey },
+    /// Grow a listing created before account versioning to the current size.
+    Migrate { listing: Pubkey },
+    /// Grow any other program account created before it carried a version.
+    MigrateAccount { account: Pubkey },
+    /// Create, inspect or finalize sale proposals on a custodied listing.
+    #[command(subcommand)]
+    Proposal(ProposalCommand),
+    /// Vote on a sale proposal with the signer's bps.
+    Vote {
+        listing: Pubkey,
+        proposal_id: u32,
+        #[arg(value_enum)]
+        vote: VoteArg,
+    },
+    /// Register, claim or list rewards of a custodied listing.
+    #[command(subcommand)]
+    Reward(RewardCommand),
+    /// Print each listing's lifecycle state and the next action currently allowed.
+    Status {
+        /// Only this listing.
+        listing: Option<Pubkey>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    Init {
+        #[arg(long)]
+        fee_vault: Pubkey,
+        #[arg(long, default_value_t = 100)]
+        custody_fee_bps: u16,
+    },
+    Update {
+        #[arg(long)]
+        allowlist_enabled: Option<bool>,
+        #[arg(long)]
+        royalties_enabled: Option<bool>,
+        #[arg(long)]
+        keeper_bounty_lamports: Option<u64>,
+    },
+    Show,
+}
+
+#[derive(Subcommand)]
+enum ListingCommand {
+    /// List the signer's NFT for fractional sale.
+    Create(Box<CreateListingArgs>),
+    Show { listing: Pubkey },
+    List {
+        #[arg(long, value_enum)]
+        status: Option<StatusArg>,
+        #[arg(long)]
+        seller: Option<Pubkey>,
+    },
+}
+
+#[derive(Args)]
+struct CreateListingArgs {
+    #[arg(long)]
+    nft_mint: Pubkey,
+    #[arg(long)]
+    price_lamports: u64,
+    /// Seconds until the deadline (3600-604800).
+    #[arg(long)]
+    deadline_offset: i64,
+    /// Verified collection of the NFT; required if it has one.
+    #[arg(long)]
+    collection: Option<Pubkey>,
+    #[arg(long, default_value_t = 0)]
+    retained_bps: u16,
+    #[arg(long, default_value_t = 0)]
+    min_fill_bps: u16,
+    #[arg(long, default_value_t = 0)]
+    max_bps_per_wallet: u16,
+    #[arg(long, default_value_t = 0)]
+    min_bps_per_contribution: u16,
+    #[arg(long, default_value_t = 0)]
+    keeper_tip_lamports: u64,
+    /// Run a Dutch auction from `price_lamports` down to this floor.
+    #[arg(long, requires = "auction_duration")]
+    auction_floor_lamports: Option<u64>,
+    #[arg(long, requires = "auction_floor_lamports")]
+    auction_duration: Option<i64>,
+    /// Decay in steps of this many seconds instead of linearly.
+    #[arg(long, requires = "auction_floor_lamports")]
+    auction_step_seconds: Option<i64>,
+    /// Hex-encoded presale allowlist Merkle root.
+    #[arg(long, requires = "presale_end")]
+    presale_root: Option<String>,
+    /// Unix timestamp at which the presale ends.
+    #[arg(long, requires = "presale_root")]
+    presale_end: Option<i64>,
+    #[arg(long, requires_all = ["soft_close_extension", "soft_close_max_extension"])]
+    soft_close_trigger: Option<i64>,
+    #[arg(long, requires = "soft_close_trigger")]
+    soft_close_extension: Option<i64>,
+    #[arg(long, requires = "soft_close_trigger")]
+    soft_close_max_extension: Option<i64>,
+}
+
+#[derive(Args)]
+struct PresaleProofArgs {
+    /// Per-wallet cap committed in the signer's allowlist leaf.
+    #[arg(long, requires = "proof")]
+    proof_max_bps: Option<u16>,
+    /// Hex-encoded Merkle proof nodes, leaf to root.
+    #[arg(long, num_args = 1.., requires = "proof_max_bps")]
+    proof: Vec<String>,
+}
+
+#[derive(Subcommand)]
+enum ProposalCommand {
+    Create {
+        listing: Pubkey,
+        #[arg(long)]
+        sale_price_lamports: u64,
+        /// Seconds until voting ends (3600-604800).
+        #[arg(long)]
+        vote_deadline_offset: i64,
+    },
+    Show { listing: Pubkey, proposal_id: u32 },
+    List { listing: Pubkey },
+    Finalize { listing: Pubkey, proposal_id: u32 },
+}
+
+#[derive(Subcommand)]
+enum RewardCommand {
+    Register {
+        listing: Pubkey,
+        #[arg(long)]
+        mint: Pubkey,
+        #[arg(long)]
+        amount: u64,
+    },
+    Claim {
+        listing: Pubkey,
+        #[arg(long)]
+        mint: Pubkey,
+    },
+    List { listing: Pubkey },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum StatusArg {
+    Open,
+    Funded,
+    Custodied,
+    Expired,
+    Refunded,
+    Sold,
+}
+
+impl From<StatusArg> for ListingStatus {
+    fn from(status: StatusArg) -> Self {
+        match status {
+            StatusArg::Open => ListingStatus::Open,
+            StatusArg::Funded => ListingStatus::Funded,
+            StatusArg::Custodied => ListingStatus::Custodied,
+            StatusArg::Expired => ListingStatus::Expired,
+            StatusArg::Refunded => ListingStatus::Refunded,
+            StatusArg::Sold => ListingStatus::Sold,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum VoteArg {
+    Yes,
+    No,
+}
+
+impl From<VoteArg> for Vote {
+    fn from(vote: VoteArg) -> Self {
+        match vote {
+            VoteArg::Yes => Vote::Yes,
+            VoteArg::No => Vote::No,
+        }
+    }
+}
+
+struct Session {
+    rpc: RpcClient,
+    keypair_path: PathBuf,
+}
+
+impl Session {
+    fn signer(&self) -> Result<Keypair> {
+        read_keypair_file(&self.keypair_path)
+            .map_err(|e| anyhow!("reading keypair {}: {e}", self.keypair_path.display()))
+    }
+
+    fn send(&self, instructions: &[Instruction], signer: &Keypair) -> Result<()> {
+        let signature = self.rpc.send_and_confirm(instructions, signer, &[])?;
+        println!("signature: {signature}");
+        Ok(())
+    }
+
+    fn listing(&self, address: &Pubkey) -> Result<Listing> {
+        Ok(fetch(&self.rpc, address)?.ok_or(ClientError::AccountNotFound(*address))?)
+    }
+
+    fn fee_vault(&self) -> Result<Pubkey> {
+        let config = fetch_config(&self.rpc)?.context("config is not initialized")?;
+        Ok(config.fee_vault)
+    }
+
+    fn now(&self) -> Result<i64> {
+        Ok(self.rpc.get_clock()?.unix_timestamp)
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+    if let Err(error) = run(cli) {
+        eprintln!("error: {error:#}");
+        if let Some(ClientError::Rpc { logs, .. } | ClientError::Simulation { logs, .. }) =
+            error.downcast_ref::<ClientError>()
+        {
+            for line in logs {
+                eprintln!("  {line}");
+            }
+        }
+        std::process::exit(1);
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    let keypair_path = match cli.keypair {
+        Some(path) => path,
+        None => default_keypair_path()?,
+    };
+    let ctx = Session { rpc: RpcClient::with_commitment(cli.url, cli.commitment), keypair_path };
+
+    match cli.command {
+        Command::Config(command) => config(&ctx, command),
+        Command::Listing(command) => listing(&ctx, command),
+        Command::Contribute { listing, bps, execute, presale } => {
+            let signer = ctx.signer()?;
+            let proof = presale.into_proof()?;
+            let instruction = if execute {
+                let state = ctx.listing(&listing)?;
+                ix::contribute_and_execute(&listing, &state, &ctx.fee_vault()?, &signer.pubkey(), bps, proof)
+            } else {
+                ix::contribute(&listing, &signer.pubkey(), bps, proof)
+            };
+            ctx.send(&[instruction], &signer)
+        }
+        Command::Refund { listing } => {
+            let signer = ctx.signer()?;
+            ctx.send(&[ix::process_refund(&listing, &signer.pubkey())], &signer)
+        }
+        Command::CrankRefunds { listing } => crank_refunds(&ctx, &listing),
+        Command::ClaimRebate { listing } => {
+            let signer = ctx.signer()?;
+            ctx.send(&[ix::claim_auction_rebate(&listing, &signer.pubkey())], &signer)
+        }
+        Command::Execute { listing } => {
+            let signer = ctx.signer()?;
+            let state = ctx.listing(&listing)?;
+            let instruction = ix::execute_purchase(&listing, &state, &ctx.fee_vault()?, &signer.pubkey());
+            ctx.send(&[instruction], &signer)
+        }
+        Command::Reclaim { listing } => {
+            let signer = ctx.signer()?;
+            let state = ctx.listing(&listing)?;
+            ctx.send(&[ix::reclaim_nft(&listing, &state)], &signer)
+        }
+        Command::Close { listing } => {
+            let signer = ctx.signer()?;
+            let state = ctx.listing(&listing)?;
+            ctx.send(&[ix::close_listing(&listing, &state)], &signer)
+        }
+        Command::Migrate { listing } => {
+            let signer = ctx.signer()?;
+            ctx.send(&[ix::migrate_listing(&listing, &signer.pubkey())], &signer)
+        }
+        Command::MigrateAccount { account } => {
+            let signer = ctx.signer()?;
+            ctx.send(&[ix::migrate_account(&account, &signer.pubkey())], &signer)
+        }
+        Command::Proposal(command) => proposal(&ctx, command),
+        Command::Vote { listing, proposal_id, vote } => {
+            let signer = ctx.signer()?;
+            ctx.send(&[ix::cast_vote(&listing, proposal_id, &signer.pubkey(), vote.into())], &signer)
+        }
+        Command::Reward(command) => reward(&ctx, command),
+        Command::Status { listing } => status(&ctx, listing),
+    }
+}
+
+fn config(ctx: &Session, command: ConfigCommand) -> Result<()> {
+    match command {
+        ConfigCommand::Init { fee_vault, custody_fee_bps } => {
+            let signer = ctx.signer()?;
+            ctx.send(&[ix::initialize_config(&signer.pubkey(), &fee_vault, custody_fee_bps)], &signer)?;
+            println!("config: {}", pda::config().0);
+            Ok(())
+        }
+        ConfigCommand::Update { allowlist_enabled, royalties_enabled, keeper_bounty_lamports } => {
+            ensure!(
+                allowlist_enabled.is_some() || royalties_enabled.is_some() || keeper_bounty_lamports.is_some(),
+                "nothing to update"
+            );
+            let signer = ctx.signer()?;
+            let instruction =
+                ix::update_config(&signer.pubkey(), allowlist_enabled, royalties_enabled, keeper_bounty_lamports);
+            ctx.send(&[instruction], &signer)
+        }
+        ConfigCommand::Show => {
+            let config = fetch_config(&ctx.rpc)?.context("config is not initialized")?;
+            output::print_config(&pda::config().0, &config);
+            Ok(())
+        }
+    }
+}
+
+fn listing(ctx: &Session, command: ListingCommand) -> Result<()> {
+    match command {
+        ListingCommand::Create(args) => {
+            let signer = ctx.signer()?;
+            let (nft_mint, collection) = (args.nft_mint, args.collection);
+            let instruction = ix::create_listing(&signer.pubkey(), &nft_mint, collection.as_ref(), (*args).into_args()?);
+            ctx.send(&[instruction], &signer)?;
+            println!("listing: {}", pda::listing(&nft_mint).0);
+            Ok(())
+        }
+        ListingCommand::Show { listing } => {
+            let state = ctx.listing(&listing)?;
+            let contributions = accounts::contributions_by_listing(&ctx.rpc, &listing)?;
+            output::print_listing(&listing, &state, &contributions, ctx.now()?);
+            Ok(())
+        }
+        ListingCommand::List { status, seller } => {
+            let mut listings = match (status, seller) {
+                (Some(status), _) => accounts::listings_by_status(&ctx.rpc, &status.into())?,
+                (None, Some(seller)) => accounts::listings_by_seller(&ctx.rpc, &seller)?,
+                (None, None) => accounts::all_listings(&ctx.rpc)?,
+            };
+            if let Some(seller) = seller {
+                listings.retain(|(_, listing)| listing.nft_seller == seller);
+            }
+            listings.sort_by_key(|(_, listing)| listing.deadline);
+            output::print_listing_table(&listings, ctx.now()?);
+            Ok(())
+        }
+    }
+}
+
+fn crank_refunds(ctx: &Session, listing: &Pubkey) -> Result<()> {
+    let signer = ctx.signer()?;
+    let wallets: Vec<Pubkey> = accounts::contributions_by_listing(&ctx.rpc, listing)?
+        .into_iter()
+        .filter(|(_, contribution)| !contribution.refund_claimed)
+        .map(|(_, contribution)| contribution.wallet)
+        .collect();
+    if wallets.is_empty() {
+        println!("no outstanding refunds");
+        return Ok(());
+    }
+    for batch in wallets.chunks(ix::CRANK_REFUNDS_BATCH) {
+        ctx.send(&[ix::crank_refunds(listing, &signer.pubkey(), batch)], &signer)?;
+    }
+    println!("refunded {} contributions", wallets.len());
+    Ok(())
+}
+
+fn proposal(ctx: &Session, command: ProposalCommand) -> Result<()> {
+    match command {
+        ProposalCommand::Create { listing, sale_price_lamports, vote_deadline_offset } => {
+            let signer = ctx.signer()?;
+            let state = ctx.listing(&listing)?;
+            let instruction =
+                ix::create_proposal(&listing, &state, &signer.pubkey(), sale_price_lamports, vote_deadline_offset);
+            ctx.send(&[instruction], &signer)?;
+            println!(
+                "proposal {}: {}",
+                state.proposal_count,
+                pda::proposal(&listing, state.proposal_count).0
+            );
+            Ok(())
+        }
+        ProposalCommand::Show { listing, proposal_id } => {
+            let address = pda::proposal(&listing, proposal_id).0;
+            let proposal: Proposal = fetch(&ctx.rpc, &address)?.ok_or(ClientError::AccountNotFound(address))?;
+            let votes = accounts::votes_by_proposal(&ctx.rpc, &address)?;
+            output::print_proposal(&address, &proposal, &votes, ctx.now()?);
+            Ok(())
+        }
+        ProposalCommand::List { listing } => {
+            let mut proposals = accounts::proposals_by_listing(&ctx.rpc, &listing)?;
+            proposals.sort_by_key(|(_, proposal)| proposal.proposal_id);
+            output::print_proposal_table(&proposals, ctx.now()?);
+            Ok(())
+        }
+        ProposalCommand::Finalize { listing, proposal_id } => {
+            let signer = ctx.signer()?;
+            ctx.send(&[ix::finalize_proposal(&listing, proposal_id, &signer.pubkey())], &signer)
+        }
+    }
+}
+
+fn reward(ctx: &Session, command: RewardCommand) -> Result<()> {
+    match command {
+        RewardCommand::Register { listing, mint, amount } => {
+            let signer = ctx.signer()?;
+            ctx.send(&[ix::register_reward(&listing, &mint, &signer.pubkey(), amount)], &signer)
+        }
+        RewardCommand::Claim { listing, mint } => {
+            let signer = ctx.signer()?;
+            ctx.send(&[ix::claim_reward(&listing, &mint, &signer.pubkey())], &signer)
+        }
+        RewardCommand::List { listing } => {
+            output::print_rewards(&accounts::rewards_by_listing(&ctx.rpc, &listing)?);
+            Ok(())
+        }
+    }
+}
+
+fn status(ctx: &Session, listing: Option<Pubkey>) -> Result<()> {
+    let mut listings = match listing {
+        Some(address) => vec![(address, ctx.listing(&address)?)],
+        None => accounts::all_listings(&ctx.rpc)?,
+    };
+    listings.sort_by_key(|(_, listing)| listing.deadline);
+    output::print_status(&listings, ctx.now()?);
+    Ok(())
+}
+
+impl CreateListingArgs {
+    fn into_args(self) -> Result<program::CreateListingArgs> {
+        let auction = match (self.auction_floor_lamports, self.auction_duration) {
+            (Some(floor_price_lamports), Some(duration)) => Some(DutchAuctionParams {
+                floor_price_lamports,
+                curve: match self.auction_step_seconds {
+                    Some(step_seconds) => DecayCurve::Stepwise { step_seconds },
+                    None => DecayCurve::Linear,
+                },
+                duration,
+            }),
+            _ => None,
+        };
+        let presale = match (self.presale_root, self.presale_end) {
+            (Some(root), Some(end_time)) => Some(Presale { merkle_root: parse_hash(&root)?, end_time }),
+            _ => None,
+        };
+        let soft_close = match (self.soft_close_trigger, self.soft_close_extension, self.soft_close_max_extension) {
+            (Some(trigger_seconds), Some(extension_seconds), Some(max_extension_seconds)) => {
+                Some(SoftCloseParams { trigger_seconds, extension_seconds, max_extension_seconds })
+            }
+            _ => None,
+        };
+        Ok(program::CreateListingArgs {
+            price_lamports: self.price_lamports,
+            deadline_offset: self.deadline_offset,
+            retained_bps: self.retained_bps,
+            min_fill_bps: self.min_fill_bps,
+            auction,
+            presale,
+            max_bps_per_wallet: self.max_bps_per_wallet,
+            min_bps_per_contribution: self.min_bps_per_contribution,
+            soft_close,
+            keeper_tip_lamports: self.keeper_tip_lamports,
+        })
+    }
+}
+
+impl PresaleProofArgs {
+    fn into_proof(self) -> Result<Option<PresaleProof>> {
+        let Some(max_bps) = self.proof_max_bps else {
+            return Ok(None);
+        };
+        let proof = self.proof.iter().map(|node| parse_hash(node)).collect::<Result<_>>()?;
+        Ok(Some(PresaleProof { max_bps, proof }))
+    }
+}
+
+fn parse_hash(hex: &str) -> Result<[u8; 32]> {
+    let hex = hex.trim_start_matches("0x");
+    ensure!(hex.len() == 64 && hex.is_ascii(), "expected 32 hex-encoded bytes, got {hex:?}");
+    let mut hash = [0u8; 32];
+    for (byte, pair) in hash.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        let pair = std::str::from_utf8(pair)?;
+        *byte = u8::from_str_radix(pair, 16).with_context(|| format!("invalid hex byte {pair:?}"))?;
+    }
+    Ok(hash)
+}
+
+fn default_keypair_path() -> Result<PathBuf> {
+    let Some(home) = std::env::var_os("HOME") else {
+        bail!("HOME is not set; pass --keypair");
+    };
+    Ok(PathBuf::from(home).join(".config/solana/id.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hex_hashes() {
+        let hash = parse_hash(&format!("0x{}", "ab".repeat(32))).unwrap();
+        assert_eq!(hash, [0xab; 32]);
+        assert!(parse_hash("abcd").is_err());
+        assert!(parse_hash(&"zz".repeat(32)).is_err());
+    }
+
+    #[test]
+    fn cli_definition_is_consistent() {
+        use clap::CommandFactory;
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn auction_flags_build_stepwise_curve() {
+        let cli = Cli::try_parse_from([
+            "fracvault",
+            "listing",
+            "create",
+            "--nft-mint",
+            &Pubkey::new_unique().to_string(),
+            "--price-lamports",
+            "1000000000",
+            "--deadline-offset",
+            "86400",
+            "--auction-floor-lamports",
+            "500000000",
+            "--auction-duration",
+            "3600",
+            "--auction-step-seconds",
+            "600",
+        ])
+        .unwrap();
+        let Command::Listing(ListingCommand::Create(args)) = cli.command else {
+            panic!("expected listing create");
+        };
+        let args = (*args).into_args().unwrap();
+        let auction = args.auction.unwrap();
+        assert!(auction.curve == DecayCurve::Stepwise { step_seconds: 600 });
+        assert_eq!((auction.floor_price_lamports, auction.duration), (500_000_000, 3600));
+        assert!(args.presale.is_none() && args.soft_close.is_none());
+    }
+}
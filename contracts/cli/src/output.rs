@@ -0,0 +1,208 @@
+//! Human-readable rendering of program accounts.
+
+use fracvault_client::accounts::status_label;
+use fracvault_client::lifecycle::{next_actions, NextAction};
+use fracvault_client::program::{
+    Config, Contribution, Listing, Proposal, ProposalStatus, RewardRegistry, Vote, VoteRecord,
+};
+use fracvault_client::Pubkey;
+
+pub fn print_config(address: &Pubkey, config: &Config) {
+    println!("config             {address}");
+    println!("authority          {}", config.authority);
+    println!("fee vault          {}", config.fee_vault);
+    println!("custody fee        {} bps", config.custody_fee_bps);
+    println!("allowlist enabled  {}", config.allowlist_enabled);
+    println!("royalties enabled  {}", config.royalties_enabled);
+    println!("keeper bounty      {}", sol(config.keeper_bounty_lamports));
+}
+
+pub fn print_listing(address: &Pubkey, listing: &Listing, contributions: &[(Pubkey, Contribution)], now: i64) {
+    println!("listing        {address}");
+    println!("nft mint       {}", listing.nft_mint);
+    println!("seller         {}", listing.nft_seller);
+    println!("status         {}", status_label(&listing.status));
+    println!("price          {}", sol(listing.price_lamports));
+    println!("custody fee    {} ({} bps)", sol(listing.custody_fee_lamports), listing.custody_fee_bps);
+    println!(
+        "sold           {} / {} bps (retained {}, min fill {})",
+        listing.bps_sold,
+        10000 - listing.retained_bps,
+        listing.retained_bps,
+        listing.min_fill_bps
+    );
+    println!("deadline       {} ({})", listing.deadline, relative(listing.deadline, now));
+    if listing.funded_at > 0 {
+        println!("funded at      {} ({})", listing.funded_at, relative(listing.funded_at, now));
+    }
+    if let Some(auction) = &listing.auction {
+        println!("auction floor  {}", sol(auction.floor_price_lamports));
+    }
+    if let Some(presale) = &listing.presale {
+        println!("presale ends   {} ({})", presale.end_time, relative(presale.end_time, now));
+    }
+    if let Some(soft_close) = &listing.soft_close {
+        println!("hard deadline  {} ({})", soft_close.hard_deadline, relative(soft_close.hard_deadline, now));
+    }
+    if listing.refunded_bps > 0 {
+        println!("refunded       {} bps ({})", listing.refunded_bps, sol(listing.refunded_lamports));
+    }
+    println!("nft reclaimed  {}", listing.nft_reclaimed);
+    println!("proposals      {}", listing.proposal_count);
+    println!("next           {}", describe_all(listing, now));
+    println!("contributions  {}", contributions.len());
+    for (_, contribution) in contributions {
+        println!(
+            "  {}  {:>5} bps  {}{}",
+            contribution.wallet,
+            contribution.bps,
+            sol(contribution.principal_lamports + contribution.fee_lamports),
+            if contribution.refund_claimed { "  refunded" } else { "" }
+        );
+    }
+}
+
+pub fn print_listing_table(listings: &[(Pubkey, Listing)], now: i64) {
+    for (address, listing) in listings {
+        println!(
+            "{address}  {:<9}  {:>5} bps  {:>20}  deadline {}",
+            status_label(&listing.status),
+            listing.bps_sold,
+            sol(listing.price_lamports),
+            relative(listing.deadline, now)
+        );
+    }
+    println!("{} listings", listings.len());
+}
+
+pub fn print_status(listings: &[(Pubkey, Listing)], now: i64) {
+    for (address, listing) in listings {
+        println!("{address}  {:<9}  {}", status_label(&listing.status), describe_all(listing, now));
+    }
+}
+
+pub fn print_proposal(address: &Pubkey, proposal: &Proposal, votes: &[(Pubkey, VoteRecord)], now: i64) {
+    println!("proposal       {address}");
+    println!("id             {}", proposal.proposal_id);
+    println!("proposer       {}", proposal.proposer);
+    println!("sale price     {}", sol(proposal.sale_price_lamports));
+    println!("status         {}", proposal_status_label(&proposal.status));
+    println!("vote deadline  {} ({})", proposal.vote_deadline, relative(proposal.vote_deadline, now));
+    println!("yes / no       {} / {} bps", proposal.yes_bps, proposal.no_bps);
+    for (_, vote) in votes {
+        let choice = match vote.vote {
+            Vote::Yes => "yes",
+            Vote::No => "no",
+        };
+        println!("  {}  {:<3}  {} bps", vote.voter, choice, vote.bps_voted);
+    }
+}
+
+pub fn print_proposal_table(proposals: &[(Pubkey, Proposal)], now: i64) {
+    for (address, proposal) in proposals {
+        println!(
+            "{:>3}  {address}  {:<8}  {:>20}  yes {} / no {}  voting ends {}",
+            proposal.proposal_id,
+            proposal_status_label(&proposal.status),
+            sol(proposal.sale_price_lamports),
+            proposal.yes_bps,
+            proposal.no_bps,
+            relative(proposal.vote_deadline, now)
+        );
+    }
+}
+
+pub fn print_rewards(rewards: &[(Pubkey, RewardRegistry)]) {
+    for (address, reward) in rewards {
+        println!(
+            "{address}  mint {}  claimed {} / {}",
+            reward.reward_mint, reward.claimed_amount, reward.total_amount
+        );
+    }
+}
+
+fn proposal_status_label(status: &ProposalStatus) -> &'static str {
+    match status {
+        ProposalStatus::Active => "active",
+        ProposalStatus::Approved => "approved",
+        ProposalStatus::Rejected => "rejected",
+        ProposalStatus::Expired => "expired",
+        ProposalStatus::Executed => "executed",
+    }
+}
+
+fn describe_all(listing: &Listing, now: i64) -> String {
+    let actions = next_actions(listing, now);
+    if actions.is_empty() {
+        return "none".into();
+    }
+    actions.iter().map(|action| describe(action, now)).collect::<Vec<_>>().join("; ")
+}
+
+pub fn describe(action: &NextAction, now: i64) -> String {
+    match action {
+        NextAction::Contribute { closes_at, presale_ends_at: Some(presale_ends_at) } => format!(
+            "contribute, allowlist only until {} (closes {})",
+            relative(*presale_ends_at, now),
+            relative(*closes_at, now)
+        ),
+        NextAction::Contribute { closes_at, presale_ends_at: None } => {
+            format!("contribute (closes {})", relative(*closes_at, now))
+        }
+        NextAction::Wait { until } => format!("wait until {until} ({})", relative(*until, now)),
+        NextAction::Execute { window_ends_at } => {
+            format!("execute (window closes {})", relative(*window_ends_at, now))
+        }
+        NextAction::Refund { outstanding_bps } => format!("crank refunds ({outstanding_bps} bps outstanding)"),
+        NextAction::ReclaimNft => "reclaim nft".into(),
+        NextAction::Close => "close".into(),
+        NextAction::Govern => "propose, vote or claim rewards".into(),
+    }
+}
+
+pub fn sol(lamports: u64) -> String {
+    format!("{}.{:09} SOL", lamports / 1_000_000_000, lamports % 1_000_000_000)
+}
+
+/// `in 2h 5m` / `3m ago` relative to `now`.
+pub fn relative(timestamp: i64, now: i64) -> String {
+    let delta = timestamp - now;
+    let seconds = delta.unsigned_abs();
+    let span = match seconds {
+        0..=59 => format!("{seconds}s"),
+        60..=3599 => format!("{}m", seconds / 60),
+        3600..=86399 => format!("{}h {}m", seconds / 3600, seconds % 3600 / 60),
+        _ => format!("{}d {}h", seconds / 86400, seconds % 86400 / 3600),
+    };
+    if delta >= 0 {
+        format!("in {span}")
+    } else {
+        format!("{span} ago")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_amounts_and_times() {
+        assert_eq!(sol(1_500_000_000), "1.500000000 SOL");
+        assert_eq!(sol(42), "0.000000042 SOL");
+        assert_eq!(relative(1_000 + 7_500, 1_000), "in 2h 5m");
+        assert_eq!(relative(1_000 - 180, 1_000), "3m ago");
+        assert_eq!(relative(1_000 + 2 * 86400 + 3600, 1_000), "in 2d 1h");
+    }
+
+    #[test]
+    fn describes_actions() {
+        assert_eq!(
+            describe(&NextAction::Execute { window_ends_at: 3_600 }, 0),
+            "execute (window closes in 1h 0m)"
+        );
+        assert_eq!(
+            describe(&NextAction::Refund { outstanding_bps: 2500 }, 0),
+            "crank refunds (2500 bps outstanding)"
+        );
+    }
+}
@@ -0,0 +1,208 @@
+//! Account decoding and `getProgramAccounts` queries.
+//!
+//! Filters only ever match the fixed-size prefix of an account (everything before its first
+//! `Option` or `Vec` field), so the offsets below are stable across optional listing features.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AccountDeserialize, AnchorSerialize, Discriminator};
+use serde_json::{json, Value};
+
+use crate::program::{
+    ClaimRecord, CollectionAllowlist, Config, Contribution, Listing, ListingStatus, Proposal,
+    RewardRegistry, VoteRecord,
+};
+use crate::rpc::RpcClient;
+use crate::{ClientError, PROGRAM_ID};
+
+/// Offset of the first field after the 8-byte Anchor discriminator.
+pub const DATA_OFFSET: usize = 8;
+
+pub const LISTING_NFT_MINT_OFFSET: usize = DATA_OFFSET;
+pub const LISTING_SELLER_OFFSET: usize = LISTING_NFT_MINT_OFFSET + 32;
+// nft_seller, price_lamports, custody_fee_lamports, total_raise_lamports, bps_sold, deadline,
+// funded_at
+pub const LISTING_STATUS_OFFSET: usize = LISTING_SELLER_OFFSET + 32 + 8 + 8 + 8 + 2 + 8 + 8;
+
+pub const CONTRIBUTION_LISTING_OFFSET: usize = DATA_OFFSET;
+pub const CONTRIBUTION_WALLET_OFFSET: usize = CONTRIBUTION_LISTING_OFFSET + 32;
+
+pub const PROPOSAL_LISTING_OFFSET: usize = DATA_OFFSET;
+
+pub const VOTE_PROPOSAL_OFFSET: usize = DATA_OFFSET;
+pub const VOTE_VOTER_OFFSET: usize = VOTE_PROPOSAL_OFFSET + 32;
+
+pub const REWARD_LISTING_OFFSET: usize = DATA_OFFSET;
+
+pub const CLAIM_REGISTRY_OFFSET: usize = DATA_OFFSET;
+pub const CLAIM_CLAIMER_OFFSET: usize = CLAIM_REGISTRY_OFFSET + 32;
+
+/// A `getProgramAccounts` filter.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Filter {
+    DataSize(u64),
+    Memcmp { offset: usize, bytes: Vec<u8> },
+}
+
+impl Filter {
+    /// Matches accounts of type `T` by their Anchor discriminator.
+    pub fn account<T: Discriminator>() -> Self {
+        Filter::Memcmp { offset: 0, bytes: T::DISCRIMINATOR.to_vec() }
+    }
+
+    pub fn pubkey(offset: usize, key: &Pubkey) -> Self {
+        Filter::Memcmp { offset, bytes: key.to_bytes().to_vec() }
+    }
+
+    pub fn to_json(&self) -> Value {
+        use base64::Engine;
+        match self {
+            Filter::DataSize(size) => json!({ "dataSize": size }),
+            Filter::Memcmp { offset, bytes } => json!({
+                "memcmp": {
+                    "offset": offset,
+                    "bytes": base64::engine::general_purpose::STANDARD.encode(bytes),
+                    "encoding": "base64",
+                }
+            }),
+        }
+    }
+}
+
+/// Decodes an account, checking its discriminator.
+pub fn decode<T: AccountDeserialize>(data: &[u8]) -> Result<T, ClientError> {
+    let mut data = data;
+    T::try_deserialize(&mut data).map_err(|e| ClientError::Decode(e.to_string()))
+}
+
+/// Fetches and decodes a single account; `None` if it does not exist.
+pub fn fetch<T: AccountDeserialize>(rpc: &RpcClient, address: &Pubkey) -> Result<Option<T>, ClientError> {
+    rpc.get_account(address)?
+        .map(|account| decode(&account.data))
+        .transpose()
+}
+
+/// Fetches and decodes every program account of type `T` matching `filters`.
+pub fn fetch_all<T: AccountDeserialize + Discriminator>(
+    rpc: &RpcClient,
+    filters: &[Filter],
+) -> Result<Vec<(Pubkey, T)>, ClientError> {
+    let mut all = vec![Filter::account::<T>()];
+    all.extend_from_slice(filters);
+    rpc.get_program_accounts(&PROGRAM_ID, &all)?
+        .into_iter()
+        .map(|(address, account)| Ok((address, decode(&account.data)?)))
+        .collect()
+}
+
+pub fn status_filter(status: &ListingStatus) -> Result<Filter, ClientError> {
+    let bytes = status.try_to_vec().map_err(|e| ClientError::Decode(e.to_string()))?;
+    Ok(Filter::Memcmp { offset: LISTING_STATUS_OFFSET, bytes })
+}
+
+pub fn fetch_config(rpc: &RpcClient) -> Result<Option<Config>, ClientError> {
+    fetch(rpc, &crate::pda::config().0)
+}
+
+pub fn all_listings(rpc: &RpcClient) -> Result<Vec<(Pubkey, Listing)>, ClientError> {
+    fetch_all(rpc, &[])
+}
+
+pub fn listings_by_status(rpc: &RpcClient, status: &ListingStatus) -> Result<Vec<(Pubkey, Listing)>, ClientError> {
+    fetch_all(rpc, &[status_filter(status)?])
+}
+
+pub fn listings_by_seller(rpc: &RpcClient, seller: &Pubkey) -> Result<Vec<(Pubkey, Listing)>, ClientError> {
+    fetch_all(rpc, &[Filter::pubkey(LISTING_SELLER_OFFSET, seller)])
+}
+
+pub fn contributions_by_wallet(rpc: &RpcClient, wallet: &Pubkey) -> Result<Vec<(Pubkey, Contribution)>, ClientError> {
+    fetch_all(rpc, &[Filter::pubkey(CONTRIBUTION_WALLET_OFFSET, wallet)])
+}
+
+pub fn contributions_by_listing(rpc: &RpcClient, listing: &Pubkey) -> Result<Vec<(Pubkey, Contribution)>, ClientError> {
+    fetch_all(rpc, &[Filter::pubkey(CONTRIBUTION_LISTING_OFFSET, listing)])
+}
+
+pub fn all_proposals(rpc: &RpcClient) -> Result<Vec<(Pubkey, Proposal)>, ClientError> {
+    fetch_all(rpc, &[])
+}
+
+pub fn proposals_by_listing(rpc: &RpcClient, listing: &Pubkey) -> Result<Vec<(Pubkey, Proposal)>, ClientError> {
+    fetch_all(rpc, &[Filter::pubkey(PROPOSAL_LISTING_OFFSET, listing)])
+}
+
+pub fn votes_by_proposal(rpc: &RpcClient, proposal: &Pubkey) -> Result<Vec<(Pubkey, VoteRecord)>, ClientError> {
+    fetch_all(rpc, &[Filter::pubkey(VOTE_PROPOSAL_OFFSET, proposal)])
+}
+
+pub fn rewards_by_listing(rpc: &RpcClient, listing: &Pubkey) -> Result<Vec<(Pubkey, RewardRegistry)>, ClientError> {
+    fetch_all(rpc, &[Filter::pubkey(REWARD_LISTING_OFFSET, listing)])
+}
+
+pub fn claims_by_claimer(rpc: &RpcClient, claimer: &Pubkey) -> Result<Vec<(Pubkey, ClaimRecord)>, ClientError> {
+    fetch_all(rpc, &[Filter::pubkey(CLAIM_CLAIMER_OFFSET, claimer)])
+}
+
+pub fn allowlisted_collections(rpc: &RpcClient) -> Result<Vec<(Pubkey, CollectionAllowlist)>, ClientError> {
+    fetch_all(rpc, &[])
+}
+
+/// Human-readable name of a listing status.
+pub fn status_label(status: &ListingStatus) -> &'static str {
+    match status {
+        ListingStatus::Open => "open",
+        ListingStatus::Funded => "funded",
+        ListingStatus::Custodied => "custodied",
+        ListingStatus::Expired => "expired",
+        ListingStatus::Refunded => "refunded",
+        ListingStatus::Sold => "sold",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::AccountSerialize;
+
+    fn matches(filter: &Filter, data: &[u8]) -> bool {
+        match filter {
+            Filter::DataSize(size) => data.len() as u64 == *size,
+            Filter::Memcmp { offset, bytes } => data.get(*offset..offset + bytes.len()) == Some(bytes),
+        }
+    }
+
+    #[test]
+    fn listing_filters_match_serialized_layout() {
+        let listing = Listing::fixture(ListingStatus::Expired);
+        let mut data = Vec::new();
+        listing.try_serialize(&mut data).unwrap();
+
+        assert!(matches(&Filter::account::<Listing>(), &data));
+        assert!(matches(&Filter::pubkey(LISTING_NFT_MINT_OFFSET, &listing.nft_mint), &data));
+        assert!(matches(&Filter::pubkey(LISTING_SELLER_OFFSET, &listing.nft_seller), &data));
+        assert!(matches(&status_filter(&ListingStatus::Expired).unwrap(), &data));
+        assert!(!matches(&status_filter(&ListingStatus::Open).unwrap(), &data));
+        assert!(!matches(&Filter::account::<Contribution>(), &data));
+    }
+
+    #[test]
+    fn contribution_filters_match_serialized_layout() {
+        let contribution = Contribution {
+            listing: Pubkey::new_unique(),
+            wallet: Pubkey::new_unique(),
+            bps: 100,
+            principal_lamports: 1,
+            fee_lamports: 1,
+            refund_claimed: false,
+            bump: 255,
+            version: Contribution::VERSION,
+        };
+        let mut data = Vec::new();
+        contribution.try_serialize(&mut data).unwrap();
+
+        assert!(matches(&Filter::pubkey(CONTRIBUTION_LISTING_OFFSET, &contribution.listing), &data));
+        assert!(matches(&Filter::pubkey(CONTRIBUTION_WALLET_OFFSET, &contribution.wallet), &data));
+        assert_eq!(decode::<Contribution>(&data).unwrap().wallet, contribution.wallet);
+        assert!(decode::<Listing>(&data).is_err());
+    }
+}
@@ -0,0 +1,506 @@
+//! One builder per program instruction. Account lists come from the program's generated
+//! `accounts` structs and instruction data from its `instruction` structs, so the builders break
+//! at compile time whenever the program interface changes.
+//!
+//! Builders that need on-chain state (seller, creators, proposal counter, ...) take the decoded
+//! account rather than fetching it themselves.
+
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::system_program;
+use anchor_lang::{InstructionData, ToAccountMetas};
+
+use crate::pda;
+use crate::program::{
+    accounts as ix_accounts, instruction as ix_data, CollectionLimits, CreateListingArgs, Listing, PresaleProof,
+    Vote,
+};
+use crate::PROGRAM_ID;
+
+fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+fn event_authority() -> Pubkey {
+    pda::event_authority().0
+}
+
+// Creator accounts `execute_purchase` pays royalties to, in listing order.
+fn creator_metas(listing: &Listing) -> Vec<AccountMeta> {
+    if listing.royalty_bps == 0 {
+        return Vec::new();
+    }
+    listing
+        .creators
+        .iter()
+        .map(|creator| AccountMeta::new(creator.address, false))
+        .collect()
+}
+
+pub fn initialize_config(authority: &Pubkey, fee_vault: &Pubkey, custody_fee_bps: u16) -> Instruction {
+    build(
+        ix_accounts::InitializeConfig {
+            config: pda::config().0,
+            authority: *authority,
+            fee_vault: *fee_vault,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        ix_data::InitializeConfig { custody_fee_bps },
+    )
+}
+
+pub fn update_config(
+    authority: &Pubkey,
+    allowlist_enabled: Option<bool>,
+    royalties_enabled: Option<bool>,
+    keeper_bounty_lamports: Option<u64>,
+) -> Instruction {
+    build(
+        ix_accounts::UpdateConfig {
+            config: pda::config().0,
+            authority: *authority,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        ix_data::UpdateConfig { allowlist_enabled, royalties_enabled, keeper_bounty_lamports },
+    )
+}
+
+pub fn add_collection(authority: &Pubkey, collection_mint: &Pubkey, limits: CollectionLimits) -> Instruction {
+    build(
+        ix_accounts::AddCollection {
+            config: pda::config().0,
+            collection_allowlist: pda::allowlist(collection_mint).0,
+            authority: *authority,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        ix_data::AddCollection { collection_mint: *collection_mint, limits },
+    )
+}
+
+pub fn update_collection(authority: &Pubkey, collection_mint: &Pubkey, limits: CollectionLimits) -> Instruction {
+    build(
+        ix_accounts::UpdateCollection {
+            config: pda::config().0,
+            collection_allowlist: pda::allowlist(collection_mint).0,
+            authority: *authority,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        ix_data::UpdateCollection { limits },
+    )
+}
+
+pub fn remove_collection(authority: &Pubkey, collection_mint: &Pubkey) -> Instruction {
+    build(
+        ix_accounts::RemoveCollection {
+            config: pda::config().0,
+            collection_allowlist: pda::allowlist(collection_mint).0,
+            authority: *authority,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        ix_data::RemoveCollection {},
+    )
+}
+
+/// `collection` is the NFT's verified collection mint; required if the NFT has one, so that the
+/// collection's allowlist entry (if any) applies.
+pub fn create_listing(
+    seller: &Pubkey,
+    nft_mint: &Pubkey,
+    collection: Option<&Pubkey>,
+    args: CreateListingArgs,
+) -> Instruction {
+    let listing = pda::listing(nft_mint).0;
+    build(
+        ix_accounts::CreateListing {
+            config: pda::config().0,
+            listing,
+            escrow: pda::escrow(&listing).0,
+            nft_mint: *nft_mint,
+            nft_metadata: pda::metadata(nft_mint).0,
+            nft_master_edition: pda::master_edition(nft_mint).0,
+            collection_allowlist: collection.map(|collection| pda::allowlist(collection).0),
+            seller_nft_account: pda::associated_token(seller, nft_mint),
+            vault: pda::vault(nft_mint).0,
+            seller: *seller,
+            token_program: anchor_spl::token::ID,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        ix_data::CreateListing { args },
+    )
+}
+
+pub fn contribute(
+    listing: &Pubkey,
+    contributor: &Pubkey,
+    bps: u16,
+    presale_proof: Option<PresaleProof>,
+) -> Instruction {
+    build(
+        ix_accounts::Contribute {
+            listing: *listing,
+            escrow: pda::escrow(listing).0,
+            contribution: pda::contribution(listing, contributor).0,
+            contributor: *contributor,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        ix_data::Contribute { bps, presale_proof },
+    )
+}
+
+/// Contributes and, if this completes the raise, settles the purchase in the same instruction.
+/// A seller who retains bps must use `contribute` and `execute_purchase` instead.
+pub fn contribute_and_execute(
+    listing_key: &Pubkey,
+    listing: &Listing,
+    fee_vault: &Pubkey,
+    contributor: &Pubkey,
+    bps: u16,
+    presale_proof: Option<PresaleProof>,
+) -> Instruction {
+    let mut ix = build(
+        ix_accounts::ContributeAndExecute {
+            config: pda::config().0,
+            listing: *listing_key,
+            escrow: pda::escrow(listing_key).0,
+            contribution: pda::contribution(listing_key, contributor).0,
+            contributor: *contributor,
+            seller: listing.nft_seller,
+            fee_vault: *fee_vault,
+            seller_contribution: (listing.retained_bps > 0)
+                .then(|| pda::contribution(listing_key, &listing.nft_seller).0),
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        ix_data::ContributeAndExecute { bps, presale_proof },
+    );
+    ix.accounts.extend(creator_metas(listing));
+    ix
+}
+
+pub fn execute_purchase(listing_key: &Pubkey, listing: &Listing, fee_vault: &Pubkey, executor: &Pubkey) -> Instruction {
+    let mut ix = build(
+        ix_accounts::ExecutePurchase {
+            config: pda::config().0,
+            listing: *listing_key,
+            escrow: pda::escrow(listing_key).0,
+            nft_mint: listing.nft_mint,
+            seller: listing.nft_seller,
+            fee_vault: *fee_vault,
+            seller_contribution: (listing.bps_sold < 10000)
+                .then(|| pda::contribution(listing_key, &listing.nft_seller).0),
+            executor: *executor,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        ix_data::ExecutePurchase {},
+    );
+    ix.accounts.extend(creator_metas(listing));
+    ix
+}
+
+pub fn process_refund(listing: &Pubkey, contributor: &Pubkey) -> Instruction {
+    build(
+        ix_accounts::ProcessRefund {
+            listing: *listing,
+            escrow: pda::escrow(listing).0,
+            contribution: pda::contribution(listing, contributor).0,
+            contributor: *contributor,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        ix_data::ProcessRefund {},
+    )
+}
+
+/// Contribution/wallet pairs per `crank_refunds` transaction, well inside the transaction size limit.
+pub const CRANK_REFUNDS_BATCH: usize = 10;
+
+/// Refunds each of `wallets` (contributors of `listing`) to their own address.
+pub fn crank_refunds(listing: &Pubkey, cranker: &Pubkey, wallets: &[Pubkey]) -> Instruction {
+    let mut ix = build(
+        ix_accounts::CrankRefunds {
+            config: pda::config().0,
+            listing: *listing,
+            escrow: pda::escrow(listing).0,
+            cranker: *cranker,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        ix_data::CrankRefunds {},
+    );
+    for wallet in wallets {
+        ix.accounts.push(AccountMeta::new(pda::contribution(listing, wallet).0, false));
+        ix.accounts.push(AccountMeta::new(*wallet, false));
+    }
+    ix
+}
+
+pub fn claim_auction_rebate(listing: &Pubkey, contributor: &Pubkey) -> Instruction {
+    build(
+        ix_accounts::ClaimAuctionRebate {
+            listing: *listing,
+            escrow: pda::escrow(listing).0,
+            contribution: pda::contribution(listing, contributor).0,
+            contributor: *contributor,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        ix_data::ClaimAuctionRebate {},
+    )
+}
+
+/// Returns the NFT to the seller's associated token account.
+pub fn reclaim_nft(listing_key: &Pubkey, listing: &Listing) -> Instruction {
+    build(
+        ix_accounts::ReclaimNft {
+            config: pda::config().0,
+            listing: *listing_key,
+            nft_mint: listing.nft_mint,
+            vault: listing.vault,
+            seller_nft_account: pda::associated_token(&listing.nft_seller, &listing.nft_mint),
+            token_program: anchor_spl::token::ID,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        ix_data::ReclaimNft {},
+    )
+}
+
+pub fn close_listing(listing_key: &Pubkey, listing: &Listing) -> Instruction {
+    build(
+        ix_accounts::CloseListing {
+            listing: *listing_key,
+            escrow: pda::escrow(listing_key).0,
+            vault: listing.vault,
+            seller: listing.nft_seller,
+            token_program: anchor_spl::token::ID,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        ix_data::CloseListing {},
+    )
+}
+
+/// Grows a listing created before account versioning to the current size; `payer` covers the rent.
+pub fn migrate_listing(listing: &Pubkey, payer: &Pubkey) -> Instruction {
+    build(
+        ix_accounts::MigrateListing {
+            listing: *listing,
+            payer: *payer,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        ix_data::MigrateListing {},
+    )
+}
+
+/// Grows any other program account created before it carried a version; `payer` covers the rent.
+pub fn migrate_account(account: &Pubkey, payer: &Pubkey) -> Instruction {
+    build(
+        ix_accounts::MigrateAccount {
+            account: *account,
+            payer: *payer,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        ix_data::MigrateAccount {},
+    )
+}
+
+/// Creates proposal number `listing.proposal_count`.
+pub fn create_proposal(
+    listing_key: &Pubkey,
+    listing: &Listing,
+    proposer: &Pubkey,
+    sale_price_lamports: u64,
+    vote_deadline_offset: i64,
+) -> Instruction {
+    build(
+        ix_accounts::CreateProposal {
+            listing: *listing_key,
+            contribution: pda::contribution(listing_key, proposer).0,
+            proposal: pda::proposal(listing_key, listing.proposal_count).0,
+            proposer: *proposer,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        ix_data::CreateProposal { sale_price_lamports, vote_deadline_offset },
+    )
+}
+
+pub fn cast_vote(listing: &Pubkey, proposal_id: u32, voter: &Pubkey, vote: Vote) -> Instruction {
+    let proposal = pda::proposal(listing, proposal_id).0;
+    build(
+        ix_accounts::CastVote {
+            listing: *listing,
+            proposal,
+            contribution: pda::contribution(listing, voter).0,
+            vote_record: pda::vote(&proposal, voter).0,
+            voter: *voter,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        ix_data::CastVote { vote },
+    )
+}
+
+pub fn finalize_proposal(listing: &Pubkey, proposal_id: u32, keeper: &Pubkey) -> Instruction {
+    build(
+        ix_accounts::FinalizeProposal {
+            config: pda::config().0,
+            listing: *listing,
+            escrow: pda::escrow(listing).0,
+            proposal: pda::proposal(listing, proposal_id).0,
+            keeper: *keeper,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        ix_data::FinalizeProposal {},
+    )
+}
+
+/// Deposits `amount` of `reward_mint` from the depositor's associated token account.
+pub fn register_reward(listing: &Pubkey, reward_mint: &Pubkey, depositor: &Pubkey, amount: u64) -> Instruction {
+    let reward_registry = pda::reward(listing, reward_mint).0;
+    build(
+        ix_accounts::RegisterReward {
+            listing: *listing,
+            reward_mint: *reward_mint,
+            reward_registry,
+            reward_vault: pda::associated_token(&reward_registry, reward_mint),
+            depositor_token_account: pda::associated_token(depositor, reward_mint),
+            depositor: *depositor,
+            token_program: anchor_spl::token::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        ix_data::RegisterReward { amount },
+    )
+}
+
+/// Claims into the claimer's associated token account.
+pub fn claim_reward(listing: &Pubkey, reward_mint: &Pubkey, claimer: &Pubkey) -> Instruction {
+    let reward_registry = pda::reward(listing, reward_mint).0;
+    build(
+        ix_accounts::ClaimReward {
+            listing: *listing,
+            contribution: pda::contribution(listing, claimer).0,
+            reward_mint: *reward_mint,
+            reward_registry,
+            reward_vault: pda::associated_token(&reward_registry, reward_mint),
+            claimer_token_account: pda::associated_token(claimer, reward_mint),
+            claimer: *claimer,
+            claim_record: pda::claim(&reward_registry, claimer).0,
+            token_program: anchor_spl::token::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        ix_data::ClaimReward {},
+    )
+}
+
+// Views: simulate these with `views::simulate` rather than sending them.
+
+pub fn quote_contribution(
+    listing: &Pubkey,
+    contributor: &Pubkey,
+    bps: u16,
+    presale_proof: Option<PresaleProof>,
+) -> Instruction {
+    build(
+        ix_accounts::QuoteContribution {
+            listing: *listing,
+            contributor: *contributor,
+            contribution: pda::contribution(listing, contributor).0,
+        },
+        ix_data::QuoteContribution { bps, presale_proof },
+    )
+}
+
+pub fn quote_refund(listing: &Pubkey, wallet: &Pubkey) -> Instruction {
+    build(
+        ix_accounts::QuoteRefund { listing: *listing, contribution: pda::contribution(listing, wallet).0 },
+        ix_data::QuoteRefund {},
+    )
+}
+
+pub fn quote_claimable_reward(listing: &Pubkey, reward_mint: &Pubkey, wallet: &Pubkey) -> Instruction {
+    let reward_registry = pda::reward(listing, reward_mint).0;
+    build(
+        ix_accounts::QuoteClaimableReward {
+            listing: *listing,
+            contribution: pda::contribution(listing, wallet).0,
+            reward_registry,
+            claim_record: pda::claim(&reward_registry, wallet).0,
+        },
+        ix_data::QuoteClaimableReward {},
+    )
+}
+
+pub fn listing_summary(listing: &Pubkey) -> Instruction {
+    build(
+        ix_accounts::ListingSummaryView { listing: *listing, escrow: pda::escrow(listing).0 },
+        ix_data::ListingSummary {},
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+
+    #[test]
+    fn crank_refunds_appends_contribution_wallet_pairs() {
+        let listing = Pubkey::new_unique();
+        let wallets = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let ix = crank_refunds(&listing, &Pubkey::new_unique(), &wallets);
+
+        assert_eq!(&ix.data[..8], ix_data::CrankRefunds::DISCRIMINATOR);
+        let pairs = &ix.accounts[ix.accounts.len() - 4..];
+        assert_eq!(pairs[0].pubkey, pda::contribution(&listing, &wallets[0]).0);
+        assert_eq!(pairs[1].pubkey, wallets[0]);
+        assert!(pairs.iter().all(|meta| meta.is_writable && !meta.is_signer));
+    }
+
+    #[test]
+    fn contribute_signs_with_contributor_and_carries_event_accounts() {
+        let (listing, contributor) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let ix = contribute(&listing, &contributor, 250, None);
+
+        assert_eq!(ix.program_id, PROGRAM_ID);
+        assert!(ix.accounts.iter().any(|meta| meta.pubkey == contributor && meta.is_signer));
+        assert!(ix.accounts.iter().any(|meta| meta.pubkey == pda::event_authority().0));
+        assert_eq!(ix.data, ix_data::Contribute { bps: 250, presale_proof: None }.data());
+    }
+}
@@ -0,0 +1,42 @@
+//! Rust client for the FracVault program.
+//!
+//! - [`pda`]: typed PDA derivation for every program seed
+//! - [`instructions`]: one builder per program instruction
+//! - [`accounts`]: account decoding and `getProgramAccounts` queries
+//! - [`lifecycle`]: the actions currently allowed on a listing
+//! - [`views`]: simulated read-only instructions returning program-computed quotes
+//! - [`rpc`]: a minimal blocking JSON-RPC client to send transactions and read accounts
+//!
+//! Program types (`Listing`, `Contribution`, instruction argument structs, ...) are re-exported
+//! from the program crate, built with `no-entrypoint`.
+
+pub mod accounts;
+pub mod instructions;
+pub mod lifecycle;
+pub mod pda;
+pub mod rpc;
+pub mod views;
+
+pub use anchor_lang::prelude::Pubkey;
+pub use anchor_lang::solana_program::instruction::Instruction;
+pub use solana_keypair::{read_keypair_file, Keypair};
+pub use solana_signature::Signature;
+pub use solana_signer::Signer;
+pub use workspace as program;
+pub use workspace::ID as PROGRAM_ID;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("transport error: {0}")]
+    Transport(String),
+    #[error("rpc error {code}: {message}")]
+    Rpc { code: i64, message: String, logs: Vec<String> },
+    #[error("decode error: {0}")]
+    Decode(String),
+    #[error("account not found: {0}")]
+    AccountNotFound(Pubkey),
+    #[error("transaction error: {0}")]
+    Transaction(String),
+    #[error("simulation failed: {message}")]
+    Simulation { message: String, logs: Vec<String> },
+}
@@ -0,0 +1,137 @@
+//! What can be done with a listing right now. Mirrors the program's status and clock checks so
+//! tools can tell operators which instruction would currently succeed.
+
+use crate::program::{Listing, ListingStatus};
+
+/// Contributions close this many seconds before the deadline.
+pub const CONTRIBUTION_CUTOFF_SECONDS: i64 = 60;
+
+/// Length of the window in which an executable listing can be executed.
+pub const EXECUTION_WINDOW_SECONDS: i64 = 86400;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NextAction {
+    /// `contribute` until `closes_at`; only allowlisted wallets until `presale_ends_at`.
+    Contribute { closes_at: i64, presale_ends_at: Option<i64> },
+    /// Nothing is allowed until `until`, when the listing becomes executable or refundable.
+    Wait { until: i64 },
+    /// `execute_purchase` until `window_ends_at`.
+    Execute { window_ends_at: i64 },
+    /// `process_refund` / `crank_refunds` for the `outstanding_bps` not yet paid back.
+    Refund { outstanding_bps: u16 },
+    /// `reclaim_nft` returns the NFT to the seller.
+    ReclaimNft,
+    /// `close_listing` returns the listing's rent to the seller.
+    Close,
+    /// The NFT is in custody: proposals, votes and rewards.
+    Govern,
+}
+
+/// Every action allowed on `listing` at cluster time `now`; empty once the listing is sold.
+pub fn next_actions(listing: &Listing, now: i64) -> Vec<NextAction> {
+    let mut actions = Vec::new();
+    match listing.status {
+        ListingStatus::Open | ListingStatus::Funded => {
+            let closes_at = listing.deadline - CONTRIBUTION_CUTOFF_SECONDS;
+            if listing.status == ListingStatus::Open && now < closes_at {
+                let presale_ends_at = listing
+                    .presale
+                    .as_ref()
+                    .map(|presale| presale.end_time)
+                    .filter(|end_time| now < *end_time);
+                actions.push(NextAction::Contribute { closes_at, presale_ends_at });
+            } else if let Some(window_start) = listing.execution_window_start() {
+                let window_ends_at = window_start + EXECUTION_WINDOW_SECONDS;
+                if now < window_start {
+                    actions.push(NextAction::Wait { until: window_start });
+                } else if now <= window_ends_at {
+                    actions.push(NextAction::Execute { window_ends_at });
+                }
+            } else if now < listing.deadline {
+                actions.push(NextAction::Wait { until: listing.deadline });
+            }
+        }
+        ListingStatus::Custodied => actions.push(NextAction::Govern),
+        ListingStatus::Refunded => actions.push(NextAction::Close),
+        ListingStatus::Expired | ListingStatus::Sold => {}
+    }
+
+    // Refunds and reclaiming share the program's refund conditions.
+    if listing.is_refundable(now) {
+        let outstanding_bps = listing.bps_sold.saturating_sub(listing.refunded_bps);
+        if outstanding_bps > 0 {
+            actions.push(NextAction::Refund { outstanding_bps });
+        }
+        if !listing.nft_reclaimed {
+            actions.push(NextAction::ReclaimNft);
+        }
+    }
+    actions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::program::Presale;
+
+    const DEADLINE: i64 = 1_000_000;
+
+    fn listing(status: ListingStatus, bps_sold: u16) -> Listing {
+        Listing { bps_sold, deadline: DEADLINE, ..Listing::fixture(status) }
+    }
+
+    #[test]
+    fn open_listing_takes_contributions_until_cutoff() {
+        let mut open = listing(ListingStatus::Open, 2500);
+        open.presale = Some(Presale { merkle_root: [0; 32], end_time: DEADLINE - 5000 });
+
+        assert_eq!(
+            next_actions(&open, DEADLINE - 6000),
+            vec![NextAction::Contribute { closes_at: DEADLINE - 60, presale_ends_at: Some(DEADLINE - 5000) }]
+        );
+        assert_eq!(
+            next_actions(&open, DEADLINE - 1000),
+            vec![NextAction::Contribute { closes_at: DEADLINE - 60, presale_ends_at: None }]
+        );
+        assert_eq!(next_actions(&open, DEADLINE - 30), vec![NextAction::Wait { until: DEADLINE }]);
+        assert_eq!(
+            next_actions(&open, DEADLINE),
+            vec![NextAction::Refund { outstanding_bps: 2500 }, NextAction::ReclaimNft]
+        );
+    }
+
+    #[test]
+    fn executable_listings_open_a_window_then_refund() {
+        let mut funded = listing(ListingStatus::Funded, 10000);
+        funded.funded_at = DEADLINE - 500;
+        let window_ends_at = funded.funded_at + EXECUTION_WINDOW_SECONDS;
+        assert_eq!(next_actions(&funded, DEADLINE), vec![NextAction::Execute { window_ends_at }]);
+        assert_eq!(
+            next_actions(&funded, window_ends_at + 1),
+            vec![NextAction::Refund { outstanding_bps: 10000 }, NextAction::ReclaimNft]
+        );
+
+        let mut partial = listing(ListingStatus::Open, 9600);
+        partial.min_fill_bps = 9500;
+        assert_eq!(next_actions(&partial, DEADLINE - 30), vec![NextAction::Wait { until: DEADLINE }]);
+        assert_eq!(
+            next_actions(&partial, DEADLINE + 10),
+            vec![NextAction::Execute { window_ends_at: DEADLINE + EXECUTION_WINDOW_SECONDS }]
+        );
+    }
+
+    #[test]
+    fn expired_listing_winds_down_to_close() {
+        let mut expired = listing(ListingStatus::Expired, 4000);
+        expired.refunded_bps = 4000;
+        assert_eq!(next_actions(&expired, DEADLINE), vec![NextAction::ReclaimNft]);
+
+        expired.refunded_bps = 1000;
+        expired.nft_reclaimed = true;
+        assert_eq!(next_actions(&expired, DEADLINE), vec![NextAction::Refund { outstanding_bps: 3000 }]);
+
+        assert_eq!(next_actions(&listing(ListingStatus::Refunded, 4000), DEADLINE), vec![NextAction::Close]);
+        assert_eq!(next_actions(&listing(ListingStatus::Custodied, 10000), DEADLINE), vec![NextAction::Govern]);
+        assert!(next_actions(&listing(ListingStatus::Sold, 10000), DEADLINE).is_empty());
+    }
+}
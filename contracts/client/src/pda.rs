@@ -0,0 +1,107 @@
+//! PDA derivation for every seed used by the program. Each function returns the address and its
+//! canonical bump, exactly as `Pubkey::find_program_address` would.
+
+use anchor_lang::prelude::Pubkey;
+use mpl_token_metadata::accounts::{MasterEdition, Metadata};
+
+use crate::PROGRAM_ID;
+
+fn find(seeds: &[&[u8]]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(seeds, &PROGRAM_ID)
+}
+
+/// `[b"config"]`
+pub fn config() -> (Pubkey, u8) {
+    find(&[b"config"])
+}
+
+/// `[b"allowlist", collection_mint]`
+pub fn allowlist(collection_mint: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"allowlist", collection_mint.as_ref()])
+}
+
+/// `[b"listing", nft_mint]`
+pub fn listing(nft_mint: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"listing", nft_mint.as_ref()])
+}
+
+/// `[b"escrow", listing]`
+pub fn escrow(listing: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"escrow", listing.as_ref()])
+}
+
+/// `[b"vault", nft_mint]`
+pub fn vault(nft_mint: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"vault", nft_mint.as_ref()])
+}
+
+/// `[b"contribution", listing, wallet]`
+pub fn contribution(listing: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"contribution", listing.as_ref(), wallet.as_ref()])
+}
+
+/// `[b"proposal", listing, proposal_id_le]`
+pub fn proposal(listing: &Pubkey, proposal_id: u32) -> (Pubkey, u8) {
+    find(&[b"proposal", listing.as_ref(), &proposal_id.to_le_bytes()])
+}
+
+/// `[b"vote", proposal, voter]`
+pub fn vote(proposal: &Pubkey, voter: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"vote", proposal.as_ref(), voter.as_ref()])
+}
+
+/// `[b"reward", listing, reward_mint]`
+pub fn reward(listing: &Pubkey, reward_mint: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"reward", listing.as_ref(), reward_mint.as_ref()])
+}
+
+/// `[b"claim", reward_registry, claimer]`
+pub fn claim(reward_registry: &Pubkey, claimer: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"claim", reward_registry.as_ref(), claimer.as_ref()])
+}
+
+/// `[b"__event_authority"]`, the signer of `emit_cpi!` self-invocations.
+pub fn event_authority() -> (Pubkey, u8) {
+    find(&[b"__event_authority"])
+}
+
+/// Token Metadata account of `mint`.
+pub fn metadata(mint: &Pubkey) -> (Pubkey, u8) {
+    Metadata::find_pda(mint)
+}
+
+/// Master edition account of `mint`.
+pub fn master_edition(mint: &Pubkey) -> (Pubkey, u8) {
+    MasterEdition::find_pda(mint)
+}
+
+/// Associated token account of `wallet` for `mint` under the legacy token program.
+pub fn associated_token(wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
+    anchor_spl::associated_token::get_associated_token_address(wallet, mint)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeds_match_program_constraints() {
+        let mint = Pubkey::new_unique();
+        let wallet = Pubkey::new_unique();
+        let (listing, _) = listing(&mint);
+
+        assert_eq!(
+            contribution(&listing, &wallet).0,
+            Pubkey::find_program_address(
+                &[b"contribution", listing.as_ref(), wallet.as_ref()],
+                &PROGRAM_ID
+            )
+            .0
+        );
+        assert_eq!(
+            proposal(&listing, 7).0,
+            Pubkey::find_program_address(&[b"proposal", listing.as_ref(), &[7, 0, 0, 0]], &PROGRAM_ID).0
+        );
+        assert_ne!(escrow(&listing).0, vault(&mint).0);
+    }
+}
@@ -0,0 +1,363 @@
+//! Minimal blocking JSON-RPC client covering what the FracVault tools need.
+
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::hash::Hash;
+use anchor_lang::solana_program::instruction::Instruction;
+use base64::Engine;
+use serde_json::{json, Value};
+use solana_keypair::Keypair;
+use solana_signature::Signature;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+
+use crate::accounts::Filter;
+use crate::ClientError;
+
+pub const DEFAULT_URL: &str = "http://127.0.0.1:8899";
+
+const CLOCK_SYSVAR: Pubkey = anchor_lang::solana_program::sysvar::clock::ID;
+
+/// An account as returned by the node.
+#[derive(Clone, Debug)]
+pub struct RpcAccount {
+    pub lamports: u64,
+    pub owner: Pubkey,
+    pub data: Vec<u8>,
+}
+
+/// Return data set by the last program that called `set_return_data` in a simulation.
+#[derive(Clone, Debug)]
+pub struct ReturnData {
+    pub program_id: Pubkey,
+    pub data: Vec<u8>,
+}
+
+/// The parts of the `Clock` sysvar the tools care about.
+#[derive(Clone, Copy, Debug)]
+pub struct ClusterClock {
+    pub slot: u64,
+    pub unix_timestamp: i64,
+}
+
+pub struct RpcClient {
+    url: String,
+    commitment: String,
+    agent: ureq::Agent,
+}
+
+impl RpcClient {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self::with_commitment(url, "confirmed")
+    }
+
+    pub fn with_commitment(url: impl Into<String>, commitment: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            commitment: commitment.into(),
+            agent: ureq::AgentBuilder::new().timeout(Duration::from_secs(30)).build(),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn commitment(&self) -> &str {
+        &self.commitment
+    }
+
+    /// Sends a raw JSON-RPC request and returns its `result`.
+    pub fn request(&self, method: &str, params: Value) -> Result<Value, ClientError> {
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response: Value = self
+            .agent
+            .post(&self.url)
+            .send_json(body)
+            .map_err(|e| ClientError::Transport(e.to_string()))?
+            .into_json()
+            .map_err(|e| ClientError::Transport(e.to_string()))?;
+
+        if let Some(error) = response.get("error") {
+            let logs = error
+                .pointer("/data/logs")
+                .and_then(Value::as_array)
+                .map(|logs| logs.iter().filter_map(Value::as_str).map(str::to_owned).collect())
+                .unwrap_or_default();
+            return Err(ClientError::Rpc {
+                code: error.get("code").and_then(Value::as_i64).unwrap_or_default(),
+                message: error.get("message").and_then(Value::as_str).unwrap_or_default().to_owned(),
+                logs,
+            });
+        }
+        response
+            .get("result")
+            .cloned()
+            .ok_or_else(|| ClientError::Transport(format!("{method}: response has no result")))
+    }
+
+    pub fn get_account(&self, address: &Pubkey) -> Result<Option<RpcAccount>, ClientError> {
+        let result = self.request(
+            "getAccountInfo",
+            json!([address.to_string(), { "encoding": "base64", "commitment": self.commitment }]),
+        )?;
+        match result.get("value") {
+            None | Some(Value::Null) => Ok(None),
+            Some(value) => parse_account(value).map(Some),
+        }
+    }
+
+    pub fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        filters: &[Filter],
+    ) -> Result<Vec<(Pubkey, RpcAccount)>, ClientError> {
+        let filters: Vec<Value> = filters.iter().map(Filter::to_json).collect();
+        let result = self.request(
+            "getProgramAccounts",
+            json!([
+                program_id.to_string(),
+                { "encoding": "base64", "commitment": self.commitment, "filters": filters }
+            ]),
+        )?;
+        result
+            .as_array()
+            .ok_or_else(|| ClientError::Decode("getProgramAccounts: expected an array".into()))?
+            .iter()
+            .map(|entry| {
+                let address = parse_pubkey(entry.get("pubkey"))?;
+                let account = parse_account(entry.get("account").unwrap_or(&Value::Null))?;
+                Ok((address, account))
+            })
+            .collect()
+    }
+
+    pub fn get_balance(&self, address: &Pubkey) -> Result<u64, ClientError> {
+        let result = self.request(
+            "getBalance",
+            json!([address.to_string(), { "commitment": self.commitment }]),
+        )?;
+        result
+            .get("value")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| ClientError::Decode("getBalance: missing value".into()))
+    }
+
+    pub fn get_latest_blockhash(&self) -> Result<Hash, ClientError> {
+        let result = self.request("getLatestBlockhash", json!([{ "commitment": self.commitment }]))?;
+        let blockhash = result
+            .pointer("/value/blockhash")
+            .and_then(Value::as_str)
+            .ok_or_else(|| ClientError::Decode("getLatestBlockhash: missing blockhash".into()))?;
+        Hash::from_str(blockhash).map_err(|e| ClientError::Decode(e.to_string()))
+    }
+
+    /// Current slot and cluster time, read from the `Clock` sysvar.
+    pub fn get_clock(&self) -> Result<ClusterClock, ClientError> {
+        let account = self
+            .get_account(&CLOCK_SYSVAR)?
+            .ok_or(ClientError::AccountNotFound(CLOCK_SYSVAR))?;
+        // slot: u64, epoch_start_timestamp: i64, epoch: u64, leader_schedule_epoch: u64,
+        // unix_timestamp: i64
+        let field = |offset: usize| -> Result<[u8; 8], ClientError> {
+            account
+                .data
+                .get(offset..offset + 8)
+                .and_then(|bytes| bytes.try_into().ok())
+                .ok_or_else(|| ClientError::Decode("clock sysvar too short".into()))
+        };
+        Ok(ClusterClock {
+            slot: u64::from_le_bytes(field(0)?),
+            unix_timestamp: i64::from_le_bytes(field(32)?),
+        })
+    }
+
+    pub fn send_transaction(&self, transaction: &Transaction) -> Result<Signature, ClientError> {
+        let wire = bincode::serialize(transaction).map_err(|e| ClientError::Transaction(e.to_string()))?;
+        let result = self.request(
+            "sendTransaction",
+            json!([
+                base64::engine::general_purpose::STANDARD.encode(wire),
+                { "encoding": "base64", "preflightCommitment": self.commitment }
+            ]),
+        )?;
+        parse_signature(&result)
+    }
+
+    /// Simulates `instructions` paid by `payer` without signing them; the node substitutes a
+    /// recent blockhash. Fails with the program logs if the simulated transaction fails.
+    pub fn simulate(&self, instructions: &[Instruction], payer: &Pubkey) -> Result<Option<ReturnData>, ClientError> {
+        let transaction = Transaction::new_with_payer(instructions, Some(payer));
+        let wire = bincode::serialize(&transaction).map_err(|e| ClientError::Transaction(e.to_string()))?;
+        let result = self.request(
+            "simulateTransaction",
+            json!([
+                base64::engine::general_purpose::STANDARD.encode(wire),
+                {
+                    "encoding": "base64",
+                    "commitment": self.commitment,
+                    "sigVerify": false,
+                    "replaceRecentBlockhash": true,
+                }
+            ]),
+        )?;
+        parse_simulation(result.get("value").unwrap_or(&Value::Null))
+    }
+
+    /// Polls until `signature` reaches this client's commitment, or fails with its error.
+    pub fn confirm_transaction(&self, signature: &Signature, timeout: Duration) -> Result<(), ClientError> {
+        let started = Instant::now();
+        loop {
+            let result = self.request(
+                "getSignatureStatuses",
+                json!([[signature.to_string()], { "searchTransactionHistory": true }]),
+            )?;
+            if let Some(status) = result.pointer("/value/0").filter(|status| !status.is_null()) {
+                if let Some(err) = status.get("err").filter(|err| !err.is_null()) {
+                    return Err(ClientError::Transaction(format!("{signature} failed: {err}")));
+                }
+                let level = status.get("confirmationStatus").and_then(Value::as_str).unwrap_or_default();
+                if commitment_reached(level, &self.commitment) {
+                    return Ok(());
+                }
+            }
+            if started.elapsed() > timeout {
+                return Err(ClientError::Transaction(format!("{signature} not confirmed after {timeout:?}")));
+            }
+            thread::sleep(Duration::from_millis(400));
+        }
+    }
+
+    /// Signs `instructions` with `payer` (plus any extra signers), sends and confirms them.
+    pub fn send_and_confirm(
+        &self,
+        instructions: &[Instruction],
+        payer: &Keypair,
+        extra_signers: &[&Keypair],
+    ) -> Result<Signature, ClientError> {
+        let mut signers: Vec<&Keypair> = vec![payer];
+        signers.extend_from_slice(extra_signers);
+        let blockhash = self.get_latest_blockhash()?;
+        let transaction =
+            Transaction::new_signed_with_payer(instructions, Some(&payer.pubkey()), &signers, blockhash);
+        let signature = self.send_transaction(&transaction)?;
+        self.confirm_transaction(&signature, Duration::from_secs(60))?;
+        Ok(signature)
+    }
+
+    /// Local validators and devnet only.
+    pub fn request_airdrop(&self, address: &Pubkey, lamports: u64) -> Result<Signature, ClientError> {
+        let result = self.request("requestAirdrop", json!([address.to_string(), lamports]))?;
+        parse_signature(&result)
+    }
+}
+
+fn commitment_reached(level: &str, wanted: &str) -> bool {
+    let rank = |level: &str| match level {
+        "processed" => 0,
+        "confirmed" => 1,
+        "finalized" => 2,
+        _ => -1,
+    };
+    rank(level) >= rank(wanted)
+}
+
+fn parse_pubkey(value: Option<&Value>) -> Result<Pubkey, ClientError> {
+    let text = value
+        .and_then(Value::as_str)
+        .ok_or_else(|| ClientError::Decode("expected a base58 pubkey".into()))?;
+    Pubkey::from_str(text).map_err(|e| ClientError::Decode(e.to_string()))
+}
+
+fn parse_signature(value: &Value) -> Result<Signature, ClientError> {
+    let text = value
+        .as_str()
+        .ok_or_else(|| ClientError::Decode("expected a base58 signature".into()))?;
+    Signature::from_str(text).map_err(|e| ClientError::Decode(e.to_string()))
+}
+
+fn parse_account(value: &Value) -> Result<RpcAccount, ClientError> {
+    let lamports = value
+        .get("lamports")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| ClientError::Decode("account: missing lamports".into()))?;
+    let owner = parse_pubkey(value.get("owner"))?;
+    let encoded = value
+        .pointer("/data/0")
+        .and_then(Value::as_str)
+        .ok_or_else(|| ClientError::Decode("account: expected base64 data".into()))?;
+    let data = base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|e| ClientError::Decode(e.to_string()))?;
+    Ok(RpcAccount { lamports, owner, data })
+}
+
+fn parse_simulation(value: &Value) -> Result<Option<ReturnData>, ClientError> {
+    if let Some(err) = value.get("err").filter(|err| !err.is_null()) {
+        let logs = value
+            .get("logs")
+            .and_then(Value::as_array)
+            .map(|logs| logs.iter().filter_map(Value::as_str).map(str::to_owned).collect())
+            .unwrap_or_default();
+        return Err(ClientError::Simulation { message: err.to_string(), logs });
+    }
+    let Some(return_data) = value.get("returnData").filter(|data| !data.is_null()) else {
+        return Ok(None);
+    };
+    let encoded = return_data
+        .pointer("/data/0")
+        .and_then(Value::as_str)
+        .ok_or_else(|| ClientError::Decode("returnData: expected base64 data".into()))?;
+    Ok(Some(ReturnData {
+        program_id: parse_pubkey(return_data.get("programId"))?,
+        data: base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|e| ClientError::Decode(e.to_string()))?,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_base64_account() {
+        let owner = Pubkey::new_unique();
+        let value = json!({
+            "lamports": 42,
+            "owner": owner.to_string(),
+            "data": ["AQID", "base64"],
+            "executable": false,
+        });
+        let account = parse_account(&value).unwrap();
+        assert_eq!((account.lamports, account.owner, account.data), (42, owner, vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn parses_simulation_return_data_and_failures() {
+        let program_id = Pubkey::new_unique();
+        let value = json!({
+            "err": null,
+            "logs": [],
+            "returnData": { "programId": program_id.to_string(), "data": ["AQID", "base64"] },
+        });
+        let return_data = parse_simulation(&value).unwrap().unwrap();
+        assert_eq!((return_data.program_id, return_data.data), (program_id, vec![1, 2, 3]));
+
+        let value = json!({ "err": { "InstructionError": [0, { "Custom": 6000 }] }, "logs": ["failed"] });
+        match parse_simulation(&value) {
+            Err(ClientError::Simulation { logs, .. }) => assert_eq!(logs, ["failed"]),
+            other => panic!("expected a simulation error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn commitment_levels_are_ordered() {
+        assert!(commitment_reached("finalized", "confirmed"));
+        assert!(commitment_reached("confirmed", "confirmed"));
+        assert!(!commitment_reached("processed", "confirmed"));
+    }
+}
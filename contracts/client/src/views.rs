@@ -0,0 +1,78 @@
+//! Read-only program instructions. Each one is simulated, never sent: the program computes the
+//! value from on-chain state and returns it with `set_return_data`, so clients do not have to
+//! reproduce its pricing, refund or reward math.
+//!
+//! The simulated transaction needs a fee payer that exists on the cluster; any funded wallet
+//! works and nothing is signed.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::AnchorDeserialize;
+
+use crate::instructions;
+use crate::program::{ContributionQuote, ListingSummary, PresaleProof, RefundQuote, RewardQuote};
+use crate::rpc::{ReturnData, RpcClient};
+use crate::{ClientError, PROGRAM_ID};
+
+/// Simulates a view instruction and decodes the value it returned.
+pub fn simulate<T: AnchorDeserialize>(rpc: &RpcClient, payer: &Pubkey, instruction: Instruction) -> Result<T, ClientError> {
+    decode_return(rpc.simulate(&[instruction], payer)?)
+}
+
+pub fn decode_return<T: AnchorDeserialize>(return_data: Option<ReturnData>) -> Result<T, ClientError> {
+    let return_data = return_data.ok_or_else(|| ClientError::Decode("view returned no data".into()))?;
+    if return_data.program_id != PROGRAM_ID {
+        return Err(ClientError::Decode(format!("return data set by {}", return_data.program_id)));
+    }
+    T::try_from_slice(&return_data.data).map_err(|e| ClientError::Decode(e.to_string()))
+}
+
+/// Principal, custody fee share and total for `contributor` contributing `bps` now; fails as
+/// `contribute` would.
+pub fn quote_contribution(
+    rpc: &RpcClient,
+    payer: &Pubkey,
+    listing: &Pubkey,
+    contributor: &Pubkey,
+    bps: u16,
+    presale_proof: Option<PresaleProof>,
+) -> Result<ContributionQuote, ClientError> {
+    simulate(rpc, payer, instructions::quote_contribution(listing, contributor, bps, presale_proof))
+}
+
+pub fn quote_refund(rpc: &RpcClient, payer: &Pubkey, listing: &Pubkey, wallet: &Pubkey) -> Result<RefundQuote, ClientError> {
+    simulate(rpc, payer, instructions::quote_refund(listing, wallet))
+}
+
+pub fn quote_claimable_reward(
+    rpc: &RpcClient,
+    payer: &Pubkey,
+    listing: &Pubkey,
+    reward_mint: &Pubkey,
+    wallet: &Pubkey,
+) -> Result<RewardQuote, ClientError> {
+    simulate(rpc, payer, instructions::quote_claimable_reward(listing, reward_mint, wallet))
+}
+
+pub fn listing_summary(rpc: &RpcClient, payer: &Pubkey, listing: &Pubkey) -> Result<ListingSummary, ClientError> {
+    simulate(rpc, payer, instructions::listing_summary(listing))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::AnchorSerialize;
+
+    #[test]
+    fn decodes_program_return_data_only() {
+        let quote = RewardQuote { bps: 2500, total_amount: 1_000, claimed_amount: 100, claimable_amount: 150 };
+        let data = quote.try_to_vec().unwrap();
+
+        let decoded: RewardQuote = decode_return(Some(ReturnData { program_id: PROGRAM_ID, data: data.clone() })).unwrap();
+        assert!(decoded == quote);
+
+        let foreign = ReturnData { program_id: Pubkey::new_unique(), data };
+        assert!(decode_return::<RewardQuote>(Some(foreign)).is_err());
+        assert!(decode_return::<RewardQuote>(None).is_err());
+    }
+}
@@ -0,0 +1,141 @@
+//! Decodes program instructions and `emit_cpi!` events with the program's own IDL types.
+
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use fracvault_client::program::{self, instruction};
+
+macro_rules! events {
+    ($($name:ident),* $(,)?) => {
+        /// Every event the program emits.
+        pub enum Event {
+            $($name(program::$name),)*
+        }
+
+        impl Event {
+            /// Decodes an event from its discriminator-prefixed Borsh encoding. `None` if the
+            /// discriminator is not one of the program's events.
+            pub fn decode(data: &[u8]) -> Option<std::io::Result<Self>> {
+                $(
+                    if let Some(mut rest) = data.strip_prefix(program::$name::DISCRIMINATOR) {
+                        return Some(program::$name::deserialize(&mut rest).map(Event::$name));
+                    }
+                )*
+                None
+            }
+
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Event::$name(_) => stringify!($name),)*
+                }
+            }
+        }
+    };
+}
+
+events!(
+    ConfigInitialized,
+    ConfigUpdated,
+    CollectionAllowlistUpdated,
+    ListingCreated,
+    ContributionMade,
+    ListingFunded,
+    PurchaseExecuted,
+    ListingExpired,
+    RefundProcessed,
+    ListingRefunded,
+    NftReclaimed,
+    ListingClosed,
+    ListingMigrated,
+    AccountMigrated,
+    AuctionRebateClaimed,
+    ProposalCreated,
+    VoteCast,
+    ProposalResolved,
+    RewardRegistered,
+    RewardClaimed,
+    CreatorRoyaltyPaid,
+    KeeperBountyPaid,
+    SoftCloseTriggered,
+    DeadlineExtended,
+);
+
+macro_rules! instruction_names {
+    ($($name:ident => $label:literal),* $(,)?) => {
+        /// Name of the program instruction `data` invokes, as in the IDL.
+        pub fn instruction_name(data: &[u8]) -> Option<&'static str> {
+            $(
+                if data.starts_with(instruction::$name::DISCRIMINATOR) {
+                    return Some($label);
+                }
+            )*
+            None
+        }
+    };
+}
+
+instruction_names!(
+    InitializeConfig => "initialize_config",
+    UpdateConfig => "update_config",
+    AddCollection => "add_collection",
+    UpdateCollection => "update_collection",
+    RemoveCollection => "remove_collection",
+    CreateListing => "create_listing",
+    Contribute => "contribute",
+    ContributeAndExecute => "contribute_and_execute",
+    ExecutePurchase => "execute_purchase",
+    ProcessRefund => "process_refund",
+    CrankRefunds => "crank_refunds",
+    ClaimAuctionRebate => "claim_auction_rebate",
+    ReclaimNft => "reclaim_nft",
+    CloseListing => "close_listing",
+    MigrateListing => "migrate_listing",
+    MigrateAccount => "migrate_account",
+    CreateProposal => "create_proposal",
+    CastVote => "cast_vote",
+    FinalizeProposal => "finalize_proposal",
+    RegisterReward => "register_reward",
+    ClaimReward => "claim_reward",
+);
+
+/// The event carried by an `emit_cpi!` self-invocation, if `data` is one.
+pub fn event_from_cpi(data: &[u8]) -> Option<std::io::Result<Event>> {
+    Event::decode(data.strip_prefix(EVENT_IX_TAG_LE)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::{Event as _, InstructionData};
+    use fracvault_client::Pubkey;
+
+    #[test]
+    fn decodes_cpi_events() {
+        let event = program::RefundProcessed {
+            listing: Pubkey::new_unique(),
+            contributor: Pubkey::new_unique(),
+            bps: 250,
+            amount_lamports: 25_250_000,
+            refunded_bps: 1000,
+            refunded_lamports: 101_000_000,
+            timestamp: 1_700_000_000,
+        };
+        let data = [EVENT_IX_TAG_LE, &event.data()].concat();
+
+        let Some(Ok(Event::RefundProcessed(decoded))) = event_from_cpi(&data) else {
+            panic!("expected a RefundProcessed event");
+        };
+        assert_eq!((decoded.contributor, decoded.amount_lamports), (event.contributor, 25_250_000));
+        // Not a self-CPI: the tag is missing.
+        assert!(event_from_cpi(&event.data()).is_none());
+        // Truncated payloads are errors, not silently dropped.
+        assert!(matches!(event_from_cpi(&data[..data.len() - 4]), Some(Err(_))));
+    }
+
+    #[test]
+    fn names_program_instructions() {
+        let contribute = instruction::Contribute { bps: 100, presale_proof: None }.data();
+        assert_eq!(instruction_name(&contribute), Some("contribute"));
+        assert_eq!(instruction_name(&instruction::FinalizeProposal {}.data()), Some("finalize_proposal"));
+        assert_eq!(instruction_name(&[0; 8]), None);
+    }
+}
@@ -0,0 +1,64 @@
+//! `fracvault-indexer`: indexes the program's history into SQLite.
+
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::Result;
+use clap::Parser;
+use fracvault_client::rpc::{RpcClient, DEFAULT_URL};
+use fracvault_indexer::{Indexer, RpcSource, Store};
+use log::{error, info};
+
+#[derive(Parser)]
+#[command(name = "fracvault-indexer", version, about = "Index FracVault history into SQLite")]
+struct Args {
+    /// JSON-RPC endpoint.
+    #[arg(long, short = 'u', env = "FRACVAULT_URL", default_value = DEFAULT_URL)]
+    url: String,
+
+    /// SQLite database to write.
+    #[arg(long, env = "FRACVAULT_INDEX_DB", default_value = "fracvault.sqlite")]
+    db: PathBuf,
+
+    /// Drop everything indexed so far and start again from the program's first transaction.
+    #[arg(long)]
+    reindex: bool,
+
+    /// Keep polling for new transactions instead of exiting once caught up.
+    #[arg(long)]
+    follow: bool,
+
+    /// Seconds between polls with `--follow`.
+    #[arg(long, default_value_t = 5)]
+    interval: u64,
+}
+
+fn main() -> Result<()> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let args = Args::parse();
+
+    // Indexing needs `getTransaction`, which does not serve `processed`.
+    let source = RpcSource::new(RpcClient::with_commitment(args.url.clone(), "confirmed"));
+    let mut indexer = Indexer::new(source, Store::open(&args.db)?);
+    if args.reindex {
+        info!("dropping {} and re-indexing from scratch", args.db.display());
+        indexer.reindex()?;
+    }
+
+    loop {
+        match indexer.sync() {
+            Ok(report) => {
+                if report.transactions > 0 {
+                    info!("indexed {} transactions, {} events", report.transactions, report.events);
+                }
+            }
+            Err(e) if args.follow => error!("sync failed: {e}"),
+            Err(e) => return Err(e.into()),
+        }
+        if !args.follow {
+            return Ok(());
+        }
+        thread::sleep(Duration::from_secs(args.interval));
+    }
+}
@@ -0,0 +1,233 @@
+//! Where transactions come from: the program's signature history over JSON-RPC.
+
+use std::str::FromStr;
+
+use fracvault_client::rpc::RpcClient;
+use fracvault_client::{ClientError, Pubkey, PROGRAM_ID};
+use serde_json::{json, Value};
+
+/// `getSignaturesForAddress` page size (the RPC maximum).
+const PAGE: usize = 1000;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignatureInfo {
+    pub signature: String,
+    pub slot: u64,
+    pub failed: bool,
+}
+
+/// One instruction invocation inside a transaction, top-level or inner.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Call {
+    /// Index of the top-level instruction this call belongs to.
+    pub index: u32,
+    /// Position among that instruction's inner instructions; `None` for the top-level call itself.
+    pub inner: Option<u32>,
+    pub program_id: Pubkey,
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransactionRecord {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub failed: bool,
+    /// Every invocation in execution order.
+    pub calls: Vec<Call>,
+}
+
+pub trait Source {
+    /// Program signatures newer than `until` (all of them if `None`), oldest first.
+    fn signatures(&self, until: Option<&str>) -> Result<Vec<SignatureInfo>, ClientError>;
+    fn transaction(&self, signature: &str) -> Result<Option<TransactionRecord>, ClientError>;
+}
+
+pub struct RpcSource {
+    rpc: RpcClient,
+}
+
+impl RpcSource {
+    pub fn new(rpc: RpcClient) -> Self {
+        Self { rpc }
+    }
+}
+
+impl Source for RpcSource {
+    fn signatures(&self, until: Option<&str>) -> Result<Vec<SignatureInfo>, ClientError> {
+        // The node returns newest first; page backwards with `before` until `until` is reached.
+        let mut signatures = Vec::new();
+        let mut before: Option<String> = None;
+        loop {
+            let result = self.rpc.request(
+                "getSignaturesForAddress",
+                json!([
+                    PROGRAM_ID.to_string(),
+                    {
+                        "limit": PAGE,
+                        "before": before,
+                        "until": until,
+                        "commitment": self.rpc.commitment(),
+                    }
+                ]),
+            )?;
+            let page = result
+                .as_array()
+                .ok_or_else(|| ClientError::Decode("getSignaturesForAddress: expected an array".into()))?
+                .iter()
+                .map(parse_signature_info)
+                .collect::<Result<Vec<_>, _>>()?;
+            let done = page.len() < PAGE;
+            before = page.last().map(|info| info.signature.clone());
+            signatures.extend(page);
+            if done {
+                break;
+            }
+        }
+        signatures.reverse();
+        Ok(signatures)
+    }
+
+    fn transaction(&self, signature: &str) -> Result<Option<TransactionRecord>, ClientError> {
+        let result = self.rpc.request(
+            "getTransaction",
+            json!([
+                signature,
+                {
+                    "encoding": "json",
+                    "commitment": self.rpc.commitment(),
+                    "maxSupportedTransactionVersion": 0,
+                }
+            ]),
+        )?;
+        if result.is_null() {
+            return Ok(None);
+        }
+        parse_transaction(signature, &result).map(Some)
+    }
+}
+
+fn decode_error(message: &str) -> ClientError {
+    ClientError::Decode(format!("getTransaction: {message}"))
+}
+
+fn parse_signature_info(value: &Value) -> Result<SignatureInfo, ClientError> {
+    Ok(SignatureInfo {
+        signature: value
+            .get("signature")
+            .and_then(Value::as_str)
+            .ok_or_else(|| ClientError::Decode("getSignaturesForAddress: missing signature".into()))?
+            .to_owned(),
+        slot: value.get("slot").and_then(Value::as_u64).unwrap_or_default(),
+        failed: value.get("err").is_some_and(|err| !err.is_null()),
+    })
+}
+
+/// Parses a `getTransaction` result in `json` encoding.
+pub fn parse_transaction(signature: &str, value: &Value) -> Result<TransactionRecord, ClientError> {
+    let meta = value.get("meta").ok_or_else(|| decode_error("missing meta"))?;
+    let message = value
+        .pointer("/transaction/message")
+        .ok_or_else(|| decode_error("missing message"))?;
+
+    // Static keys, then addresses loaded from lookup tables (writable first), as the runtime orders them.
+    let mut keys = Vec::new();
+    for key in message
+        .get("accountKeys")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .chain(meta.pointer("/loadedAddresses/writable").and_then(Value::as_array).into_iter().flatten())
+        .chain(meta.pointer("/loadedAddresses/readonly").and_then(Value::as_array).into_iter().flatten())
+    {
+        let key = key.as_str().ok_or_else(|| decode_error("account key is not a string"))?;
+        keys.push(Pubkey::from_str(key).map_err(|e| decode_error(&e.to_string()))?);
+    }
+
+    let parse_call = |instruction: &Value, index: u32, inner: Option<u32>| -> Result<Call, ClientError> {
+        let program_index = instruction
+            .get("programIdIndex")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| decode_error("missing programIdIndex"))?;
+        let program_id = *keys
+            .get(program_index as usize)
+            .ok_or_else(|| decode_error("programIdIndex out of range"))?;
+        let data = instruction.get("data").and_then(Value::as_str).unwrap_or_default();
+        let data = bs58::decode(data).into_vec().map_err(|e| decode_error(&e.to_string()))?;
+        Ok(Call { index, inner, program_id, data })
+    };
+
+    let inner_sets = meta.get("innerInstructions").and_then(Value::as_array);
+    let mut calls = Vec::new();
+    for (index, instruction) in message
+        .get("instructions")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        let index = index as u32;
+        calls.push(parse_call(instruction, index, None)?);
+        let inner = inner_sets
+            .into_iter()
+            .flatten()
+            .find(|set| set.get("index").and_then(Value::as_u64) == Some(index as u64))
+            .and_then(|set| set.get("instructions"))
+            .and_then(Value::as_array);
+        for (position, instruction) in inner.into_iter().flatten().enumerate() {
+            calls.push(parse_call(instruction, index, Some(position as u32))?);
+        }
+    }
+
+    Ok(TransactionRecord {
+        signature: signature.to_owned(),
+        slot: value.get("slot").and_then(Value::as_u64).unwrap_or_default(),
+        block_time: value.get("blockTime").and_then(Value::as_i64),
+        failed: meta.get("err").is_some_and(|err| !err.is_null()),
+        calls,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_top_level_and_inner_calls_in_order() {
+        let payer = Pubkey::new_unique();
+        let system = Pubkey::default();
+        let value = json!({
+            "slot": 42,
+            "blockTime": 1_700_000_000,
+            "meta": {
+                "err": null,
+                "innerInstructions": [
+                    { "index": 0, "instructions": [
+                        { "programIdIndex": 2, "accounts": [], "data": bs58::encode([9]).into_string() },
+                        { "programIdIndex": 1, "accounts": [], "data": bs58::encode([7, 7]).into_string() },
+                    ]}
+                ],
+                "loadedAddresses": { "writable": [], "readonly": [] },
+            },
+            "transaction": {
+                "signatures": ["sig"],
+                "message": {
+                    "accountKeys": [payer.to_string(), PROGRAM_ID.to_string(), system.to_string()],
+                    "instructions": [
+                        { "programIdIndex": 1, "accounts": [0], "data": bs58::encode([1, 2, 3]).into_string() },
+                        { "programIdIndex": 2, "accounts": [0], "data": "" },
+                    ],
+                },
+            },
+        });
+
+        let record = parse_transaction("sig", &value).unwrap();
+        assert_eq!((record.slot, record.block_time, record.failed), (42, Some(1_700_000_000), false));
+        let calls: Vec<_> = record.calls.iter().map(|call| (call.index, call.inner, call.program_id)).collect();
+        assert_eq!(
+            calls,
+            [(0, None, PROGRAM_ID), (0, Some(0), system), (0, Some(1), PROGRAM_ID), (1, None, system)]
+        );
+        assert_eq!(record.calls[2].data, [7, 7]);
+    }
+}
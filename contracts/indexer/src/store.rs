@@ -0,0 +1,642 @@
+//! Normalized SQLite schema and the event handlers that maintain it.
+//!
+//! Per-event rows (contributions, refunds, votes, ...) are appended; entity rows (listings,
+//! proposals, rewards) are upserted to their latest state. Every transaction is applied atomically
+//! together with the indexing cursor, so an interrupted run resumes cleanly.
+//!
+//! A mint relisted after `close_listing` gets the same listing PDA, so listings are keyed by
+//! `(address, generation)` and the rows of a listing's lifecycle carry its generation.
+//! Lamport and token amounts are `u64`; one past `i64::MAX` fails the transaction rather than
+//! wrapping in a signed SQLite integer.
+
+use std::path::Path;
+
+use fracvault_client::accounts::status_label;
+use fracvault_client::program::{KeeperAction, ListingStatus, ProposalStatus, Vote};
+use fracvault_client::{Pubkey, PROGRAM_ID};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+
+use crate::decode::{event_from_cpi, instruction_name, Event};
+use crate::source::TransactionRecord;
+use crate::IndexerError;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS meta (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS transactions (
+    signature TEXT PRIMARY KEY,
+    slot INTEGER NOT NULL,
+    block_time INTEGER,
+    failed INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS instructions (
+    signature TEXT NOT NULL,
+    ix_index INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    PRIMARY KEY (signature, ix_index)
+);
+CREATE TABLE IF NOT EXISTS events (
+    signature TEXT NOT NULL,
+    ix_index INTEGER NOT NULL,
+    inner_index INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    PRIMARY KEY (signature, ix_index, inner_index)
+);
+CREATE TABLE IF NOT EXISTS listings (
+    address TEXT NOT NULL,
+    generation INTEGER NOT NULL,
+    nft_mint TEXT NOT NULL,
+    seller TEXT NOT NULL,
+    status TEXT NOT NULL,
+    price_lamports INTEGER NOT NULL,
+    custody_fee_lamports INTEGER NOT NULL,
+    total_raise_lamports INTEGER NOT NULL,
+    retained_bps INTEGER NOT NULL,
+    min_fill_bps INTEGER NOT NULL,
+    deadline INTEGER NOT NULL,
+    auction INTEGER NOT NULL,
+    presale INTEGER NOT NULL,
+    keeper_tip_lamports INTEGER NOT NULL,
+    bps_sold INTEGER NOT NULL DEFAULT 0,
+    refunded_bps INTEGER NOT NULL DEFAULT 0,
+    refunded_lamports INTEGER NOT NULL DEFAULT 0,
+    executor TEXT,
+    royalty_lamports INTEGER,
+    seller_proceeds_lamports INTEGER,
+    created_at INTEGER NOT NULL,
+    funded_at INTEGER,
+    executed_at INTEGER,
+    expired_at INTEGER,
+    reclaimed_at INTEGER,
+    closed_at INTEGER,
+    created_signature TEXT NOT NULL,
+    PRIMARY KEY (address, generation)
+);
+CREATE TABLE IF NOT EXISTS contributions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    signature TEXT NOT NULL,
+    listing TEXT NOT NULL,
+    generation INTEGER NOT NULL,
+    contributor TEXT NOT NULL,
+    bps INTEGER NOT NULL,
+    principal_lamports INTEGER NOT NULL,
+    fee_lamports INTEGER NOT NULL,
+    wallet_bps INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS contributions_listing ON contributions (listing);
+CREATE INDEX IF NOT EXISTS contributions_contributor ON contributions (contributor);
+CREATE TABLE IF NOT EXISTS refunds (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    signature TEXT NOT NULL,
+    listing TEXT NOT NULL,
+    generation INTEGER NOT NULL,
+    contributor TEXT NOT NULL,
+    bps INTEGER NOT NULL,
+    amount_lamports INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS rebates (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    signature TEXT NOT NULL,
+    listing TEXT NOT NULL,
+    generation INTEGER NOT NULL,
+    contributor TEXT NOT NULL,
+    amount_lamports INTEGER NOT NULL,
+    clearing_price_lamports INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS royalties (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    signature TEXT NOT NULL,
+    listing TEXT NOT NULL,
+    generation INTEGER NOT NULL,
+    creator TEXT NOT NULL,
+    share INTEGER NOT NULL,
+    amount_lamports INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS keeper_bounties (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    signature TEXT NOT NULL,
+    listing TEXT NOT NULL,
+    generation INTEGER NOT NULL,
+    keeper TEXT NOT NULL,
+    action TEXT NOT NULL,
+    from_tip_lamports INTEGER NOT NULL,
+    from_fee_lamports INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS proposals (
+    address TEXT PRIMARY KEY,
+    listing TEXT NOT NULL,
+    proposal_id INTEGER NOT NULL,
+    proposer TEXT NOT NULL,
+    sale_price_lamports INTEGER NOT NULL,
+    vote_deadline INTEGER NOT NULL,
+    status TEXT NOT NULL,
+    yes_bps INTEGER NOT NULL DEFAULT 0,
+    no_bps INTEGER NOT NULL DEFAULT 0,
+    created_at INTEGER NOT NULL,
+    resolved_at INTEGER
+);
+CREATE TABLE IF NOT EXISTS votes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    signature TEXT NOT NULL,
+    listing TEXT NOT NULL,
+    proposal TEXT NOT NULL,
+    voter TEXT NOT NULL,
+    vote TEXT NOT NULL,
+    bps INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS rewards (
+    registry TEXT PRIMARY KEY,
+    listing TEXT NOT NULL,
+    reward_mint TEXT NOT NULL,
+    total_amount INTEGER NOT NULL,
+    claimed_amount INTEGER NOT NULL DEFAULT 0
+);
+CREATE TABLE IF NOT EXISTS reward_deposits (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    signature TEXT NOT NULL,
+    registry TEXT NOT NULL,
+    depositor TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS reward_claims (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    signature TEXT NOT NULL,
+    listing TEXT NOT NULL,
+    registry TEXT NOT NULL,
+    claimer TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL
+);
+
+CREATE VIEW IF NOT EXISTS contributor_positions AS
+SELECT listing, generation, contributor, COUNT(*) AS contributions, SUM(bps) AS bps,
+       SUM(principal_lamports) AS principal_lamports, SUM(fee_lamports) AS fee_lamports
+FROM contributions GROUP BY listing, generation, contributor;
+
+CREATE VIEW IF NOT EXISTS listing_refund_rates AS
+SELECT address AS listing, generation, status, bps_sold, refunded_bps, refunded_lamports,
+       CASE WHEN bps_sold > 0 THEN CAST(refunded_bps AS REAL) / bps_sold END AS refund_rate
+FROM listings;
+
+CREATE VIEW IF NOT EXISTS proposal_participation AS
+SELECT p.address AS proposal, p.listing, p.proposal_id, p.status, COUNT(v.id) AS voters,
+       p.yes_bps, p.no_bps, CAST(p.yes_bps + p.no_bps AS REAL) / 10000 AS participation
+FROM proposals p LEFT JOIN votes v ON v.proposal = p.address
+GROUP BY p.address;
+";
+
+const TABLES: &[&str] = &[
+    "meta",
+    "transactions",
+    "instructions",
+    "events",
+    "listings",
+    "contributions",
+    "refunds",
+    "rebates",
+    "royalties",
+    "keeper_bounties",
+    "proposals",
+    "votes",
+    "rewards",
+    "reward_deposits",
+    "reward_claims",
+];
+
+const VIEWS: &[&str] = &["contributor_positions", "listing_refund_rates", "proposal_participation"];
+
+const CURSOR_KEY: &str = "cursor";
+
+// Stored as SQLite's `user_version`; a database indexed under another schema is dropped and
+// re-indexed from scratch when opened.
+const SCHEMA_VERSION: i64 = 2;
+
+pub struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    pub fn open(path: &Path) -> Result<Self, IndexerError> {
+        Self::init(Connection::open(path)?)
+    }
+
+    pub fn in_memory() -> Result<Self, IndexerError> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, IndexerError> {
+        let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version != SCHEMA_VERSION {
+            drop_schema(&conn)?;
+            conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        }
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Drops every table and view, leaving an empty schema to re-index into.
+    pub fn reset(&mut self) -> Result<(), IndexerError> {
+        let tx = self.conn.transaction()?;
+        drop_schema(&tx)?;
+        tx.execute_batch(SCHEMA)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Newest indexed signature.
+    pub fn cursor(&self) -> Result<Option<String>, IndexerError> {
+        Ok(self
+            .conn
+            .query_row("SELECT value FROM meta WHERE key = ?1", [CURSOR_KEY], |row| row.get(0))
+            .optional()?)
+    }
+
+    pub fn contains(&self, signature: &str) -> Result<bool, IndexerError> {
+        Ok(self
+            .conn
+            .query_row("SELECT 1 FROM transactions WHERE signature = ?1", [signature], |_| Ok(()))
+            .optional()?
+            .is_some())
+    }
+
+    /// Records `record` and applies its events, then advances the cursor to it. Returns the
+    /// number of events applied.
+    pub fn apply(&mut self, record: &TransactionRecord) -> Result<usize, IndexerError> {
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT OR IGNORE INTO transactions (signature, slot, block_time, failed) VALUES (?1, ?2, ?3, ?4)",
+            params![record.signature, int(record.slot)?, record.block_time, record.failed],
+        )?;
+
+        let mut applied = 0;
+        // A failed transaction's instructions were rolled back and emitted nothing.
+        if !record.failed {
+            for call in record.calls.iter().filter(|call| call.program_id == PROGRAM_ID) {
+                if call.inner.is_none() {
+                    if let Some(name) = instruction_name(&call.data) {
+                        tx.execute(
+                            "INSERT OR IGNORE INTO instructions (signature, ix_index, name) VALUES (?1, ?2, ?3)",
+                            params![record.signature, call.index, name],
+                        )?;
+                    }
+                    continue;
+                }
+                let Some(event) = event_from_cpi(&call.data) else {
+                    continue;
+                };
+                let event = event.map_err(|e| IndexerError::Decode {
+                    signature: record.signature.clone(),
+                    message: e.to_string(),
+                })?;
+                let inserted = tx.execute(
+                    "INSERT OR IGNORE INTO events (signature, ix_index, inner_index, name) VALUES (?1, ?2, ?3, ?4)",
+                    params![record.signature, call.index, call.inner, event.name()],
+                )?;
+                if inserted > 0 {
+                    apply_event(&tx, &record.signature, &event)?;
+                    applied += 1;
+                }
+            }
+        }
+
+        tx.execute(
+            "INSERT INTO meta (key, value) VALUES (?1, ?2) ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+            params![CURSOR_KEY, record.signature],
+        )?;
+        tx.commit()?;
+        Ok(applied)
+    }
+}
+
+fn drop_schema(conn: &Connection) -> rusqlite::Result<()> {
+    for view in VIEWS {
+        conn.execute_batch(&format!("DROP VIEW IF EXISTS {view}"))?;
+    }
+    for table in TABLES {
+        conn.execute_batch(&format!("DROP TABLE IF EXISTS {table}"))?;
+    }
+    Ok(())
+}
+
+fn key(pubkey: &Pubkey) -> String {
+    pubkey.to_string()
+}
+
+fn int(value: u64) -> rusqlite::Result<i64> {
+    i64::try_from(value).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+}
+
+// Generation of the listing currently at `listing`.
+fn generation(tx: &Transaction, listing: &Pubkey) -> rusqlite::Result<i64> {
+    tx.query_row(
+        "SELECT COALESCE(MAX(generation), 0) FROM listings WHERE address = ?1",
+        [key(listing)],
+        |row| row.get(0),
+    )
+}
+
+fn listing_status(tx: &Transaction, listing: &Pubkey, status: ListingStatus) -> rusqlite::Result<usize> {
+    tx.execute(
+        "UPDATE listings SET status = ?3 WHERE address = ?1 AND generation = ?2",
+        params![key(listing), generation(tx, listing)?, status_label(&status)],
+    )
+}
+
+fn proposal_status_label(status: &ProposalStatus) -> &'static str {
+    match status {
+        ProposalStatus::Active => "active",
+        ProposalStatus::Approved => "approved",
+        ProposalStatus::Rejected => "rejected",
+        ProposalStatus::Expired => "expired",
+        ProposalStatus::Executed => "executed",
+    }
+}
+
+fn keeper_action_label(action: &KeeperAction) -> &'static str {
+    match action {
+        KeeperAction::ExecutePurchase => "execute_purchase",
+        KeeperAction::CrankRefunds => "crank_refunds",
+        KeeperAction::FinalizeProposal => "finalize_proposal",
+    }
+}
+
+fn apply_event(tx: &Transaction, signature: &str, event: &Event) -> rusqlite::Result<()> {
+    match event {
+        Event::ListingCreated(e) => {
+            let generation: i64 = tx.query_row(
+                "SELECT COALESCE(MAX(generation) + 1, 0) FROM listings WHERE address = ?1",
+                [key(&e.listing)],
+                |row| row.get(0),
+            )?;
+            tx.execute(
+                "INSERT INTO listings (address, generation, nft_mint, seller, status, price_lamports,
+                    custody_fee_lamports, total_raise_lamports, retained_bps, min_fill_bps, deadline,
+                    auction, presale, keeper_tip_lamports, created_at, created_signature)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+                params![
+                    key(&e.listing),
+                    generation,
+                    key(&e.nft_mint),
+                    key(&e.seller),
+                    status_label(&ListingStatus::Open),
+                    int(e.price_lamports)?,
+                    int(e.custody_fee_lamports)?,
+                    int(e.total_raise_lamports)?,
+                    e.retained_bps,
+                    e.min_fill_bps,
+                    e.deadline,
+                    e.auction,
+                    e.presale,
+                    int(e.keeper_tip_lamports)?,
+                    e.timestamp,
+                    signature,
+                ],
+            )?;
+        }
+        Event::ContributionMade(e) => {
+            let generation = generation(tx, &e.listing)?;
+            tx.execute(
+                "INSERT INTO contributions (signature, listing, generation, contributor, bps, principal_lamports,
+                    fee_lamports, wallet_bps, timestamp)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    signature,
+                    key(&e.listing),
+                    generation,
+                    key(&e.contributor),
+                    e.bps,
+                    int(e.principal_lamports)?,
+                    int(e.fee_lamports)?,
+                    e.wallet_bps,
+                    e.timestamp,
+                ],
+            )?;
+            tx.execute(
+                "UPDATE listings SET bps_sold = ?3 WHERE address = ?1 AND generation = ?2",
+                params![key(&e.listing), generation, e.bps_sold],
+            )?;
+        }
+        Event::ListingFunded(e) => {
+            tx.execute(
+                "UPDATE listings SET status = ?3, funded_at = ?4, price_lamports = ?5, custody_fee_lamports = ?6
+                 WHERE address = ?1 AND generation = ?2",
+                params![
+                    key(&e.listing),
+                    generation(tx, &e.listing)?,
+                    status_label(&ListingStatus::Funded),
+                    e.timestamp,
+                    int(e.price_lamports)?,
+                    int(e.custody_fee_lamports)?,
+                ],
+            )?;
+        }
+        Event::PurchaseExecuted(e) => {
+            tx.execute(
+                "UPDATE listings SET status = ?3, executed_at = ?4, executor = ?5, royalty_lamports = ?6,
+                    seller_proceeds_lamports = ?7, bps_sold = ?8
+                 WHERE address = ?1 AND generation = ?2",
+                params![
+                    key(&e.listing),
+                    generation(tx, &e.listing)?,
+                    status_label(&ListingStatus::Custodied),
+                    e.timestamp,
+                    key(&e.executor),
+                    int(e.royalty_lamports)?,
+                    int(e.seller_proceeds_lamports)?,
+                    e.bps_sold,
+                ],
+            )?;
+        }
+        Event::ListingExpired(e) => {
+            tx.execute(
+                "UPDATE listings SET status = ?3, expired_at = ?4 WHERE address = ?1 AND generation = ?2",
+                params![
+                    key(&e.listing),
+                    generation(tx, &e.listing)?,
+                    status_label(&ListingStatus::Expired),
+                    e.timestamp,
+                ],
+            )?;
+        }
+        Event::RefundProcessed(e) => {
+            let generation = generation(tx, &e.listing)?;
+            tx.execute(
+                "INSERT INTO refunds (signature, listing, generation, contributor, bps, amount_lamports, timestamp)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    signature,
+                    key(&e.listing),
+                    generation,
+                    key(&e.contributor),
+                    e.bps,
+                    int(e.amount_lamports)?,
+                    e.timestamp,
+                ],
+            )?;
+            tx.execute(
+                "UPDATE listings SET refunded_bps = ?3, refunded_lamports = ?4 WHERE address = ?1 AND generation = ?2",
+                params![key(&e.listing), generation, e.refunded_bps, int(e.refunded_lamports)?],
+            )?;
+        }
+        Event::ListingRefunded(e) => {
+            listing_status(tx, &e.listing, ListingStatus::Refunded)?;
+        }
+        Event::NftReclaimed(e) => {
+            tx.execute(
+                "UPDATE listings SET reclaimed_at = ?3 WHERE address = ?1 AND generation = ?2",
+                params![key(&e.listing), generation(tx, &e.listing)?, e.timestamp],
+            )?;
+        }
+        Event::ListingClosed(e) => {
+            tx.execute(
+                "UPDATE listings SET closed_at = ?3 WHERE address = ?1 AND generation = ?2",
+                params![key(&e.listing), generation(tx, &e.listing)?, e.timestamp],
+            )?;
+        }
+        Event::DeadlineExtended(e) => {
+            tx.execute(
+                "UPDATE listings SET deadline = ?3 WHERE address = ?1 AND generation = ?2",
+                params![key(&e.listing), generation(tx, &e.listing)?, e.new_deadline],
+            )?;
+        }
+        Event::AuctionRebateClaimed(e) => {
+            tx.execute(
+                "INSERT INTO rebates (signature, listing, generation, contributor, amount_lamports,
+                    clearing_price_lamports, timestamp)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    signature,
+                    key(&e.listing),
+                    generation(tx, &e.listing)?,
+                    key(&e.contributor),
+                    int(e.amount_lamports)?,
+                    int(e.clearing_price_lamports)?,
+                    e.timestamp,
+                ],
+            )?;
+        }
+        Event::CreatorRoyaltyPaid(e) => {
+            tx.execute(
+                "INSERT INTO royalties (signature, listing, generation, creator, share, amount_lamports)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    signature,
+                    key(&e.listing),
+                    generation(tx, &e.listing)?,
+                    key(&e.creator),
+                    e.share,
+                    int(e.amount_lamports)?,
+                ],
+            )?;
+        }
+        Event::KeeperBountyPaid(e) => {
+            tx.execute(
+                "INSERT INTO keeper_bounties (signature, listing, generation, keeper, action, from_tip_lamports,
+                    from_fee_lamports)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    signature,
+                    key(&e.listing),
+                    generation(tx, &e.listing)?,
+                    key(&e.keeper),
+                    keeper_action_label(&e.action),
+                    int(e.from_tip_lamports)?,
+                    int(e.from_fee_lamports)?,
+                ],
+            )?;
+        }
+        Event::ProposalCreated(e) => {
+            tx.execute(
+                "INSERT OR REPLACE INTO proposals (address, listing, proposal_id, proposer, sale_price_lamports,
+                    vote_deadline, status, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    key(&e.proposal),
+                    key(&e.listing),
+                    e.proposal_id,
+                    key(&e.proposer),
+                    int(e.sale_price_lamports)?,
+                    e.vote_deadline,
+                    proposal_status_label(&ProposalStatus::Active),
+                    e.timestamp,
+                ],
+            )?;
+        }
+        Event::VoteCast(e) => {
+            let vote = match e.vote {
+                Vote::Yes => "yes",
+                Vote::No => "no",
+            };
+            tx.execute(
+                "INSERT INTO votes (signature, listing, proposal, voter, vote, bps, timestamp)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![signature, key(&e.listing), key(&e.proposal), key(&e.voter), vote, e.bps, e.timestamp],
+            )?;
+            tx.execute(
+                "UPDATE proposals SET yes_bps = ?2, no_bps = ?3 WHERE address = ?1",
+                params![key(&e.proposal), e.yes_bps, e.no_bps],
+            )?;
+        }
+        Event::ProposalResolved(e) => {
+            tx.execute(
+                "UPDATE proposals SET status = ?2, yes_bps = ?3, no_bps = ?4, resolved_at = ?5 WHERE address = ?1",
+                params![
+                    key(&e.proposal),
+                    proposal_status_label(&e.status),
+                    e.yes_bps,
+                    e.no_bps,
+                    e.timestamp,
+                ],
+            )?;
+        }
+        Event::RewardRegistered(e) => {
+            tx.execute(
+                "INSERT INTO rewards (registry, listing, reward_mint, total_amount) VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT (registry) DO UPDATE SET total_amount = excluded.total_amount",
+                params![key(&e.registry), key(&e.listing), key(&e.reward_mint), int(e.total_amount)?],
+            )?;
+            tx.execute(
+                "INSERT INTO reward_deposits (signature, registry, depositor, amount, timestamp)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![signature, key(&e.registry), key(&e.depositor), int(e.amount)?, e.timestamp],
+            )?;
+        }
+        Event::RewardClaimed(e) => {
+            tx.execute(
+                "INSERT INTO reward_claims (signature, listing, registry, claimer, amount, timestamp)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    signature,
+                    key(&e.listing),
+                    key(&e.registry),
+                    key(&e.claimer),
+                    int(e.amount)?,
+                    e.timestamp,
+                ],
+            )?;
+            tx.execute(
+                "UPDATE rewards SET claimed_amount = claimed_amount + ?2 WHERE registry = ?1",
+                params![key(&e.registry), int(e.amount)?],
+            )?;
+        }
+        // Recorded in `events` only.
+        Event::ConfigInitialized(_)
+        | Event::ConfigUpdated(_)
+        | Event::CollectionAllowlistUpdated(_)
+        | Event::SoftCloseTriggered(_)
+        | Event::ListingMigrated(_)
+        | Event::AccountMigrated(_) => {}
+    }
+    Ok(())
+}
@@ -0,0 +1,412 @@
+//! Indexes a scripted program history and checks the normalized tables and analytics views.
+
+use std::cell::RefCell;
+
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::{Event, InstructionData};
+use fracvault_client::program::{self, instruction, ProposalStatus, Vote};
+use fracvault_client::{ClientError, Pubkey, PROGRAM_ID};
+use fracvault_indexer::source::{Call, SignatureInfo};
+use fracvault_indexer::{Indexer, Source, Store, TransactionRecord};
+
+const T: i64 = 1_700_000_000;
+
+#[derive(Default)]
+struct ScriptedSource {
+    history: RefCell<Vec<TransactionRecord>>,
+}
+
+impl ScriptedSource {
+    /// Appends a transaction running `ix` that emitted `events`.
+    fn push(&self, ix: impl InstructionData, events: &[Vec<u8>], failed: bool) {
+        let mut history = self.history.borrow_mut();
+        let signature = format!("sig{}", history.len());
+        let mut calls = vec![Call { index: 0, inner: None, program_id: PROGRAM_ID, data: ix.data() }];
+        calls.extend(events.iter().enumerate().map(|(position, event)| Call {
+            index: 0,
+            inner: Some(position as u32),
+            program_id: PROGRAM_ID,
+            data: [EVENT_IX_TAG_LE, event].concat(),
+        }));
+        let slot = history.len() as u64 + 1;
+        history.push(TransactionRecord { signature, slot, block_time: Some(T), failed, calls });
+    }
+}
+
+impl Source for ScriptedSource {
+    fn signatures(&self, until: Option<&str>) -> Result<Vec<SignatureInfo>, ClientError> {
+        let history = self.history.borrow();
+        let start = until
+            .and_then(|until| history.iter().position(|record| record.signature == until))
+            .map_or(0, |position| position + 1);
+        Ok(history[start..]
+            .iter()
+            .map(|record| SignatureInfo { signature: record.signature.clone(), slot: record.slot, failed: record.failed })
+            .collect())
+    }
+
+    fn transaction(&self, signature: &str) -> Result<Option<TransactionRecord>, ClientError> {
+        Ok(self.history.borrow().iter().find(|record| record.signature == signature).cloned())
+    }
+}
+
+fn listing_created(listing: Pubkey, seller: Pubkey) -> Vec<u8> {
+    program::ListingCreated {
+        listing,
+        nft_mint: Pubkey::new_unique(),
+        seller,
+        price_lamports: 1_000_000_000,
+        custody_fee_lamports: 10_000_000,
+        total_raise_lamports: 1_010_000_000,
+        retained_bps: 0,
+        min_fill_bps: 0,
+        deadline: T + 86400,
+        auction: false,
+        presale: false,
+        keeper_tip_lamports: 0,
+        timestamp: T,
+    }
+    .data()
+}
+
+fn contribution(listing: Pubkey, contributor: Pubkey, bps: u16, bps_sold: u16) -> Vec<u8> {
+    program::ContributionMade {
+        listing,
+        contributor,
+        bps,
+        principal_lamports: 100_000 * bps as u64,
+        fee_lamports: 1_000 * bps as u64,
+        wallet_bps: bps,
+        bps_sold,
+        timestamp: T + 60,
+    }
+    .data()
+}
+
+fn contribute_ix(bps: u16) -> instruction::Contribute {
+    instruction::Contribute { bps, presale_proof: None }
+}
+
+fn create_ix() -> instruction::CreateListing {
+    instruction::CreateListing {
+        args: program::CreateListingArgs {
+            price_lamports: 1_000_000_000,
+            deadline_offset: 86400,
+            retained_bps: 0,
+            min_fill_bps: 0,
+            auction: None,
+            presale: None,
+            max_bps_per_wallet: 0,
+            min_bps_per_contribution: 0,
+            soft_close: None,
+            keeper_tip_lamports: 0,
+        },
+    }
+}
+
+/// One listing fails and is refunded in full; another sells and goes through governance.
+fn scripted_history() -> (ScriptedSource, Pubkey, Pubkey, Pubkey) {
+    let source = ScriptedSource::default();
+    let (failed, sold) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let (seller, alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+    let create = |listing| source.push(create_ix(), &[listing_created(listing, seller)], false);
+    // Failed listing: two contributions, then refunds and reclaim.
+    create(failed);
+    source.push(contribute_ix(1000), &[contribution(failed, alice, 1000, 1000)], false);
+    source.push(contribute_ix(3000), &[contribution(failed, bob, 3000, 4000)], false);
+    // Rolled back: recorded, but nothing applied.
+    source.push(contribute_ix(9000), &[contribution(failed, bob, 9000, 13000)], true);
+    let refund = |contributor, bps: u16, refunded_bps| {
+        program::RefundProcessed {
+            listing: failed,
+            contributor,
+            bps,
+            amount_lamports: 101_000 * bps as u64,
+            refunded_bps,
+            refunded_lamports: 101_000 * refunded_bps as u64,
+            timestamp: T + 90000,
+        }
+        .data()
+    };
+    source.push(
+        instruction::CrankRefunds {},
+        &[
+            refund(alice, 1000, 1000),
+            program::ListingExpired { listing: failed, bps_sold: 4000, timestamp: T + 90000 }.data(),
+        ],
+        false,
+    );
+    source.push(
+        instruction::ReclaimNft {},
+        &[program::NftReclaimed { listing: failed, nft_mint: Pubkey::new_unique(), seller, timestamp: T + 90001 }
+            .data()],
+        false,
+    );
+    source.push(
+        instruction::ProcessRefund {},
+        &[
+            refund(bob, 3000, 4000),
+            program::ListingRefunded { listing: failed, refunded_bps: 4000, refunded_lamports: 404_000_000, timestamp: T + 90002 }
+                .data(),
+        ],
+        false,
+    );
+
+    // Sold listing: fully funded, executed, then a proposal is voted on and rewards flow.
+    create(sold);
+    source.push(
+        contribute_ix(10000),
+        &[
+            contribution(sold, alice, 6000, 6000),
+            program::ListingFunded {
+                listing: sold,
+                bps_sold: 6000,
+                price_lamports: 1_000_000_000,
+                custody_fee_lamports: 10_000_000,
+                contributor_count: 1,
+                timestamp: T + 60,
+            }
+            .data(),
+        ],
+        false,
+    );
+    source.push(
+        instruction::ExecutePurchase {},
+        &[program::PurchaseExecuted {
+            listing: sold,
+            executor: bob,
+            bps_sold: 6000,
+            retained_bps: 4000,
+            price_lamports: 600_000_000,
+            custody_fee_lamports: 6_000_000,
+            royalty_lamports: 0,
+            seller_proceeds_lamports: 600_000_000,
+            keeper_tip_refund_lamports: 0,
+            timestamp: T + 120,
+        }
+        .data()],
+        false,
+    );
+    let proposal = Pubkey::new_unique();
+    source.push(
+        instruction::CreateProposal { sale_price_lamports: 2_000_000_000, vote_deadline_offset: 3600 },
+        &[program::ProposalCreated {
+            listing: sold,
+            proposal,
+            proposal_id: 0,
+            proposer: alice,
+            sale_price_lamports: 2_000_000_000,
+            vote_deadline: T + 4000,
+            timestamp: T + 400,
+        }
+        .data()],
+        false,
+    );
+    source.push(
+        instruction::CastVote { vote: Vote::Yes },
+        &[program::VoteCast {
+            listing: sold,
+            proposal,
+            voter: alice,
+            vote: Vote::Yes,
+            bps: 6000,
+            yes_bps: 6000,
+            no_bps: 0,
+            timestamp: T + 500,
+        }
+        .data(),
+        program::ProposalResolved {
+            listing: sold,
+            proposal,
+            status: ProposalStatus::Approved,
+            yes_bps: 6000,
+            no_bps: 0,
+            timestamp: T + 500,
+        }
+        .data()],
+        false,
+    );
+    let registry = Pubkey::new_unique();
+    source.push(
+        instruction::RegisterReward { amount: 1_000 },
+        &[program::RewardRegistered {
+            listing: sold,
+            registry,
+            reward_mint: Pubkey::new_unique(),
+            depositor: seller,
+            amount: 1_000,
+            total_amount: 1_000,
+            timestamp: T + 600,
+        }
+        .data()],
+        false,
+    );
+    source.push(
+        instruction::ClaimReward {},
+        &[program::RewardClaimed {
+            listing: sold,
+            registry,
+            claimer: alice,
+            amount: 600,
+            claimed_total: 600,
+            timestamp: T + 700,
+        }
+        .data()],
+        false,
+    );
+
+    (source, failed, sold, alice)
+}
+
+fn query<T: rusqlite::types::FromSql>(store: &Store, sql: &str, key: &Pubkey) -> T {
+    store.connection().query_row(sql, [key.to_string()], |row| row.get(0)).unwrap()
+}
+
+#[test]
+fn indexes_listing_lifecycles_and_governance() {
+    let (source, failed, sold, alice) = scripted_history();
+    let mut indexer = Indexer::new(source, Store::in_memory().unwrap());
+
+    let report = indexer.sync().unwrap();
+    assert_eq!(report.transactions, 14);
+    assert_eq!(report.events, 17);
+    let store = indexer.store();
+
+    let status: String = query(store, "SELECT status FROM listings WHERE address = ?1", &failed);
+    assert_eq!(status, "refunded");
+    let refund_rate: f64 = query(store, "SELECT refund_rate FROM listing_refund_rates WHERE listing = ?1", &failed);
+    assert_eq!(refund_rate, 1.0);
+    let bps_sold: i64 = query(store, "SELECT bps_sold FROM listings WHERE address = ?1", &failed);
+    assert_eq!(bps_sold, 4000, "the rolled-back contribution must not count");
+
+    let status: String = query(store, "SELECT status FROM listings WHERE address = ?1", &sold);
+    assert_eq!(status, "custodied");
+    let contributed: i64 = query(
+        store,
+        "SELECT SUM(principal_lamports) FROM contributor_positions WHERE contributor = ?1",
+        &alice,
+    );
+    assert_eq!(contributed, 100_000 * (1000 + 6000));
+    let (voters, participation): (i64, f64) = store
+        .connection()
+        .query_row(
+            "SELECT voters, participation FROM proposal_participation WHERE listing = ?1",
+            [sold.to_string()],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap();
+    assert_eq!((voters, participation), (1, 0.6));
+    let claimed: i64 = query(store, "SELECT claimed_amount FROM rewards WHERE listing = ?1", &sold);
+    assert_eq!(claimed, 600);
+
+    let names: Vec<String> = store
+        .connection()
+        .prepare("SELECT name FROM instructions ORDER BY signature")
+        .unwrap()
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert!(names.contains(&"crank_refunds".to_string()) && names.contains(&"cast_vote".to_string()));
+}
+
+#[test]
+fn resumes_from_cursor_and_reindexes_from_scratch() {
+    let (source, failed, _, _) = scripted_history();
+    let mut indexer = Indexer::new(source, Store::in_memory().unwrap());
+    indexer.sync().unwrap();
+
+    // Caught up: nothing is applied twice.
+    assert_eq!(indexer.sync().unwrap().transactions, 0);
+    let refunds: i64 = query(indexer.store(), "SELECT COUNT(*) FROM refunds WHERE listing = ?1", &failed);
+    assert_eq!(refunds, 2);
+
+    indexer.reindex().unwrap();
+    assert_eq!(indexer.store().cursor().unwrap(), None);
+    let report = indexer.sync().unwrap();
+    assert_eq!((report.transactions, report.events), (14, 17));
+    let refunds: i64 = query(indexer.store(), "SELECT COUNT(*) FROM refunds WHERE listing = ?1", &failed);
+    assert_eq!(refunds, 2);
+}
+
+#[test]
+fn relisted_mints_get_a_new_listing_generation() {
+    let (source, failed, _, alice) = scripted_history();
+    let seller = Pubkey::new_unique();
+    let rebate = |amount_lamports, timestamp| {
+        program::AuctionRebateClaimed { listing: failed, contributor: alice, amount_lamports, clearing_price_lamports: 0, timestamp }
+            .data()
+    };
+    source.push(instruction::ClaimAuctionRebate {}, &[rebate(100, T + 90002)], false);
+    source.push(
+        instruction::CloseListing {},
+        &[program::ListingClosed { listing: failed, seller, timestamp: T + 90003 }.data()],
+        false,
+    );
+    source.push(create_ix(), &[listing_created(failed, seller)], false);
+    source.push(contribute_ix(2000), &[contribution(failed, alice, 2000, 2000)], false);
+    source.push(instruction::ClaimAuctionRebate {}, &[rebate(200, T + 90004)], false);
+    let mut indexer = Indexer::new(source, Store::in_memory().unwrap());
+    indexer.sync().unwrap();
+    let store = indexer.store();
+
+    let listings: Vec<(i64, String, i64, Option<i64>)> = store
+        .connection()
+        .prepare("SELECT generation, status, bps_sold, closed_at FROM listings WHERE address = ?1 ORDER BY generation")
+        .unwrap()
+        .query_map([failed.to_string()], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(
+        listings,
+        vec![(0, "refunded".to_string(), 4000, Some(T + 90003)), (1, "open".to_string(), 2000, None)]
+    );
+    let positions: Vec<(i64, i64)> = store
+        .connection()
+        .prepare("SELECT generation, bps FROM contributor_positions WHERE listing = ?1 AND contributor = ?2")
+        .unwrap()
+        .query_map([failed.to_string(), alice.to_string()], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(positions, vec![(0, 1000), (1, 2000)]);
+    let rebates: Vec<(i64, i64)> = store
+        .connection()
+        .prepare("SELECT generation, amount_lamports FROM rebates WHERE listing = ?1 ORDER BY id")
+        .unwrap()
+        .query_map([failed.to_string()], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(rebates, vec![(0, 100), (1, 200)]);
+}
+
+#[test]
+fn amounts_past_i64_max_fail_instead_of_wrapping() {
+    let source = ScriptedSource::default();
+    let listing = Pubkey::new_unique();
+    source.push(create_ix(), &[listing_created(listing, Pubkey::new_unique())], false);
+    source.push(
+        instruction::RegisterReward { amount: u64::MAX },
+        &[program::RewardRegistered {
+            listing,
+            registry: Pubkey::new_unique(),
+            reward_mint: Pubkey::new_unique(),
+            depositor: Pubkey::new_unique(),
+            amount: u64::MAX,
+            total_amount: u64::MAX,
+            timestamp: T,
+        }
+        .data()],
+        false,
+    );
+    let mut indexer = Indexer::new(source, Store::in_memory().unwrap());
+
+    assert!(indexer.sync().is_err());
+    // The failing transaction was rolled back with its cursor update.
+    assert_eq!(indexer.store().cursor().unwrap().as_deref(), Some("sig0"));
+    let rewards: i64 = indexer.store().connection().query_row("SELECT COUNT(*) FROM rewards", [], |row| row.get(0)).unwrap();
+    assert_eq!(rewards, 0);
+}
@@ -0,0 +1,90 @@
+//! The keeper's view of the cluster.
+
+use fracvault_client::accounts::{self, fetch_config};
+use fracvault_client::program::{Config, Contribution, Listing, Proposal};
+use fracvault_client::rpc::RpcClient;
+use fracvault_client::{pda, ClientError, Instruction, Keypair, Pubkey, Signature, Signer};
+
+pub trait Chain {
+    /// Cluster time, as the program sees it.
+    fn now(&self) -> Result<i64, ClientError>;
+    fn config(&self) -> Result<Config, ClientError>;
+    fn listings(&self) -> Result<Vec<(Pubkey, Listing)>, ClientError>;
+    fn contributions(&self, listing: &Pubkey) -> Result<Vec<(Pubkey, Contribution)>, ClientError>;
+    fn proposals(&self) -> Result<Vec<(Pubkey, Proposal)>, ClientError>;
+    /// The keeper's wallet: fee payer, and recipient of keeper bounties.
+    fn keeper(&self) -> Pubkey;
+    /// Sends `instructions` in one transaction and waits for confirmation.
+    fn send(&self, instructions: &[Instruction]) -> Result<Signature, ClientError>;
+}
+
+impl<T: Chain + ?Sized> Chain for &T {
+    fn now(&self) -> Result<i64, ClientError> {
+        (**self).now()
+    }
+
+    fn config(&self) -> Result<Config, ClientError> {
+        (**self).config()
+    }
+
+    fn listings(&self) -> Result<Vec<(Pubkey, Listing)>, ClientError> {
+        (**self).listings()
+    }
+
+    fn contributions(&self, listing: &Pubkey) -> Result<Vec<(Pubkey, Contribution)>, ClientError> {
+        (**self).contributions(listing)
+    }
+
+    fn proposals(&self) -> Result<Vec<(Pubkey, Proposal)>, ClientError> {
+        (**self).proposals()
+    }
+
+    fn keeper(&self) -> Pubkey {
+        (**self).keeper()
+    }
+
+    fn send(&self, instructions: &[Instruction]) -> Result<Signature, ClientError> {
+        (**self).send(instructions)
+    }
+}
+
+pub struct RpcChain {
+    rpc: RpcClient,
+    keeper: Keypair,
+}
+
+impl RpcChain {
+    pub fn new(rpc: RpcClient, keeper: Keypair) -> Self {
+        Self { rpc, keeper }
+    }
+}
+
+impl Chain for RpcChain {
+    fn now(&self) -> Result<i64, ClientError> {
+        Ok(self.rpc.get_clock()?.unix_timestamp)
+    }
+
+    fn config(&self) -> Result<Config, ClientError> {
+        fetch_config(&self.rpc)?.ok_or(ClientError::AccountNotFound(pda::config().0))
+    }
+
+    fn listings(&self) -> Result<Vec<(Pubkey, Listing)>, ClientError> {
+        accounts::all_listings(&self.rpc)
+    }
+
+    fn contributions(&self, listing: &Pubkey) -> Result<Vec<(Pubkey, Contribution)>, ClientError> {
+        accounts::contributions_by_listing(&self.rpc, listing)
+    }
+
+    fn proposals(&self) -> Result<Vec<(Pubkey, Proposal)>, ClientError> {
+        accounts::all_proposals(&self.rpc)
+    }
+
+    fn keeper(&self) -> Pubkey {
+        self.keeper.pubkey()
+    }
+
+    fn send(&self, instructions: &[Instruction]) -> Result<Signature, ClientError> {
+        self.rpc.send_and_confirm(instructions, &self.keeper, &[])
+    }
+}
@@ -0,0 +1,109 @@
+//! Keeper for the FracVault program.
+//!
+//! Each [`Keeper::tick`] reads the cluster clock and the program's accounts, plans the
+//! housekeeping that is currently allowed and sends one transaction per task:
+//!
+//! - `execute_purchase` for listings inside their execution window
+//! - `crank_refunds` for every outstanding contribution of a refundable listing
+//! - `reclaim_nft` back to the seller of a failed listing
+//! - `finalize_proposal` once voting has ended
+//!
+//! Per-task attempts are kept in a [`State`] file so a restarted keeper keeps backing off from
+//! failing tasks and does not resend tasks it has just completed. The cluster is reached through
+//! the [`Chain`] trait: [`RpcChain`] in production, and the program's test bank in
+//! `tests/bank.rs`.
+
+pub mod chain;
+pub mod plan;
+pub mod state;
+
+use std::path::PathBuf;
+
+use fracvault_client::{ClientError, Instruction};
+use log::{info, warn};
+
+pub use chain::{Chain, RpcChain};
+pub use plan::{plan, Task};
+pub use state::State;
+
+#[derive(Debug, thiserror::Error)]
+pub enum KeeperError {
+    #[error(transparent)]
+    Client(#[from] ClientError),
+    #[error("state file {path}: {message}")]
+    State { path: PathBuf, message: String },
+}
+
+/// What a single tick did.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TickReport {
+    pub planned: usize,
+    pub sent: usize,
+    pub failed: usize,
+    pub deferred: usize,
+}
+
+pub struct Keeper<C: Chain> {
+    chain: C,
+    state: State,
+    state_path: PathBuf,
+}
+
+impl<C: Chain> Keeper<C> {
+    /// Resumes from `state_path` if it exists.
+    pub fn new(chain: C, state_path: impl Into<PathBuf>) -> Result<Self, KeeperError> {
+        let state_path = state_path.into();
+        let state = State::load(&state_path)?;
+        Ok(Self { chain, state, state_path })
+    }
+
+    pub fn chain(&self) -> &C {
+        &self.chain
+    }
+
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    pub fn tick(&mut self) -> Result<TickReport, KeeperError> {
+        let now = self.chain.now()?;
+        let fee_vault = self.chain.config()?.fee_vault;
+        let listings = self.chain.listings()?;
+        let proposals = self.chain.proposals()?;
+        let tasks = plan(&self.chain, &listings, &proposals, now)?;
+
+        let mut report = TickReport { planned: tasks.len(), ..TickReport::default() };
+        for task in &tasks {
+            let key = task.key();
+            if !self.state.should_attempt(&key, now) {
+                report.deferred += 1;
+                continue;
+            }
+            let keeper = self.chain.keeper();
+            let result = task
+                .instructions(&listings, &fee_vault, &keeper)
+                .into_iter()
+                .try_for_each(|instructions: Vec<Instruction>| {
+                    let signature = self.chain.send(&instructions)?;
+                    info!("{task}: {signature}");
+                    Ok::<_, ClientError>(())
+                });
+            match result {
+                Ok(()) => {
+                    self.state.record_success(&key, now);
+                    report.sent += 1;
+                }
+                Err(error) => {
+                    let retry_at = self.state.record_failure(&key, now, &error.to_string());
+                    warn!("{task} failed, retrying after {retry_at}: {error}");
+                    report.failed += 1;
+                }
+            }
+            self.state.save(&self.state_path)?;
+        }
+
+        self.state.finish_tick(now);
+        self.state.save(&self.state_path)?;
+        Ok(report)
+    }
+}
@@ -0,0 +1,69 @@
+//! `fracvault-keeper`: polls the program and performs housekeeping until stopped.
+
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use fracvault_client::read_keypair_file;
+use fracvault_client::rpc::{RpcClient, DEFAULT_URL};
+use fracvault_keeper::{Chain, Keeper, RpcChain};
+use log::{error, info};
+
+#[derive(Parser)]
+#[command(name = "fracvault-keeper", version, about = "Drive FracVault listing lifecycles")]
+struct Args {
+    /// JSON-RPC endpoint.
+    #[arg(long, short = 'u', env = "FRACVAULT_URL", default_value = DEFAULT_URL)]
+    url: String,
+
+    /// Keeper wallet: pays transaction fees and receives keeper bounties.
+    #[arg(long, short = 'k', env = "FRACVAULT_KEYPAIR")]
+    keypair: PathBuf,
+
+    /// Where attempts and backoff are persisted between runs.
+    #[arg(long, env = "FRACVAULT_KEEPER_STATE", default_value = "fracvault-keeper.json")]
+    state: PathBuf,
+
+    /// Seconds between polls.
+    #[arg(long, default_value_t = 10)]
+    interval: u64,
+
+    /// Run a single pass and exit.
+    #[arg(long)]
+    once: bool,
+}
+
+fn main() -> Result<()> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let args = Args::parse();
+
+    let keypair = read_keypair_file(&args.keypair)
+        .map_err(|e| anyhow!("reading keypair {}: {e}", args.keypair.display()))?;
+    let chain = RpcChain::new(RpcClient::new(args.url.clone()), keypair);
+    let mut keeper = Keeper::new(chain, &args.state)?;
+    info!(
+        "keeper {} on {}, state in {}",
+        keeper.chain().keeper(),
+        args.url,
+        args.state.display()
+    );
+
+    loop {
+        let result = keeper.tick();
+        match &result {
+            Ok(report) if report.planned > 0 => info!(
+                "tick: {} planned, {} sent, {} failed, {} deferred",
+                report.planned, report.sent, report.failed, report.deferred
+            ),
+            Ok(_) => {}
+            // Transient RPC failures: log and poll again.
+            Err(e) => error!("tick failed: {e}"),
+        }
+        if args.once {
+            return result.map(|_| ()).map_err(Into::into);
+        }
+        thread::sleep(Duration::from_secs(args.interval));
+    }
+}
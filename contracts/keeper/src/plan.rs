@@ -0,0 +1,111 @@
+//! Turns on-chain state into keeper tasks.
+
+use std::fmt;
+
+use fracvault_client::instructions as ix;
+use fracvault_client::lifecycle::{next_actions, NextAction};
+use fracvault_client::program::{Listing, Proposal, ProposalStatus};
+use fracvault_client::{ClientError, Instruction, Pubkey};
+
+use crate::chain::Chain;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Task {
+    Execute { listing: Pubkey },
+    CrankRefunds { listing: Pubkey, wallets: Vec<Pubkey> },
+    Reclaim { listing: Pubkey },
+    Finalize { listing: Pubkey, proposal_id: u32 },
+}
+
+impl Task {
+    /// Stable identity of the task across ticks, used to key its state.
+    pub fn key(&self) -> String {
+        match self {
+            Task::Execute { listing } => format!("execute:{listing}"),
+            Task::CrankRefunds { listing, .. } => format!("crank_refunds:{listing}"),
+            Task::Reclaim { listing } => format!("reclaim:{listing}"),
+            Task::Finalize { listing, proposal_id } => format!("finalize:{listing}:{proposal_id}"),
+        }
+    }
+
+    /// The task's transactions, in order. Only refund cranks span more than one.
+    pub fn instructions(
+        &self,
+        listings: &[(Pubkey, Listing)],
+        fee_vault: &Pubkey,
+        keeper: &Pubkey,
+    ) -> Vec<Vec<Instruction>> {
+        let listing_state = |address: &Pubkey| {
+            listings
+                .iter()
+                .find(|(key, _)| key == address)
+                .map(|(_, listing)| listing)
+                .expect("tasks are planned from the listings they reference")
+        };
+        match self {
+            Task::Execute { listing } => {
+                vec![vec![ix::execute_purchase(listing, listing_state(listing), fee_vault, keeper)]]
+            }
+            Task::CrankRefunds { listing, wallets } => wallets
+                .chunks(ix::CRANK_REFUNDS_BATCH)
+                .map(|batch| vec![ix::crank_refunds(listing, keeper, batch)])
+                .collect(),
+            Task::Reclaim { listing } => vec![vec![ix::reclaim_nft(listing, listing_state(listing))]],
+            Task::Finalize { listing, proposal_id } => {
+                vec![vec![ix::finalize_proposal(listing, *proposal_id, keeper)]]
+            }
+        }
+    }
+}
+
+impl fmt::Display for Task {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Task::Execute { listing } => write!(f, "execute listing {listing}"),
+            Task::CrankRefunds { listing, wallets } => {
+                write!(f, "crank {} refunds of listing {listing}", wallets.len())
+            }
+            Task::Reclaim { listing } => write!(f, "reclaim nft of listing {listing}"),
+            Task::Finalize { listing, proposal_id } => {
+                write!(f, "finalize proposal {proposal_id} of listing {listing}")
+            }
+        }
+    }
+}
+
+/// Every task the program would currently accept, executions first.
+pub fn plan(
+    chain: &impl Chain,
+    listings: &[(Pubkey, Listing)],
+    proposals: &[(Pubkey, Proposal)],
+    now: i64,
+) -> Result<Vec<Task>, ClientError> {
+    let mut tasks = Vec::new();
+    for (address, listing) in listings {
+        for action in next_actions(listing, now) {
+            match action {
+                NextAction::Execute { .. } => tasks.push(Task::Execute { listing: *address }),
+                NextAction::Refund { .. } => {
+                    let wallets: Vec<Pubkey> = chain
+                        .contributions(address)?
+                        .into_iter()
+                        .filter(|(_, contribution)| !contribution.refund_claimed)
+                        .map(|(_, contribution)| contribution.wallet)
+                        .collect();
+                    if !wallets.is_empty() {
+                        tasks.push(Task::CrankRefunds { listing: *address, wallets });
+                    }
+                }
+                NextAction::ReclaimNft => tasks.push(Task::Reclaim { listing: *address }),
+                _ => {}
+            }
+        }
+    }
+    for (_, proposal) in proposals {
+        if proposal.status == ProposalStatus::Active && now >= proposal.vote_deadline {
+            tasks.push(Task::Finalize { listing: proposal.listing, proposal_id: proposal.proposal_id });
+        }
+    }
+    tasks.sort_by_key(|task| !matches!(task, Task::Execute { .. }));
+    Ok(tasks)
+}
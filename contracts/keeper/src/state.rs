@@ -0,0 +1,141 @@
+//! Keeper state persisted between runs. Times are cluster unix timestamps.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::KeeperError;
+
+/// A completed task is not resent for this long, so lagging account reads cannot replay it.
+pub const SETTLE_SECONDS: i64 = 30;
+
+/// First retry delay after a failure; doubles per consecutive failure up to [`MAX_BACKOFF_SECONDS`].
+pub const BASE_BACKOFF_SECONDS: i64 = 30;
+pub const MAX_BACKOFF_SECONDS: i64 = 3600;
+
+/// Records untouched for this long belong to tasks that no longer exist and are dropped.
+pub const RECORD_TTL_SECONDS: i64 = 86400;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskRecord {
+    pub attempts: u32,
+    /// Consecutive failures since the last success.
+    pub failures: u32,
+    pub last_attempt: i64,
+    pub completed_at: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+impl TaskRecord {
+    pub fn retry_at(&self) -> i64 {
+        if self.failures == 0 {
+            return self.last_attempt;
+        }
+        let backoff = BASE_BACKOFF_SECONDS
+            .saturating_mul(1 << (self.failures - 1).min(16))
+            .min(MAX_BACKOFF_SECONDS);
+        self.last_attempt + backoff
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct State {
+    pub last_tick: Option<i64>,
+    pub completed: u64,
+    pub failed: u64,
+    pub tasks: BTreeMap<String, TaskRecord>,
+}
+
+impl State {
+    /// Loads `path`, or starts empty if it does not exist yet.
+    pub fn load(path: &Path) -> Result<Self, KeeperError> {
+        match fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| state_error(path, e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(state_error(path, e)),
+        }
+    }
+
+    /// Writes through a temporary file and a rename, so a crash never leaves a torn file.
+    pub fn save(&self, path: &Path) -> Result<(), KeeperError> {
+        let json = serde_json::to_vec_pretty(self).map_err(|e| state_error(path, e))?;
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, json).map_err(|e| state_error(path, e))?;
+        fs::rename(&tmp, path).map_err(|e| state_error(path, e))
+    }
+
+    pub fn should_attempt(&self, key: &str, now: i64) -> bool {
+        let Some(record) = self.tasks.get(key) else {
+            return true;
+        };
+        if record.completed_at.is_some_and(|completed_at| now < completed_at + SETTLE_SECONDS) {
+            return false;
+        }
+        now >= record.retry_at()
+    }
+
+    pub fn record_success(&mut self, key: &str, now: i64) {
+        let record = self.tasks.entry(key.to_owned()).or_default();
+        record.attempts += 1;
+        record.failures = 0;
+        record.last_attempt = now;
+        record.completed_at = Some(now);
+        record.last_error = None;
+        self.completed += 1;
+    }
+
+    /// Returns when the task may be retried.
+    pub fn record_failure(&mut self, key: &str, now: i64, error: &str) -> i64 {
+        let record = self.tasks.entry(key.to_owned()).or_default();
+        record.attempts += 1;
+        record.failures += 1;
+        record.last_attempt = now;
+        record.last_error = Some(error.to_owned());
+        self.failed += 1;
+        record.retry_at()
+    }
+
+    pub fn finish_tick(&mut self, now: i64) {
+        self.last_tick = Some(now);
+        self.tasks.retain(|_, record| now < record.last_attempt + RECORD_TTL_SECONDS);
+    }
+}
+
+fn state_error(path: &Path, error: impl std::fmt::Display) -> KeeperError {
+    KeeperError::State { path: path.to_owned(), message: error.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failures_back_off_exponentially_up_to_a_cap() {
+        let mut state = State::default();
+        assert_eq!(state.record_failure("t", 1000, "boom"), 1030);
+        assert!(!state.should_attempt("t", 1029));
+        assert!(state.should_attempt("t", 1030));
+        assert_eq!(state.record_failure("t", 1030, "boom"), 1090);
+        for _ in 0..10 {
+            state.record_failure("t", 2000, "boom");
+        }
+        assert_eq!(state.tasks["t"].retry_at(), 2000 + MAX_BACKOFF_SECONDS);
+
+        state.record_success("t", 9000);
+        assert_eq!(state.tasks["t"].failures, 0);
+        assert!(!state.should_attempt("t", 9000 + SETTLE_SECONDS - 1));
+        assert!(state.should_attempt("t", 9000 + SETTLE_SECONDS));
+    }
+
+    #[test]
+    fn stale_records_are_dropped() {
+        let mut state = State::default();
+        state.record_failure("old", 0, "boom");
+        state.record_success("new", RECORD_TTL_SECONDS);
+        state.finish_tick(RECORD_TTL_SECONDS);
+        assert_eq!(state.tasks.keys().collect::<Vec<_>>(), ["new"]);
+        assert_eq!(state.last_tick, Some(RECORD_TTL_SECONDS));
+    }
+}
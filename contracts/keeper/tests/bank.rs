@@ -0,0 +1,215 @@
+//! Runs the keeper against the program on the workspace test bank: every planned task is executed
+//! by the program, and the tests warp the bank's clock past deadlines, execution windows, auction
+//! ends and vote ends.
+
+#[path = "../../programs/workspace/tests/harness/mod.rs"]
+mod harness;
+
+use std::cell::RefCell;
+use std::path::PathBuf;
+
+use anchor_lang::prelude::Rent;
+use anchor_lang::Space;
+use fracvault_client::instructions as ix;
+use fracvault_client::lifecycle::EXECUTION_WINDOW_SECONDS;
+use fracvault_client::program::{
+    Config, Contribution, CreateListingArgs, DecayCurve, DutchAuctionParams, Listing, ListingStatus, Proposal,
+    ProposalStatus,
+};
+use fracvault_client::{pda, ClientError, Instruction, Pubkey, Signature};
+use fracvault_keeper::{Chain, Keeper, TickReport};
+use harness::{assert_ok, listing_args, Env, SOL};
+
+struct BankChain {
+    env: RefCell<Env>,
+    keeper: Pubkey,
+}
+
+impl BankChain {
+    /// A bank whose config pays `keeper_bounty_lamports` per keeper action.
+    fn new(keeper_bounty_lamports: u64) -> Self {
+        let mut env = Env::new();
+        let authority = env.authority;
+        assert_ok(env.send(ix::update_config(&authority, None, None, Some(keeper_bounty_lamports))));
+        let keeper = env.wallet();
+        Self { env: RefCell::new(env), keeper }
+    }
+
+    fn balance(&self, address: &Pubkey) -> u64 {
+        self.env.borrow().bank.lamports(address)
+    }
+}
+
+impl Chain for BankChain {
+    fn now(&self) -> Result<i64, ClientError> {
+        Ok(self.env.borrow().now())
+    }
+
+    fn config(&self) -> Result<Config, ClientError> {
+        Ok(self.env.borrow().config())
+    }
+
+    fn listings(&self) -> Result<Vec<(Pubkey, Listing)>, ClientError> {
+        Ok(self.env.borrow().bank.program_accounts())
+    }
+
+    fn contributions(&self, listing: &Pubkey) -> Result<Vec<(Pubkey, Contribution)>, ClientError> {
+        let contributions = self.env.borrow().bank.program_accounts::<Contribution>();
+        Ok(contributions.into_iter().filter(|(_, contribution)| contribution.listing == *listing).collect())
+    }
+
+    fn proposals(&self) -> Result<Vec<(Pubkey, Proposal)>, ClientError> {
+        Ok(self.env.borrow().bank.program_accounts())
+    }
+
+    fn keeper(&self) -> Pubkey {
+        self.keeper
+    }
+
+    fn send(&self, instructions: &[Instruction]) -> Result<Signature, ClientError> {
+        self.env
+            .borrow_mut()
+            .bank
+            .send(instructions)
+            .map(|_| Signature::default())
+            .map_err(|failure| ClientError::Transaction(format!("{}: {:?}", failure.error, failure.logs)))
+    }
+}
+
+fn state_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("fracvault-keeper-bank-{}-{name}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn executes_a_funded_listing_then_finalizes_its_proposal() {
+    let chain = BankChain::new(1_000_000);
+    let (listed, wallets) = {
+        let mut env = chain.env.borrow_mut();
+        let listed = env.list(CreateListingArgs { keeper_tip_lamports: 3_000_000, ..listing_args() });
+        let wallets = env.fund(&listed.key, &[6000, 4000]);
+        (listed, wallets)
+    };
+    let mut keeper = Keeper::new(&chain, state_path("execute")).unwrap();
+
+    let before = chain.balance(&chain.keeper);
+    assert_eq!(keeper.tick().unwrap(), TickReport { planned: 1, sent: 1, ..TickReport::default() });
+    let listing = chain.env.borrow().listing(&listed.key);
+    assert!(listing.status == ListingStatus::Custodied);
+    assert_eq!(chain.balance(&chain.keeper) - before, 1_000_000);
+    assert_eq!(chain.env.borrow().contribution(&listed.key, &wallets[0]).bps, 6000);
+
+    // Nothing to do while the proposal is open.
+    let proposal = {
+        let mut env = chain.env.borrow_mut();
+        assert_ok(env.send(ix::create_proposal(&listed.key, &listing, &wallets[1], 20 * SOL, 3600)));
+        pda::proposal(&listed.key, 0).0
+    };
+    chain.env.borrow_mut().bank.warp(3599);
+    assert_eq!(keeper.tick().unwrap(), TickReport::default());
+
+    chain.env.borrow_mut().bank.warp(1);
+    let before = chain.balance(&chain.keeper);
+    assert_eq!(keeper.tick().unwrap(), TickReport { planned: 1, sent: 1, ..TickReport::default() });
+    let resolved: Proposal = chain.env.borrow().bank.get(&proposal);
+    assert!(resolved.status == ProposalStatus::Rejected);
+    assert_eq!(chain.balance(&chain.keeper) - before, 1_000_000);
+
+    chain.env.borrow_mut().bank.warp(60);
+    assert_eq!(keeper.tick().unwrap(), TickReport::default());
+    assert_eq!(keeper.state().completed, 2);
+}
+
+#[test]
+fn refunds_and_reclaims_a_listing_that_missed_its_deadline() {
+    let chain = BankChain::new(1_000_000);
+    let (listed, wallets) = {
+        let mut env = chain.env.borrow_mut();
+        let listed = env.list(CreateListingArgs { keeper_tip_lamports: 1_000_000, ..listing_args() });
+        let wallets = env.fund(&listed.key, &[500; 12]);
+        (listed, wallets)
+    };
+    let mut keeper = Keeper::new(&chain, state_path("expired")).unwrap();
+    assert_eq!(keeper.tick().unwrap(), TickReport::default());
+
+    let deadline = chain.env.borrow().listing(&listed.key).deadline;
+    chain.env.borrow_mut().bank.warp_to(deadline);
+    let before = chain.balance(&chain.keeper);
+    assert_eq!(keeper.tick().unwrap(), TickReport { planned: 2, sent: 2, ..TickReport::default() });
+    // Two cranks of 10 and 2 positions share one bounty, less rounding.
+    assert_eq!(chain.balance(&chain.keeper) - before, 833_333 + 166_666);
+
+    // Every position is refunded in full, rent included, and the NFT is back with the seller.
+    let env = chain.env.borrow();
+    for wallet in &wallets {
+        assert_eq!(env.bank.lamports(wallet), 1000 * SOL);
+        assert!(!env.bank.exists(&pda::contribution(&listed.key, wallet).0));
+    }
+    let listing = env.listing(&listed.key);
+    assert!(listing.status == ListingStatus::Refunded && listing.nft_reclaimed);
+    assert_eq!(env.bank.token_account(&pda::associated_token(&listed.seller, &listed.nft_mint)).amount, 1);
+    drop(env);
+
+    chain.env.borrow_mut().bank.warp(60);
+    assert_eq!(keeper.tick().unwrap(), TickReport::default());
+}
+
+#[test]
+fn refunds_a_funded_listing_once_its_execution_window_closes() {
+    let chain = BankChain::new(0);
+    let (listed, wallets) = {
+        let mut env = chain.env.borrow_mut();
+        let listed = env.list(listing_args());
+        let wallets = env.fund(&listed.key, &[10000]);
+        (listed, wallets)
+    };
+    let funded_at = chain.env.borrow().listing(&listed.key).funded_at;
+    chain.env.borrow_mut().bank.warp_to(funded_at + EXECUTION_WINDOW_SECONDS + 1);
+
+    let mut keeper = Keeper::new(&chain, state_path("window")).unwrap();
+    assert_eq!(keeper.tick().unwrap(), TickReport { planned: 2, sent: 2, ..TickReport::default() });
+    let env = chain.env.borrow();
+    assert!(env.listing(&listed.key).status == ListingStatus::Refunded);
+    assert_eq!(env.bank.lamports(&wallets[0]), 1000 * SOL);
+}
+
+#[test]
+fn executes_a_partial_auction_fill_at_its_clearing_price() {
+    let chain = BankChain::new(0);
+    let (listed, early) = {
+        let mut env = chain.env.borrow_mut();
+        let args = CreateListingArgs {
+            deadline_offset: 2 * 86400,
+            min_fill_bps: 5000,
+            auction: Some(DutchAuctionParams { floor_price_lamports: 5 * SOL, curve: DecayCurve::Linear, duration: 86400 }),
+            ..listing_args()
+        };
+        let listed = env.list(args);
+        let early = env.fund(&listed.key, &[3000])[0];
+        (listed, early)
+    };
+    let mut keeper = Keeper::new(&chain, state_path("auction")).unwrap();
+
+    // Past the auction's end the price sits at the floor, but the listing is open until its deadline.
+    chain.env.borrow_mut().bank.warp(86400);
+    chain.env.borrow_mut().fund(&listed.key, &[3000]);
+    assert_eq!(chain.env.borrow().listing(&listed.key).price_lamports, 5 * SOL);
+    assert_eq!(keeper.tick().unwrap(), TickReport::default());
+
+    let deadline = chain.env.borrow().listing(&listed.key).deadline;
+    chain.env.borrow_mut().bank.warp_to(deadline);
+    let seller_before = chain.balance(&listed.seller);
+    assert_eq!(keeper.tick().unwrap(), TickReport { planned: 1, sent: 1, ..TickReport::default() });
+
+    // 6000 bps sell at the 5 SOL clearing price, less the rent of the seller's 4000 bps position;
+    // the early contributor can claim the difference.
+    let mut env = chain.env.borrow_mut();
+    assert!(env.listing(&listed.key).status == ListingStatus::Custodied);
+    let position_rent = Rent::default().minimum_balance(8 + Contribution::INIT_SPACE);
+    assert_eq!(env.bank.lamports(&listed.seller) - seller_before, 3 * SOL - position_rent);
+    assert_eq!(env.contribution(&listed.key, &listed.seller).bps, 4000);
+    let before = env.bank.lamports(&early);
+    assert_ok(env.send(ix::claim_auction_rebate(&listed.key, &early)));
+    assert_eq!(env.bank.lamports(&early) - before, 1_500_000_000 + 15_000_000);
+}
@@ -0,0 +1,244 @@
+//! Keeper bookkeeping (deduplication, retry backoff, restarts) against a scripted chain that only
+//! records what it is sent; `bank.rs` runs the tasks through the program.
+
+use std::cell::{Cell, RefCell};
+use std::path::PathBuf;
+
+use fracvault_client::instructions as ix;
+use fracvault_client::lifecycle::EXECUTION_WINDOW_SECONDS;
+use fracvault_client::program::{Config, Contribution, Listing, ListingStatus, Proposal, ProposalStatus};
+use fracvault_client::{pda, ClientError, Instruction, Pubkey, Signature};
+use fracvault_keeper::{Chain, Keeper, TickReport};
+
+const START: i64 = 1_700_000_000;
+
+struct FakeChain {
+    now: Cell<i64>,
+    keeper: Pubkey,
+    fee_vault: Pubkey,
+    listings: RefCell<Vec<(Pubkey, Listing)>>,
+    contributions: RefCell<Vec<(Pubkey, Contribution)>>,
+    proposals: RefCell<Vec<(Pubkey, Proposal)>>,
+    sent: RefCell<Vec<Vec<Instruction>>>,
+    fail_sends: Cell<bool>,
+}
+
+impl FakeChain {
+    fn new() -> Self {
+        Self {
+            now: Cell::new(START),
+            keeper: Pubkey::new_unique(),
+            fee_vault: Pubkey::new_unique(),
+            listings: RefCell::default(),
+            contributions: RefCell::default(),
+            proposals: RefCell::default(),
+            sent: RefCell::default(),
+            fail_sends: Cell::new(false),
+        }
+    }
+
+    fn warp(&self, seconds: i64) {
+        self.now.set(self.now.get() + seconds);
+    }
+
+    fn add_listing(&self, listing: Listing) -> Pubkey {
+        let address = pda::listing(&listing.nft_mint).0;
+        self.listings.borrow_mut().push((address, listing));
+        address
+    }
+
+    fn set_status(&self, address: &Pubkey, status: ListingStatus) {
+        for (key, listing) in self.listings.borrow_mut().iter_mut() {
+            if key == address {
+                listing.status = status.clone();
+            }
+        }
+    }
+
+    fn listing(&self, address: &Pubkey) -> Listing {
+        self.listings.borrow().iter().find(|(key, _)| key == address).unwrap().1.clone()
+    }
+
+    fn take_sent(&self) -> Vec<Vec<Instruction>> {
+        self.sent.take()
+    }
+}
+
+impl Chain for FakeChain {
+    fn now(&self) -> Result<i64, ClientError> {
+        Ok(self.now.get())
+    }
+
+    fn config(&self) -> Result<Config, ClientError> {
+        Ok(Config {
+            authority: Pubkey::new_unique(),
+            custody_fee_bps: 100,
+            fee_vault: self.fee_vault,
+            bump: 255,
+            allowlist_enabled: false,
+            royalties_enabled: false,
+            keeper_bounty_lamports: 1_000_000,
+            version: Config::VERSION,
+        })
+    }
+
+    fn listings(&self) -> Result<Vec<(Pubkey, Listing)>, ClientError> {
+        Ok(self.listings.borrow().clone())
+    }
+
+    fn contributions(&self, listing: &Pubkey) -> Result<Vec<(Pubkey, Contribution)>, ClientError> {
+        Ok(self
+            .contributions
+            .borrow()
+            .iter()
+            .filter(|(_, contribution)| contribution.listing == *listing)
+            .cloned()
+            .collect())
+    }
+
+    fn proposals(&self) -> Result<Vec<(Pubkey, Proposal)>, ClientError> {
+        Ok(self.proposals.borrow().clone())
+    }
+
+    fn keeper(&self) -> Pubkey {
+        self.keeper
+    }
+
+    fn send(&self, instructions: &[Instruction]) -> Result<Signature, ClientError> {
+        if self.fail_sends.get() {
+            return Err(ClientError::Transaction("simulated failure".into()));
+        }
+        self.sent.borrow_mut().push(instructions.to_vec());
+        Ok(Signature::default())
+    }
+}
+
+fn listing(status: ListingStatus, bps_sold: u16) -> Listing {
+    Listing { bps_sold, deadline: START + 86400, ..Listing::fixture(status) }
+}
+
+fn contribution(listing: &Pubkey, bps: u16) -> (Pubkey, Contribution) {
+    let wallet = Pubkey::new_unique();
+    let contribution = Contribution {
+        listing: *listing,
+        wallet,
+        bps,
+        principal_lamports: 100_000 * bps as u64,
+        fee_lamports: 1_000 * bps as u64,
+        refund_claimed: false,
+        bump: 255,
+        version: Contribution::VERSION,
+    };
+    (pda::contribution(listing, &wallet).0, contribution)
+}
+
+fn state_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("fracvault-keeper-{}-{name}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn executes_funded_listing_inside_its_window() {
+    let chain = FakeChain::new();
+    let mut funded = listing(ListingStatus::Funded, 10000);
+    funded.funded_at = START;
+    let address = chain.add_listing(funded);
+    let mut keeper = Keeper::new(&chain, state_path("execute")).unwrap();
+
+    chain.warp(600);
+    assert_eq!(keeper.tick().unwrap(), TickReport { planned: 1, sent: 1, ..TickReport::default() });
+    let expected = ix::execute_purchase(&address, &chain.listing(&address), &chain.fee_vault, &chain.keeper);
+    assert_eq!(chain.take_sent(), vec![vec![expected]]);
+
+    // A lagging read still shows the listing as funded: the completed task is not resent.
+    chain.warp(5);
+    assert_eq!(keeper.tick().unwrap().deferred, 1);
+    assert!(chain.take_sent().is_empty());
+
+    chain.set_status(&address, ListingStatus::Custodied);
+    chain.warp(60);
+    assert_eq!(keeper.tick().unwrap(), TickReport::default());
+    assert_eq!(keeper.state().completed, 1);
+}
+
+#[test]
+fn refunds_and_reclaims_once_the_window_has_passed() {
+    let chain = FakeChain::new();
+    let mut funded = listing(ListingStatus::Funded, 10000);
+    funded.funded_at = START;
+    let address = chain.add_listing(funded);
+    for _ in 0..12 {
+        chain.contributions.borrow_mut().push(contribution(&address, 800));
+    }
+    let mut refunded = contribution(&address, 400);
+    refunded.1.refund_claimed = true;
+    chain.contributions.borrow_mut().push(refunded);
+    let mut keeper = Keeper::new(&chain, state_path("refund")).unwrap();
+
+    chain.warp(EXECUTION_WINDOW_SECONDS + 1);
+    assert_eq!(keeper.tick().unwrap(), TickReport { planned: 2, sent: 2, ..TickReport::default() });
+
+    let sent = chain.take_sent();
+    let listing = chain.listing(&address);
+    let cranked: usize = sent[..2].iter().map(|tx| (tx[0].accounts.len() - 7) / 2).sum();
+    assert_eq!(cranked, 12);
+    assert_eq!(sent[2], vec![ix::reclaim_nft(&address, &listing)]);
+}
+
+#[test]
+fn failing_tasks_back_off_across_restarts() {
+    let chain = FakeChain::new();
+    let open = listing(ListingStatus::Open, 0);
+    let deadline = open.deadline;
+    chain.add_listing(open);
+    let path = state_path("backoff");
+
+    chain.now.set(deadline);
+    chain.fail_sends.set(true);
+    let report = Keeper::new(&chain, &path).unwrap().tick().unwrap();
+    assert_eq!((report.planned, report.failed), (1, 1));
+
+    // Restarted keeper resumes the backoff from the state file.
+    chain.warp(10);
+    let mut keeper = Keeper::new(&chain, &path).unwrap();
+    assert_eq!(keeper.state().failed, 1);
+    assert_eq!(keeper.tick().unwrap().deferred, 1);
+
+    chain.fail_sends.set(false);
+    chain.warp(20);
+    assert_eq!(keeper.tick().unwrap().sent, 1);
+    assert_eq!(chain.take_sent().len(), 1);
+    assert_eq!(Keeper::new(&chain, &path).unwrap().state().completed, 1);
+}
+
+#[test]
+fn finalizes_proposals_after_voting_ends() {
+    let chain = FakeChain::new();
+    let address = chain.add_listing(listing(ListingStatus::Custodied, 10000));
+    let proposal = |proposal_id: u32, vote_deadline: i64, status: ProposalStatus| {
+        let key = pda::proposal(&address, proposal_id).0;
+        let proposal = Proposal {
+            listing: address,
+            proposer: Pubkey::new_unique(),
+            proposal_id,
+            sale_price_lamports: 2_000_000_000,
+            vote_deadline,
+            yes_bps: 3000,
+            no_bps: 1000,
+            status,
+            bump: 255,
+            version: Proposal::VERSION,
+        };
+        (key, proposal)
+    };
+    chain.proposals.borrow_mut().extend([
+        proposal(0, START - 10, ProposalStatus::Rejected),
+        proposal(1, START - 10, ProposalStatus::Active),
+        proposal(2, START + 3600, ProposalStatus::Active),
+    ]);
+    let mut keeper = Keeper::new(&chain, state_path("finalize")).unwrap();
+
+    assert_eq!(keeper.tick().unwrap().sent, 1);
+    assert_eq!(chain.take_sent(), vec![vec![ix::finalize_proposal(&address, 1, &chain.keeper)]]);
+}
@@ -0,0 +1,34 @@
+//! Runs a keeper pass against a live `solana-test-validator` with the program deployed
+//! (`anchor localnet`). Ignored by default:
+//!
+//! `FRACVAULT_URL=http://127.0.0.1:8899 cargo test -p fracvault-keeper --test validator -- --ignored`
+
+use std::time::Duration;
+
+use fracvault_client::accounts::fetch_config;
+use fracvault_client::instructions as ix;
+use fracvault_client::rpc::{RpcClient, DEFAULT_URL};
+use fracvault_client::{Keypair, Signer};
+use fracvault_keeper::{Keeper, RpcChain};
+
+#[test]
+#[ignore = "needs a local validator with the program deployed"]
+fn keeper_pass_against_local_validator() {
+    let url = std::env::var("FRACVAULT_URL").unwrap_or_else(|_| DEFAULT_URL.into());
+    let rpc = RpcClient::new(url.clone());
+    let keeper = Keypair::new();
+    let airdrop = rpc.request_airdrop(&keeper.pubkey(), 2_000_000_000).unwrap();
+    rpc.confirm_transaction(&airdrop, Duration::from_secs(30)).unwrap();
+
+    if fetch_config(&rpc).unwrap().is_none() {
+        let init = ix::initialize_config(&keeper.pubkey(), &keeper.pubkey(), 100);
+        rpc.send_and_confirm(&[init], &keeper, &[]).unwrap();
+    }
+
+    let state = std::env::temp_dir().join(format!("fracvault-keeper-validator-{}.json", std::process::id()));
+    let mut keeper = Keeper::new(RpcChain::new(RpcClient::new(url), keeper), &state).unwrap();
+    let report = keeper.tick().unwrap();
+    assert_eq!(report.sent + report.failed + report.deferred, report.planned);
+    assert!(keeper.state().last_tick.is_some());
+    assert!(state.exists());
+}
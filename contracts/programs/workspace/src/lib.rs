@@ -1,9 +1,27 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer, Mint};
 use anchor_spl::associated_token::AssociatedToken;
+use mpl_token_metadata::accounts::{MasterEdition, Metadata};
+use mpl_token_metadata::types::Key as MetadataKey;
+
+pub mod math;
 
 declare_id!("5gc3CQE2ge6QQ6MyQzA8M7GLktquXxYAbroyW6rRfwMb");
 
+// Emits the event of a `Listing::transition` from an instruction handler.
+macro_rules! emit_transition {
+    ($ctx:ident, $event:expr) => {{
+        let ctx = &$ctx;
+        match $event {
+            TransitionEvent::Funded(event) => emit_cpi!(event),
+            TransitionEvent::Executed(event) => emit_cpi!(event),
+            TransitionEvent::Expired(event) => emit_cpi!(event),
+            TransitionEvent::Refunded(event) => emit_cpi!(event),
+        }
+    }};
+}
+
 #[program]
 pub mod workspace {
     use super::*;
@@ -18,27 +36,227 @@ pub mod workspace {
         let config = &mut ctx.accounts.config;
         config.authority = ctx.accounts.authority.key();
         // Operational authority used for non-custodial safety controls (e.g., pausing contributions).
-        // This key MUST NOT have the ability to move NFTs or user funds.        config.custody_fee_bps = custody_fee_bps;
+        // This key MUST NOT have the ability to move NFTs or user funds.
+        config.custody_fee_bps = custody_fee_bps;
         config.fee_vault = ctx.accounts.fee_vault.key();
         config.bump = ctx.bumps.config;
-        
+        config.allowlist_enabled = false;
+        config.royalties_enabled = false;
+        config.keeper_bounty_lamports = 0;
+        config.version = Config::VERSION;
+
+        emit_cpi!(ConfigInitialized {
+            authority: config.authority,
+            fee_vault: config.fee_vault,
+            custody_fee_bps,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    // allowlist_enabled: Option<bool>, Restrict new listings to allowlisted collections
+    // royalties_enabled: Option<bool>, Pay creator royalties on sales of newly created listings
+    // keeper_bounty_lamports: Option<u64>, Paid per successful keeper crank, 1000000 = 0.001 SOL
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        allowlist_enabled: Option<bool>,
+        royalties_enabled: Option<bool>,
+        keeper_bounty_lamports: Option<u64>,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        if let Some(allowlist_enabled) = allowlist_enabled {
+            config.allowlist_enabled = allowlist_enabled;
+        }
+        if let Some(royalties_enabled) = royalties_enabled {
+            config.royalties_enabled = royalties_enabled;
+        }
+        if let Some(keeper_bounty_lamports) = keeper_bounty_lamports {
+            config.keeper_bounty_lamports = keeper_bounty_lamports;
+        }
+
+        emit_cpi!(ConfigUpdated {
+            authority: config.authority,
+            allowlist_enabled: config.allowlist_enabled,
+            royalties_enabled: config.royalties_enabled,
+            keeper_bounty_lamports: config.keeper_bounty_lamports,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
         Ok(())
     }
 
-    // price_lamports: u64, Total price for NFT in lamports, 1000000000 = 1 SOL
-    // deadline_offset: i64, Seconds until deadline (3600-604800), 86400 = 1 day
-    pub fn create_listing(
-        ctx: Context<CreateListing>,
-        price_lamports: u64,
-        deadline_offset: i64,
+    // collection_mint: Pubkey, Verified collection mint to allow
+    // limits: CollectionLimits, Optional per-collection listing limits
+    pub fn add_collection(
+        ctx: Context<AddCollection>,
+        collection_mint: Pubkey,
+        limits: CollectionLimits,
     ) -> Result<()> {
+        limits.validate()?;
+
+        let entry = &mut ctx.accounts.collection_allowlist;
+        entry.collection = collection_mint;
+        entry.limits = limits.clone();
+        entry.bump = ctx.bumps.collection_allowlist;
+        entry.version = CollectionAllowlist::VERSION;
+
+        emit_cpi!(CollectionAllowlistUpdated {
+            collection: collection_mint,
+            limits: Some(limits),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    // limits: CollectionLimits, Replacement per-collection listing limits
+    pub fn update_collection(
+        ctx: Context<UpdateCollection>,
+        limits: CollectionLimits,
+    ) -> Result<()> {
+        limits.validate()?;
+
+        let entry = &mut ctx.accounts.collection_allowlist;
+        entry.limits = limits.clone();
+
+        emit_cpi!(CollectionAllowlistUpdated {
+            collection: entry.collection,
+            limits: Some(limits),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    // Closing the entry only affects future listings; existing listings are untouched.
+    pub fn remove_collection(ctx: Context<RemoveCollection>) -> Result<()> {
+        emit_cpi!(CollectionAllowlistUpdated {
+            collection: ctx.accounts.collection_allowlist.collection,
+            limits: None,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    // args: CreateListingArgs, the listing's price, deadline and optional terms
+    pub fn create_listing(ctx: Context<CreateListing>, args: CreateListingArgs) -> Result<()> {
+        let CreateListingArgs {
+            price_lamports,
+            deadline_offset,
+            retained_bps,
+            min_fill_bps,
+            auction,
+            presale,
+            max_bps_per_wallet,
+            min_bps_per_contribution,
+            soft_close,
+            keeper_tip_lamports,
+        } = args;
         let config = &ctx.accounts.config;
         require!(price_lamports > 0, ErrorCode::InvalidAmount);
-        require!(deadline_offset >= 3600 && deadline_offset <= 604800, ErrorCode::InvalidDeadline);
+        require!((3600..=604800).contains(&deadline_offset), ErrorCode::InvalidDeadline);
+        require!(retained_bps < 10000, ErrorCode::InvalidBps);
+        require!(min_fill_bps <= 10000 - retained_bps, ErrorCode::InvalidBps);
+        require!(max_bps_per_wallet <= 10000 - retained_bps, ErrorCode::InvalidBps);
+        require!(min_bps_per_contribution <= 10000 - retained_bps, ErrorCode::InvalidBps);
+        require!(
+            max_bps_per_wallet == 0 || min_bps_per_contribution <= max_bps_per_wallet,
+            ErrorCode::InvalidBps
+        );
+        if let Some(auction) = &auction {
+            auction.validate(price_lamports, deadline_offset)?;
+        }
+        if let Some(soft_close) = &soft_close {
+            soft_close.validate()?;
+        }
 
         let nft_account = &ctx.accounts.seller_nft_account;
         require!(nft_account.amount == 1, ErrorCode::InvalidNftOwnership);
 
+        // The escrowed mint must be a real NFT: indivisible, a single unit in existence, and unable
+        // to mint more. A live mint authority is only acceptable if it is the Metaplex master edition.
+        let nft_mint = &ctx.accounts.nft_mint;
+        require!(nft_mint.decimals == 0 && nft_mint.supply == 1, ErrorCode::InvalidNftMint);
+        if let COption::Some(mint_authority) = nft_mint.mint_authority {
+            let master_edition = &ctx.accounts.nft_master_edition;
+            require!(mint_authority == master_edition.key(), ErrorCode::InvalidNftMint);
+            require!(master_edition.owner == &mpl_token_metadata::ID, ErrorCode::InvalidNftMint);
+            let edition = MasterEdition::from_bytes(&master_edition.try_borrow_data()?)
+                .map_err(|_| error!(ErrorCode::InvalidNftMint))?;
+            require!(
+                edition.key == MetadataKey::MasterEditionV2 || edition.key == MetadataKey::MasterEditionV1,
+                ErrorCode::InvalidNftMint
+            );
+        }
+
+        // Snapshot the verified collection and creators so indexers and frontends can trust them.
+        let nft_metadata = &ctx.accounts.nft_metadata;
+        require!(nft_metadata.owner == &mpl_token_metadata::ID, ErrorCode::InvalidMetadata);
+        let metadata = Metadata::from_bytes(&nft_metadata.try_borrow_data()?)
+            .map_err(|_| error!(ErrorCode::InvalidMetadata))?;
+        require!(metadata.key == MetadataKey::MetadataV1, ErrorCode::InvalidMetadata);
+        require!(metadata.mint == nft_mint.key(), ErrorCode::InvalidMetadata);
+
+        let collection = metadata.collection
+            .filter(|collection| collection.verified)
+            .map(|collection| collection.key);
+        let creators: Vec<ListingCreator> = metadata.creators
+            .unwrap_or_default()
+            .iter()
+            .map(|creator| ListingCreator {
+                address: creator.address,
+                verified: creator.verified,
+                share: creator.share,
+            })
+            .collect();
+        require!(creators.len() <= MAX_CREATORS, ErrorCode::InvalidMetadata);
+
+        // Royalty mode is snapshotted per listing so a config change never alters a live raise.
+        let royalty_bps = if config.royalties_enabled {
+            require!(metadata.seller_fee_basis_points <= 10000, ErrorCode::InvalidMetadata);
+            metadata.seller_fee_basis_points
+        } else {
+            0
+        };
+
+        // Collection curation: an NFT with a verified collection must be listed with that
+        // collection's allowlist PDA, and the entry's limits apply whenever it exists, whether or not
+        // allowlist mode is on. With allowlist mode on, only collections with an entry can be listed.
+        let entry = match collection {
+            Some(collection) => {
+                let account = ctx.accounts.collection_allowlist.as_ref().ok_or(ErrorCode::InvalidAllowlistEntry)?;
+                let (expected, _) = Pubkey::find_program_address(&[b"allowlist", collection.as_ref()], &crate::ID);
+                require_keys_eq!(account.key(), expected, ErrorCode::InvalidAllowlistEntry);
+                if account.owner == &crate::ID {
+                    Some(CollectionAllowlist::try_deserialize(&mut &account.try_borrow_data()?[..])?)
+                } else {
+                    None
+                }
+            }
+            None => None,
+        };
+        let mut custody_fee_bps = config.custody_fee_bps;
+        match &entry {
+            Some(entry) => {
+                let limits = &entry.limits;
+                if let Some(max_price) = limits.max_price_lamports {
+                    require!(price_lamports <= max_price, ErrorCode::InvalidAmount);
+                }
+                if let Some(min_offset) = limits.min_deadline_offset {
+                    require!(deadline_offset >= min_offset, ErrorCode::InvalidDeadline);
+                }
+                if let Some(max_offset) = limits.max_deadline_offset {
+                    require!(deadline_offset <= max_offset, ErrorCode::InvalidDeadline);
+                }
+                if let Some(fee_bps) = limits.custody_fee_bps {
+                    custody_fee_bps = fee_bps;
+                }
+            }
+            None => require!(!config.allowlist_enabled, ErrorCode::CollectionNotAllowed),
+        }
+
         // Escrow the NFT into the program-controlled vault at listing creation.
         // This prevents a funded listing from being griefed by a seller who disappears.
         token::transfer(
@@ -54,26 +272,49 @@ pub mod workspace {
         )?;
 
 
-        let custody_fee = price_lamports
-            .checked_mul(config.custody_fee_bps as u64)
-            .ok_or(ErrorCode::MathOverflow)?
-            .checked_div(10000)
-            .ok_or(ErrorCode::MathOverflow)?;
+        // The seller opens the escrow with its rent-exempt reserve, plus the keeper tip pool.
+        let escrow_rent = Rent::get()?.minimum_balance(0);
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.seller.to_account_info(),
+                    to: ctx.accounts.escrow.to_account_info(),
+                },
+            ),
+            escrow_rent.checked_add(keeper_tip_lamports).ok_or(ErrorCode::MathOverflow)?,
+        )?;
 
-        let total_raise = price_lamports
-            .checked_add(custody_fee)
-            .ok_or(ErrorCode::MathOverflow)?;
+        // Only the portion offered to contributors is raised; the fee is charged on that portion.
+        let raise = math::raise(price_lamports, retained_bps, custody_fee_bps).ok_or(ErrorCode::MathOverflow)?;
 
         let clock = Clock::get()?;
         let deadline = clock.unix_timestamp
             .checked_add(deadline_offset)
             .ok_or(ErrorCode::MathOverflow)?;
+        if let Some(presale) = &presale {
+            require!(
+                presale.end_time > clock.unix_timestamp && presale.end_time <= deadline,
+                ErrorCode::InvalidDeadline
+            );
+        }
+        let soft_close = match soft_close {
+            Some(params) => Some(SoftClose {
+                trigger_seconds: params.trigger_seconds,
+                extension_seconds: params.extension_seconds,
+                hard_deadline: deadline
+                    .checked_add(params.max_extension_seconds)
+                    .ok_or(ErrorCode::MathOverflow)?,
+            }),
+            None => None,
+        };
 
-        let listing = &mut ctx.accounts.listing;        listing.nft_mint = ctx.accounts.nft_mint.key();
+        let listing = &mut ctx.accounts.listing;
+        listing.nft_mint = ctx.accounts.nft_mint.key();
         listing.nft_seller = ctx.accounts.seller.key();
         listing.price_lamports = price_lamports;
-        listing.custody_fee_lamports = custody_fee;
-        listing.total_raise_lamports = total_raise;
+        listing.custody_fee_lamports = raise.custody_fee_lamports;
+        listing.total_raise_lamports = raise.total_raise_lamports;
         listing.bps_sold = 0;
         listing.deadline = deadline;
         listing.funded_at = 0;
@@ -81,177 +322,365 @@ pub mod workspace {
         listing.vault = ctx.accounts.vault.key();
         listing.bump = ctx.bumps.listing;
         listing.proposal_count = 0;
+        listing.collection = collection;
+        listing.creators = creators;
+        listing.royalty_bps = royalty_bps;
+        listing.retained_bps = retained_bps;
+        listing.min_fill_bps = min_fill_bps;
+        listing.custody_fee_bps = custody_fee_bps;
+        listing.auction = auction.map(|params| DutchAuction {
+            start_price_lamports: price_lamports,
+            floor_price_lamports: params.floor_price_lamports,
+            curve: params.curve,
+            duration: params.duration,
+            start_time: clock.unix_timestamp,
+        });
+        listing.presale = presale;
+        listing.max_bps_per_wallet = max_bps_per_wallet;
+        listing.min_bps_per_contribution = min_bps_per_contribution;
+        listing.contributor_count = 0;
+        listing.soft_close = soft_close;
+        listing.keeper_tip_lamports = keeper_tip_lamports;
+        listing.refunded_bps = 0;
+        listing.refunded_lamports = 0;
+        listing.nft_reclaimed = false;
+        listing.escrow_bump = ctx.bumps.escrow;
+        listing.version = Listing::VERSION;
+
+        emit_cpi!(ListingCreated {
+            listing: listing.key(),
+            nft_mint: listing.nft_mint,
+            seller: listing.nft_seller,
+            price_lamports: listing.price_lamports,
+            custody_fee_lamports: listing.custody_fee_lamports,
+            total_raise_lamports: listing.total_raise_lamports,
+            retained_bps,
+            min_fill_bps,
+            deadline: listing.deadline,
+            auction: listing.auction.is_some(),
+            presale: listing.presale.is_some(),
+            keeper_tip_lamports,
+            timestamp: clock.unix_timestamp,
+        });
 
         Ok(())
     }
 
     // bps: u16, Basis points to purchase (1-10000), 1000 = 10%
+    // presale_proof: Option<PresaleProof>, Allowlist proof; required only during the presale
+    pub fn contribute(
+        ctx: Context<Contribute>,
+        bps: u16,
+        presale_proof: Option<PresaleProof>,
+    ) -> Result<()> {
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let escrow = ListingEscrow::new(&escrow_info, &ctx.accounts.listing, &system_program);
+        let events = apply_contribution(
+            &mut ctx.accounts.listing,
+            &mut ctx.accounts.contribution,
+            ctx.bumps.contribution,
+            &ctx.accounts.contributor,
+            &escrow,
+            bps,
+            presale_proof,
+        )?;
 
-pub fn contribute(ctx: Context<Contribute>, bps: u16) -> Result<()> {
-        let listing = &ctx.accounts.listing;        require!(listing.status == ListingStatus::Open, ErrorCode::InvalidListingStatus);
-        require!(bps >= 1 && bps <= 10000, ErrorCode::InvalidBps);
-
-        let clock = Clock::get()?;
-        require!(clock.unix_timestamp < listing.deadline - 60, ErrorCode::ListingExpired);
-
-        let remaining_bps = 10000u16.checked_sub(listing.bps_sold).ok_or(ErrorCode::MathOverflow)?;
-        require!(bps <= remaining_bps, ErrorCode::ExceedsAvailable);
-
-        let principal = listing.price_lamports
-            .checked_mul(bps as u64)
-            .ok_or(ErrorCode::MathOverflow)?
-            .checked_div(10000)
-            .ok_or(ErrorCode::MathOverflow)?;
+        emit_cpi!(events.contribution);
+        if let Some(event) = events.funded {
+            emit_transition!(ctx, event);
+        }
+        if let Some(event) = events.soft_close {
+            emit_cpi!(event);
+        }
+        if let Some(event) = events.deadline_extended {
+            emit_cpi!(event);
+        }
 
-        let fee_share = listing.custody_fee_lamports
-            .checked_mul(bps as u64)
-            .ok_or(ErrorCode::MathOverflow)?
-            .checked_div(10000)
-            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
 
-        let total_payment = principal
-            .checked_add(fee_share)
-            .ok_or(ErrorCode::MathOverflow)?;
+    // Same as `contribute`, but if this contribution completes the raise the purchase is settled in
+    // the same transaction, leaving no window in which a funded listing waits for an executor.
+    // Creator accounts are passed as remaining accounts, in the order of `listing.creators`.
+    // Not available to a seller who retains bps, whose position would be passed twice.
+    // bps: u16, Basis points to purchase (1-10000), 1000 = 10%
+    // presale_proof: Option<PresaleProof>, Allowlist proof; required only during the presale
+    pub fn contribute_and_execute<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ContributeAndExecute<'info>>,
+        bps: u16,
+        presale_proof: Option<PresaleProof>,
+    ) -> Result<()> {
+        // A seller who keeps bps would pass the same position as `contribution` and
+        // `seller_contribution`; Anchor writes each back separately, so one update would be lost.
+        require!(
+            ctx.accounts.seller_contribution.is_none() || ctx.accounts.contributor.key() != ctx.accounts.listing.nft_seller,
+            ErrorCode::SellerMustExecuteSeparately
+        );
 
-        anchor_lang::system_program::transfer(
-            CpiContext::new(
-                ctx.accounts.system_program.to_account_info(),
-                anchor_lang::system_program::Transfer {
-                    from: ctx.accounts.contributor.to_account_info(),
-                    to: ctx.accounts.listing.to_account_info(),
-                },
-            ),
-            total_payment,
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let escrow = ListingEscrow::new(&escrow_info, &ctx.accounts.listing, &system_program);
+        let events = apply_contribution(
+            &mut ctx.accounts.listing,
+            &mut ctx.accounts.contribution,
+            ctx.bumps.contribution,
+            &ctx.accounts.contributor,
+            &escrow,
+            bps,
+            presale_proof,
         )?;
 
-        let contribution = &mut ctx.accounts.contribution;
+        emit_cpi!(events.contribution);
+        if let Some(event) = events.funded {
+            emit_transition!(ctx, event);
+        }
+        if let Some(event) = events.soft_close {
+            emit_cpi!(event);
+        }
+        if let Some(event) = events.deadline_extended {
+            emit_cpi!(event);
+        }
 
-        // Support multiple contributions (top-ups) from the same wallet for the same listing.
-        // The PDA seed ties the contribution to (listing, contributor), so only the contributor can pay in.
-        if contribution.bps == 0 {
-            // First contribution initialization
-            contribution.listing = ctx.accounts.listing.key();
-            contribution.wallet = ctx.accounts.contributor.key();
-            contribution.refund_claimed = false;
-            contribution.bump = ctx.bumps.contribution;
-        } else {
-            // Safety: contribution must belong to this listing + wallet
-            require!(contribution.listing == ctx.accounts.listing.key(), ErrorCode::InvalidContribution);
-            require!(contribution.wallet == ctx.accounts.contributor.key(), ErrorCode::InvalidContribution);
-            require!(!contribution.refund_claimed, ErrorCode::AlreadyRefunded);
+        if ctx.accounts.listing.status == ListingStatus::Funded {
+            let settlement = settle_purchase(
+                &ctx.accounts.config,
+                &mut ctx.accounts.listing,
+                &escrow,
+                &ctx.accounts.seller.to_account_info(),
+                &ctx.accounts.fee_vault.to_account_info(),
+                ctx.accounts.seller_contribution.as_ref().map(|account| account.to_account_info()).as_ref(),
+                ctx.bumps.seller_contribution,
+                &ctx.accounts.contributor.to_account_info(),
+                ctx.remaining_accounts,
+            )?;
+
+            for event in settlement.royalties {
+                emit_cpi!(event);
+            }
+            if let Some(event) = settlement.keeper_bounty {
+                emit_cpi!(event);
+            }
+            emit_transition!(ctx, settlement.executed);
         }
 
-        // Accumulate
-        contribution.bps = contribution.bps.checked_add(bps).ok_or(ErrorCode::MathOverflow)?;
-        contribution.principal_lamports = contribution.principal_lamports.checked_add(principal).ok_or(ErrorCode::MathOverflow)?;
-        contribution.fee_lamports = contribution.fee_lamports.checked_add(fee_share).ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
 
-        let listing = &mut ctx.accounts.listing;
-        listing.bps_sold = listing.bps_sold.checked_add(bps).ok_or(ErrorCode::MathOverflow)?;
+    // Creator accounts are passed as remaining accounts, in the order of `listing.creators`.
+    pub fn execute_purchase<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecutePurchase<'info>>,
+    ) -> Result<()> {
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let escrow = ListingEscrow::new(&escrow_info, &ctx.accounts.listing, &system_program);
+        let settlement = settle_purchase(
+            &ctx.accounts.config,
+            &mut ctx.accounts.listing,
+            &escrow,
+            &ctx.accounts.seller.to_account_info(),
+            &ctx.accounts.fee_vault.to_account_info(),
+            ctx.accounts.seller_contribution.as_ref().map(|account| account.to_account_info()).as_ref(),
+            ctx.bumps.seller_contribution,
+            &ctx.accounts.executor.to_account_info(),
+            ctx.remaining_accounts,
+        )?;
 
-        if listing.bps_sold == 10000 {
-            let clock2 = Clock::get()?;
-            listing.status = ListingStatus::Funded;
-            listing.funded_at = clock2.unix_timestamp;
+        for event in settlement.royalties {
+            emit_cpi!(event);
+        }
+        if let Some(event) = settlement.keeper_bounty {
+            emit_cpi!(event);
         }
+        emit_transition!(ctx, settlement.executed);
 
         Ok(())
     }
 
-    pub fn execute_purchase(ctx: Context<ExecutePurchase>) -> Result<()> {
+
+    pub fn process_refund(ctx: Context<ProcessRefund>) -> Result<()> {
         let listing = &ctx.accounts.listing;
-        require!(listing.status == ListingStatus::Funded, ErrorCode::InvalidListingStatus);
+        let contribution = &ctx.accounts.contribution;
+
+        require!(!contribution.refund_claimed, ErrorCode::AlreadyRefunded);
 
-        // If a funded listing isn't executed within a reasonable window, allow contributors to refund.
-        // This prevents "no-one-called-execute" griefing.
         let clock = Clock::get()?;
-        if listing.funded_at > 0 && clock.unix_timestamp > listing.funded_at + 86400 {
-            return err!(ErrorCode::ExecutionWindowExpired);
+        require!(listing.is_refundable(clock.unix_timestamp), ErrorCode::NotRefundable);
+
+        let refund_amount = contribution.refund_amount()?;
+
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        ListingEscrow::new(&escrow_info, listing, &system_program)
+            .pay_out(&ctx.accounts.contributor.to_account_info(), refund_amount)?;
+
+        // The position is closed on exit and its rent returned, so nothing of it is left for a
+        // relisting of the same mint to find.
+        let contribution = &ctx.accounts.contribution;
+        let listing_key = ctx.accounts.listing.key();
+        let listing = &mut ctx.accounts.listing;
+        listing.record_refund(contribution.bps, refund_amount)?;
+        let refund = RefundProcessed {
+            listing: listing_key,
+            contributor: contribution.wallet,
+            bps: contribution.bps,
+            amount_lamports: refund_amount,
+            refunded_bps: listing.refunded_bps,
+            refunded_lamports: listing.refunded_lamports,
+            timestamp: clock.unix_timestamp,
+        };
+        let transitions = listing.wind_down(listing_key, clock.unix_timestamp)?;
+
+        emit_cpi!(refund);
+        for event in transitions {
+            emit_transition!(ctx, event);
         }
 
-        let price = listing.price_lamports;
-        let fee = listing.custody_fee_lamports;
+        Ok(())
+    }
+
 
-        // Ensure sufficient lamports (defensive; should be true if contributions succeeded).
+    // Permissionless: remaining accounts are (contribution, wallet) pairs. Each refund is paid to the
+    // wallet recorded on the contribution, along with the position's rent as it is closed; positions
+    // already refunded (and so closed) are skipped.
+    pub fn crank_refunds<'info>(ctx: Context<'_, '_, 'info, 'info, CrankRefunds<'info>>) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(ctx.accounts.listing.is_refundable(clock.unix_timestamp), ErrorCode::NotRefundable);
         require!(
-            **ctx.accounts.listing.to_account_info().try_borrow_lamports()? >= price + fee,
-            ErrorCode::InsufficientListingLamports
+            !ctx.remaining_accounts.is_empty() && ctx.remaining_accounts.len().is_multiple_of(2),
+            ErrorCode::InvalidRemainingAccounts
         );
 
-        **ctx.accounts.listing.to_account_info().try_borrow_mut_lamports()? -= price;
-        **ctx.accounts.seller.to_account_info().try_borrow_mut_lamports()? += price;
+        let listing_key = ctx.accounts.listing.key();
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let escrow = ListingEscrow::new(&escrow_info, &ctx.accounts.listing, &system_program);
+        let mut refunded = 0u32;
+
+        for pair in ctx.remaining_accounts.chunks(2) {
+            let (contribution_info, wallet_info) = (&pair[0], &pair[1]);
+            if contribution_info.owner == &System::id() && contribution_info.data_is_empty() {
+                continue;
+            }
+            let contribution = Account::<Contribution>::try_from(contribution_info)?;
+            require!(contribution.listing == listing_key, ErrorCode::InvalidContribution);
+            require!(contribution.wallet == wallet_info.key(), ErrorCode::InvalidContribution);
+            require!(wallet_info.is_writable, ErrorCode::InvalidRemainingAccounts);
 
-        **ctx.accounts.listing.to_account_info().try_borrow_mut_lamports()? -= fee;
-        **ctx.accounts.fee_vault.to_account_info().try_borrow_mut_lamports()? += fee;
+            if contribution.refund_claimed {
+                continue;
+            }
 
-        let listing = &mut ctx.accounts.listing;
-        listing.status = ListingStatus::Custodied;
+            let refund_amount = contribution.refund_amount()?;
 
-        Ok(())
-    }
+            escrow.pay_out(wallet_info, refund_amount)?;
 
+            contribution.close(wallet_info.clone())?;
+            ctx.accounts.listing.record_refund(contribution.bps, refund_amount)?;
+            refunded += 1;
 
-    pub fn process_refund(ctx: Context<ProcessRefund>) -> Result<()> {
-        let listing = &ctx.accounts.listing;
-        let contribution = &ctx.accounts.contribution;
+            emit_cpi!(RefundProcessed {
+                listing: listing_key,
+                contributor: contribution.wallet,
+                bps: contribution.bps,
+                amount_lamports: refund_amount,
+                refunded_bps: ctx.accounts.listing.refunded_bps,
+                refunded_lamports: ctx.accounts.listing.refunded_lamports,
+                timestamp: clock.unix_timestamp,
+            });
+        }
 
-        require!(!contribution.refund_claimed, ErrorCode::AlreadyRefunded);
+        // Cranks that refund nothing earn nothing. Refund cranks share one bounty per listing, paid
+        // out of the seller's tip pool in proportion to the positions each crank refunds, so
+        // splitting a batch into single-position cranks earns no more.
+        if refunded > 0 {
+            let transitions = ctx.accounts.listing.wind_down(listing_key, clock.unix_timestamp)?;
+            for event in transitions {
+                emit_transition!(ctx, event);
+            }
 
-        let clock = Clock::get()?;
+            let listing = &mut ctx.accounts.listing;
+            let bounty = math::mul_div(
+                ctx.accounts.config.keeper_bounty_lamports,
+                refunded as u64,
+                listing.contributor_count as u64,
+            )
+            .ok_or(ErrorCode::MathOverflow)?;
+            let from_tip = bounty.min(listing.keeper_tip_lamports);
+            let bounty = pay_keeper_bounty(
+                listing,
+                &escrow,
+                &ctx.accounts.cranker.to_account_info(),
+                KeeperAction::CrankRefunds,
+                from_tip,
+                0,
+            )?;
+            if let Some(event) = bounty {
+                emit_cpi!(event);
+            }
+        }
 
-        // Refund conditions:
-        // 1) Listing never funded: after deadline.
-        // 2) Listing funded but not executed: after execution window.
-        let refundable = match listing.status {
-            ListingStatus::Open => clock.unix_timestamp >= listing.deadline,
-            ListingStatus::Expired => true,
-            ListingStatus::Funded => listing.funded_at > 0 && clock.unix_timestamp >= listing.funded_at + 86400,
-            _ => false,
-        };
-        require!(refundable, ErrorCode::NotRefundable);
+        Ok(())
+    }
 
-        let refund_amount = contribution.principal_lamports
-            .checked_add(contribution.fee_lamports)
-            .ok_or(ErrorCode::MathOverflow)?;
+    pub fn claim_auction_rebate(ctx: Context<ClaimAuctionRebate>) -> Result<()> {
+        let listing = &ctx.accounts.listing;
+        let contribution = &ctx.accounts.contribution;
 
+        require!(listing.auction.is_some(), ErrorCode::NotAnAuction);
+        // The clearing price is final once the raise filled (or executed after a partial fill).
         require!(
-            **ctx.accounts.listing.to_account_info().try_borrow_lamports()? >= refund_amount,
-            ErrorCode::InsufficientListingLamports
+            listing.status == ListingStatus::Funded || listing.status == ListingStatus::Custodied,
+            ErrorCode::InvalidListingStatus
         );
+        require!(!contribution.refund_claimed, ErrorCode::AlreadyRefunded);
 
-        **ctx.accounts.listing.to_account_info().try_borrow_mut_lamports()? -= refund_amount;
-        **ctx.accounts.contributor.to_account_info().try_borrow_mut_lamports()? += refund_amount;
+        // What this position costs at the clearing price; exact, since auction prices are whole
+        // lamports per bps.
+        let paid_bps = contribution.paid_bps(listing)?;
+        let due_principal = math::principal_through(listing.price_lamports, paid_bps)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let due_fee = math::mul_div(listing.custody_fee_lamports, paid_bps as u64, listing.bps_for_sale() as u64)
+            .ok_or(ErrorCode::MathOverflow)?;
 
+        let rebate = contribution.principal_lamports
+            .checked_add(contribution.fee_lamports)
+            .ok_or(ErrorCode::MathOverflow)?
+            .saturating_sub(due_principal.checked_add(due_fee).ok_or(ErrorCode::MathOverflow)?);
+        require!(rebate > 0, ErrorCode::NothingToClaim);
+
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        ListingEscrow::new(&escrow_info, listing, &system_program)
+            .pay_out(&ctx.accounts.contributor.to_account_info(), rebate)?;
+
+        emit_cpi!(AuctionRebateClaimed {
+            listing: listing.key(),
+            contributor: ctx.accounts.contributor.key(),
+            amount_lamports: rebate,
+            clearing_price_lamports: listing.price_lamports,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        // Settle the position at the clearing price so a later refund can only return what remains.
         let contribution = &mut ctx.accounts.contribution;
-        contribution.refund_claimed = true;
-
-        let listing = &mut ctx.accounts.listing;
-        if listing.status == ListingStatus::Open {
-            listing.status = ListingStatus::Expired;
-        }
+        contribution.principal_lamports = due_principal;
+        contribution.fee_lamports = due_fee;
 
         Ok(())
     }
 
-
     pub fn reclaim_nft(ctx: Context<ReclaimNft>) -> Result<()> {
         let listing = &ctx.accounts.listing;
-        require!(listing.status == ListingStatus::Open || listing.status == ListingStatus::Expired || listing.status == ListingStatus::Funded, ErrorCode::InvalidListingStatus);
-
-        // Only reclaim if not executed/custodied.
-        require!(listing.status != ListingStatus::Custodied, ErrorCode::InvalidListingStatus);
-
         let clock = Clock::get()?;
-        // If still open, must be past deadline. If funded, must be past execution window.
-        if listing.status == ListingStatus::Open {
-            require!(clock.unix_timestamp >= listing.deadline, ErrorCode::ListingNotExpired);
-        }
-        if listing.status == ListingStatus::Funded {
-            require!(listing.funded_at > 0 && clock.unix_timestamp >= listing.funded_at + 86400, ErrorCode::ExecutionWindowNotExpired);
+        // Reclaiming expires the listing, so it needs the same deadline or execution window to have
+        // passed; an already expired listing can always be reclaimed.
+        if listing.status != ListingStatus::Expired {
+            listing.check_transition(&ListingStatus::Expired, clock.unix_timestamp)?;
         }
+        require!(!listing.nft_reclaimed, ErrorCode::AlreadyReclaimed);
 
         // Return the NFT from the vault to the seller's token account.
-        let listing_key = ctx.accounts.listing.key();
         let nft_mint_key = ctx.accounts.nft_mint.key();
         let listing_bump = ctx.accounts.listing.bump;
         let seeds = &[
@@ -274,6 +703,137 @@ pub fn contribute(ctx: Context<Contribute>, bps: u16) -> Result<()> {
             1,
         )?;
 
+        // The NFT is gone, so the listing can no longer execute; all that is left is refunds.
+        let listing_key = ctx.accounts.listing.key();
+        let listing = &mut ctx.accounts.listing;
+        listing.nft_reclaimed = true;
+        let reclaimed = NftReclaimed {
+            listing: listing_key,
+            nft_mint: listing.nft_mint,
+            seller: listing.nft_seller,
+            timestamp: clock.unix_timestamp,
+        };
+        let transitions = listing.wind_down(listing_key, clock.unix_timestamp)?;
+
+        emit_cpi!(reclaimed);
+        for event in transitions {
+            emit_transition!(ctx, event);
+        }
+
+        Ok(())
+    }
+
+    // Closes a fully refunded listing, its empty vault and its escrow. Rent and any unspent keeper
+    // tip go back to the seller.
+    pub fn close_listing(ctx: Context<CloseListing>) -> Result<()> {
+        require!(ctx.accounts.listing.status == ListingStatus::Refunded, ErrorCode::InvalidListingStatus);
+
+        let nft_mint_key = ctx.accounts.listing.nft_mint;
+        let listing_bump = ctx.accounts.listing.bump;
+        let seeds = &[
+            b"listing",
+            nft_mint_key.as_ref(),
+            &[listing_bump],
+        ];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::CloseAccount {
+                account: ctx.accounts.vault.to_account_info(),
+                destination: ctx.accounts.seller.to_account_info(),
+                authority: ctx.accounts.listing.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        ListingEscrow::new(&escrow_info, &ctx.accounts.listing, &system_program)
+            .drain(&ctx.accounts.seller.to_account_info())?;
+
+        emit_cpi!(ListingClosed {
+            listing: ctx.accounts.listing.key(),
+            seller: ctx.accounts.seller.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    // Permissionless: grows a listing created before `Listing::VERSION` to the current size and
+    // stamps the version. The payer covers the extra rent.
+    pub fn migrate_listing(ctx: Context<MigrateListing>) -> Result<()> {
+        let listing_info = ctx.accounts.listing.to_account_info();
+        require!(
+            listing_info.try_borrow_data()?.starts_with(Listing::DISCRIMINATOR),
+            anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+        );
+        let previous_version = migrate_versioned::<Listing>(
+            &listing_info,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            ErrorCode::ListingAlreadyMigrated,
+        )?;
+
+        emit_cpi!(ListingMigrated {
+            listing: listing_info.key(),
+            previous_version,
+            version: Listing::VERSION,
+            space: listing_info.data_len() as u64,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    // Permissionless: `migrate_listing` for every other account type. Their sizes were exact
+    // before they carried a version, so until migrated they are one byte short and fail to decode.
+    pub fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+        let account = ctx.accounts.account.to_account_info();
+        let payer = ctx.accounts.payer.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let discriminator = account.try_borrow_data()?.get(..8).map(<[u8]>::to_vec).unwrap_or_default();
+        let already_migrated = ErrorCode::AccountAlreadyMigrated;
+
+        let (previous_version, version) = match discriminator.as_slice() {
+            d if d == Config::DISCRIMINATOR => {
+                (migrate_versioned::<Config>(&account, &payer, &system_program, already_migrated)?, Config::VERSION)
+            }
+            d if d == CollectionAllowlist::DISCRIMINATOR => (
+                migrate_versioned::<CollectionAllowlist>(&account, &payer, &system_program, already_migrated)?,
+                CollectionAllowlist::VERSION,
+            ),
+            d if d == Contribution::DISCRIMINATOR => (
+                migrate_versioned::<Contribution>(&account, &payer, &system_program, already_migrated)?,
+                Contribution::VERSION,
+            ),
+            d if d == Proposal::DISCRIMINATOR => {
+                (migrate_versioned::<Proposal>(&account, &payer, &system_program, already_migrated)?, Proposal::VERSION)
+            }
+            d if d == VoteRecord::DISCRIMINATOR => (
+                migrate_versioned::<VoteRecord>(&account, &payer, &system_program, already_migrated)?,
+                VoteRecord::VERSION,
+            ),
+            d if d == RewardRegistry::DISCRIMINATOR => (
+                migrate_versioned::<RewardRegistry>(&account, &payer, &system_program, already_migrated)?,
+                RewardRegistry::VERSION,
+            ),
+            d if d == ClaimRecord::DISCRIMINATOR => (
+                migrate_versioned::<ClaimRecord>(&account, &payer, &system_program, already_migrated)?,
+                ClaimRecord::VERSION,
+            ),
+            _ => return err!(anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch),
+        };
+
+        emit_cpi!(AccountMigrated {
+            account: account.key(),
+            previous_version,
+            version,
+            space: account.data_len() as u64,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
         Ok(())
     }
 
@@ -289,9 +849,9 @@ pub fn contribute(ctx: Context<Contribute>, bps: u16) -> Result<()> {
         let contribution = &ctx.accounts.contribution;
 
         require!(listing.status == ListingStatus::Custodied, ErrorCode::InvalidListingStatus);
-        require!(contribution.bps > 0, ErrorCode::NoVotingPower);
+        require!(contribution.weight_bps() > 0, ErrorCode::NoVotingPower);
         require!(sale_price_lamports > 0, ErrorCode::InvalidAmount);
-        require!(vote_deadline_offset >= 3600 && vote_deadline_offset <= 604800, ErrorCode::InvalidDeadline);
+        require!((3600..=604800).contains(&vote_deadline_offset), ErrorCode::InvalidDeadline);
 
         let clock = Clock::get()?;
         let vote_deadline = clock.unix_timestamp
@@ -310,20 +870,31 @@ pub fn contribute(ctx: Context<Contribute>, bps: u16) -> Result<()> {
         proposal.no_bps = 0;
         proposal.status = ProposalStatus::Active;
         proposal.bump = ctx.bumps.proposal;
+        proposal.version = Proposal::VERSION;
 
         let listing = &mut ctx.accounts.listing;
         listing.proposal_count = listing.proposal_count.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
 
+        emit_cpi!(ProposalCreated {
+            listing: listing.key(),
+            proposal: ctx.accounts.proposal.key(),
+            proposal_id,
+            proposer: ctx.accounts.proposer.key(),
+            sale_price_lamports,
+            vote_deadline,
+            timestamp: clock.unix_timestamp,
+        });
+
         Ok(())
     }
 
     // vote: Vote, Vote choice (Yes or No)
     pub fn cast_vote(ctx: Context<CastVote>, vote: Vote) -> Result<()> {
         let proposal = &ctx.accounts.proposal;
-        let contribution = &ctx.accounts.contribution;
+        let bps = ctx.accounts.contribution.weight_bps();
 
         require!(proposal.status == ProposalStatus::Active, ErrorCode::InvalidProposalStatus);
-        require!(contribution.bps > 0, ErrorCode::NoVotingPower);
+        require!(bps > 0, ErrorCode::NoVotingPower);
 
         let clock = Clock::get()?;
         require!(clock.unix_timestamp < proposal.vote_deadline - 60, ErrorCode::VotingEnded);
@@ -331,26 +902,86 @@ pub fn contribute(ctx: Context<Contribute>, bps: u16) -> Result<()> {
         let vote_record = &mut ctx.accounts.vote_record;
         vote_record.proposal = ctx.accounts.proposal.key();
         vote_record.voter = ctx.accounts.voter.key();
-        vote_record.bps_voted = contribution.bps;
+        vote_record.bps_voted = bps;
         vote_record.vote = vote.clone();
         vote_record.bump = ctx.bumps.vote_record;
+        vote_record.version = VoteRecord::VERSION;
 
         let proposal = &mut ctx.accounts.proposal;
         match vote {
             Vote::Yes => {
                 proposal.yes_bps = proposal.yes_bps
-                    .checked_add(contribution.bps)
+                    .checked_add(bps)
                     .ok_or(ErrorCode::MathOverflow)?;
             }
             Vote::No => {
                 proposal.no_bps = proposal.no_bps
-                    .checked_add(contribution.bps)
+                    .checked_add(bps)
                     .ok_or(ErrorCode::MathOverflow)?;
             }
         }
 
+        emit_cpi!(VoteCast {
+            listing: proposal.listing,
+            proposal: proposal.key(),
+            voter: ctx.accounts.voter.key(),
+            vote,
+            bps,
+            yes_bps: proposal.yes_bps,
+            no_bps: proposal.no_bps,
+            timestamp: clock.unix_timestamp,
+        });
+
         if proposal.yes_bps > 5000 {
             proposal.status = ProposalStatus::Approved;
+            emit_cpi!(ProposalResolved {
+                listing: proposal.listing,
+                proposal: proposal.key(),
+                status: ProposalStatus::Approved,
+                yes_bps: proposal.yes_bps,
+                no_bps: proposal.no_bps,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
+        Ok(())
+    }
+
+    // Permissionless: closes voting on a proposal whose vote deadline has passed without approval.
+    pub fn finalize_proposal(ctx: Context<FinalizeProposal>) -> Result<()> {
+        let proposal = &ctx.accounts.proposal;
+        require!(proposal.status == ProposalStatus::Active, ErrorCode::InvalidProposalStatus);
+
+        let clock = Clock::get()?;
+        require!(clock.unix_timestamp >= proposal.vote_deadline, ErrorCode::VotingNotEnded);
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.status = ProposalStatus::Rejected;
+
+        emit_cpi!(ProposalResolved {
+            listing: proposal.listing,
+            proposal: proposal.key(),
+            status: ProposalStatus::Rejected,
+            yes_bps: proposal.yes_bps,
+            no_bps: proposal.no_bps,
+            timestamp: clock.unix_timestamp,
+        });
+
+        // Only the single bounty held back at execution is left to pay, so one finalize collects it.
+        let from_tip = ctx.accounts.config.keeper_bounty_lamports.min(ctx.accounts.listing.keeper_tip_lamports);
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let escrow = ListingEscrow::new(&escrow_info, &ctx.accounts.listing, &system_program);
+        let bounty = pay_keeper_bounty(
+            &mut ctx.accounts.listing,
+            &escrow,
+            &ctx.accounts.keeper.to_account_info(),
+            KeeperAction::FinalizeProposal,
+            from_tip,
+            0,
+        )?;
+        if let Some(event) = bounty {
+            emit_cpi!(event);
         }
 
         Ok(())
@@ -379,6 +1010,17 @@ pub fn contribute(ctx: Context<Contribute>, bps: u16) -> Result<()> {
         registry.reward_mint = ctx.accounts.reward_mint.key();
         registry.total_amount = registry.total_amount.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
         registry.bump = ctx.bumps.reward_registry;
+        registry.version = RewardRegistry::VERSION;
+
+        emit_cpi!(RewardRegistered {
+            listing: registry.listing,
+            registry: registry.key(),
+            reward_mint: registry.reward_mint,
+            depositor: ctx.accounts.depositor.key(),
+            amount,
+            total_amount: registry.total_amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
 
         Ok(())
     }
@@ -390,7 +1032,7 @@ pub fn contribute(ctx: Context<Contribute>, bps: u16) -> Result<()> {
         let claim_record = &mut ctx.accounts.claim_record;
 
         require!(listing.status == ListingStatus::Custodied, ErrorCode::InvalidListingStatus);
-        require!(contribution.bps > 0, ErrorCode::NoVotingPower);
+        require!(contribution.weight_bps() > 0, ErrorCode::NoVotingPower);
 
         // Initialize claim record on first claim
         if claim_record.claimer == Pubkey::default() {
@@ -398,31 +1040,13 @@ pub fn contribute(ctx: Context<Contribute>, bps: u16) -> Result<()> {
             claim_record.claimer = ctx.accounts.claimer.key();
             claim_record.claimed_amount = 0;
             claim_record.bump = ctx.bumps.claim_record;
+            claim_record.version = ClaimRecord::VERSION;
         } else {
             require!(claim_record.registry == registry.key(), ErrorCode::InvalidClaimRecord);
             require!(claim_record.claimer == ctx.accounts.claimer.key(), ErrorCode::InvalidClaimRecord);
         }
 
-        // Total claimable for this claimer across the lifetime of the registry
-        let claimable_total = registry.total_amount
-            .checked_mul(contribution.bps as u64)
-            .ok_or(ErrorCode::MathOverflow)?
-            .checked_div(10000)
-            .ok_or(ErrorCode::MathOverflow)?;
-
-        let already_claimed = claim_record.claimed_amount;
-        require!(claimable_total > already_claimed, ErrorCode::NothingToClaim);
-
-        let mut claim_amount = claimable_total
-            .checked_sub(already_claimed)
-            .ok_or(ErrorCode::MathOverflow)?;
-
-        // Also cap by what's actually left in the vault (defensive).
-        let remaining_global = registry.total_amount
-            .checked_sub(registry.claimed_amount)
-            .ok_or(ErrorCode::MathOverflow)?;
-        claim_amount = claim_amount.min(remaining_global);
-
+        let claim_amount = registry.claimable(contribution.weight_bps(), claim_record.claimed_amount)?;
         require!(claim_amount > 0, ErrorCode::NothingToClaim);
 
         let listing_key = ctx.accounts.listing.key();
@@ -458,26 +1082,144 @@ pub fn contribute(ctx: Context<Contribute>, bps: u16) -> Result<()> {
             .checked_add(claim_amount)
             .ok_or(ErrorCode::MathOverflow)?;
 
+        emit_cpi!(RewardClaimed {
+            listing: listing_key,
+            registry: registry.key(),
+            claimer: claim_record.claimer,
+            amount: claim_amount,
+            claimed_total: claim_record.claimed_amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
         Ok(())
     }
 
+    // ---- Views: read-only; simulate them and decode the transaction's return data. ----
+
+    // What `contributor` contributing `bps` would cost right now, with the checks `contribute`
+    // applies.
+    pub fn quote_contribution(
+        ctx: Context<QuoteContribution>,
+        bps: u16,
+        presale_proof: Option<PresaleProof>,
+    ) -> Result<ContributionQuote> {
+        let mut listing = (*ctx.accounts.listing).clone();
+
+        // The position only exists after a first contribution.
+        let contribution = &ctx.accounts.contribution;
+        let wallet_bps = if contribution.owner == &crate::ID {
+            Contribution::try_deserialize(&mut &contribution.try_borrow_data()?[..])?.weight_bps()
+        } else {
+            0
+        };
+
+        let clock = Clock::get()?;
+        let (principal_lamports, fee_lamports) = listing.price_contribution(
+            &ctx.accounts.contributor.key(),
+            wallet_bps,
+            bps,
+            presale_proof.as_ref(),
+            clock.unix_timestamp,
+        )?;
+        let remaining_bps = listing.bps_for_sale().checked_sub(listing.bps_sold).ok_or(ErrorCode::MathOverflow)?;
+        Ok(ContributionQuote {
+            bps,
+            principal_lamports,
+            fee_lamports,
+            total_lamports: principal_lamports.checked_add(fee_lamports).ok_or(ErrorCode::MathOverflow)?,
+            price_lamports: listing.price_lamports,
+            remaining_bps,
+        })
+    }
+
+    // What `process_refund` would pay this position, and whether it can be claimed now.
+    pub fn quote_refund(ctx: Context<QuoteRefund>) -> Result<RefundQuote> {
+        let contribution = &ctx.accounts.contribution;
+        let clock = Clock::get()?;
+        Ok(RefundQuote {
+            refundable: ctx.accounts.listing.is_refundable(clock.unix_timestamp) && !contribution.refund_claimed,
+            refund_claimed: contribution.refund_claimed,
+            bps: contribution.bps,
+            principal_lamports: contribution.principal_lamports,
+            fee_lamports: contribution.fee_lamports,
+            total_lamports: contribution.refund_amount()?,
+        })
+    }
+
+    // What `claim_reward` would transfer to this position's wallet.
+    pub fn quote_claimable_reward(ctx: Context<QuoteClaimableReward>) -> Result<RewardQuote> {
+        require!(ctx.accounts.listing.status == ListingStatus::Custodied, ErrorCode::InvalidListingStatus);
+        let contribution = &ctx.accounts.contribution;
+        let registry = &ctx.accounts.reward_registry;
+
+        // The claim record only exists after a first claim.
+        let claim_record = &ctx.accounts.claim_record;
+        let claimed_amount = if claim_record.owner == &crate::ID {
+            ClaimRecord::try_deserialize(&mut &claim_record.try_borrow_data()?[..])?.claimed_amount
+        } else {
+            0
+        };
+
+        Ok(RewardQuote {
+            bps: contribution.weight_bps(),
+            total_amount: registry.total_amount,
+            claimed_amount,
+            claimable_amount: registry.claimable(contribution.weight_bps(), claimed_amount)?,
+        })
+    }
+
+    // Listing state as clients display it, with auction prices brought up to date.
+    pub fn listing_summary(ctx: Context<ListingSummaryView>) -> Result<ListingSummary> {
+        let mut listing = (*ctx.accounts.listing).clone();
+        let clock = Clock::get()?;
+        if listing.status == ListingStatus::Open {
+            listing.reprice_auction(clock.unix_timestamp)?;
+        }
+
+        Ok(ListingSummary {
+            status: listing.status.clone(),
+            price_lamports: listing.price_lamports,
+            custody_fee_lamports: listing.custody_fee_lamports,
+            total_raise_lamports: listing.total_raise_lamports,
+            bps_for_sale: listing.bps_for_sale(),
+            bps_sold: listing.bps_sold,
+            remaining_bps: listing.bps_for_sale().saturating_sub(listing.bps_sold),
+            contributor_count: listing.contributor_count,
+            deadline: listing.deadline,
+            execution_window_start: listing.execution_window_start(),
+            refundable: listing.is_refundable(clock.unix_timestamp),
+            escrowed_lamports: LamportLedger::for_account(&ctx.accounts.escrow.to_account_info())?.escrowed(),
+            refunded_bps: listing.refunded_bps,
+            refunded_lamports: listing.refunded_lamports,
+            nft_reclaimed: listing.nft_reclaimed,
+        })
+    }
+
 }
 
 // ============== ACCOUNT STRUCTURES ==============
 
 #[account]
+#[derive(InitSpace)]
 pub struct Config {
-    pub authority: Pubkey,    pub custody_fee_bps: u16,
+    pub authority: Pubkey,
+    pub custody_fee_bps: u16,
     pub fee_vault: Pubkey,
     pub bump: u8,
+    pub allowlist_enabled: bool,
+    pub royalties_enabled: bool,
+    pub keeper_bounty_lamports: u64,
+    pub version: u8,
 }
 
 impl Config {
-    pub const LEN: usize = 144;
+    pub const VERSION: u8 = 1;
 }
 
 #[account]
-pub struct Listing {    pub nft_mint: Pubkey,
+#[derive(InitSpace)]
+pub struct Listing {
+    pub nft_mint: Pubkey,
     pub nft_seller: Pubkey,
     pub price_lamports: u64,
     pub custody_fee_lamports: u64,
@@ -489,39 +1231,429 @@ pub struct Listing {    pub nft_mint: Pubkey,
     pub vault: Pubkey,
     pub bump: u8,
     pub proposal_count: u32,
+    /// Collection mint, only recorded if the collection is verified in the NFT's metadata.
+    pub collection: Option<Pubkey>,
+    /// Creators as listed in the NFT's metadata at listing time.
+    #[max_len(MAX_CREATORS)]
+    pub creators: Vec<ListingCreator>,
+    /// Metadata `seller_fee_basis_points` if royalty mode was on at listing time, otherwise 0.
+    pub royalty_bps: u16,
+    /// Basis points the seller keeps; only `10000 - retained_bps` is offered to contributors.
+    pub retained_bps: u16,
+    /// Sold bps at which the listing may still execute once the deadline passes; 0 requires a full fill.
+    pub min_fill_bps: u16,
+    pub custody_fee_bps: u16,
+    /// Decaying-price parameters; `price_lamports` tracks the latest contribution price.
+    pub auction: Option<DutchAuction>,
+    /// Allowlist-only contribution window before the listing opens to everyone.
+    pub presale: Option<Presale>,
+    /// Cap on any wallet's accumulated bps; 0 = uncapped.
+    pub max_bps_per_wallet: u16,
+    /// Smallest single contribution, waived for the last bps of the raise; 0 = no minimum.
+    pub min_bps_per_contribution: u16,
+    /// Number of distinct wallets that have contributed.
+    pub contributor_count: u32,
+    /// Deadline extension rule for late contributions.
+    pub soft_close: Option<SoftClose>,
+    /// Remaining seller-posted lamports that fund keeper bounties for this listing.
+    pub keeper_tip_lamports: u64,
+    /// Bps of the raise paid back to contributors so far.
+    pub refunded_bps: u16,
+    /// Lamports (principal + fee) paid back to contributors so far.
+    pub refunded_lamports: u64,
+    /// Set once the NFT has been returned from the vault to the seller.
+    pub nft_reclaimed: bool,
+    /// Bump of the listing's escrow PDA, which holds contributor SOL.
+    pub escrow_bump: u8,
+    /// Account layout version; older listings are brought up to `Listing::VERSION` by `migrate_listing`.
+    pub version: u8,
 }
 
 impl Listing {
-    // nft_mint: 32
-    // nft_seller: 32
-    // price_lamports: 8
-    // custody_fee_lamports: 8
-    // total_raise_lamports: 8
-    // bps_sold: 2
-    // deadline: 8
-    // status: 1
-    // vault: 32
-    // bump: 1
-    // proposal_count: 4
-    pub const LEN: usize = 1 + 32 + 32 + 8 + 8 + 8 + 2 + 8 + 1 + 32 + 1 + 4;
-}
+    // Version 0 listings predate `version` and were allocated with a hand-counted size that was
+    // 7 bytes short of the largest listing; `migrate_listing` reallocates them.
+    pub const VERSION: u8 = 1;
 
-#[account]
-pub struct Contribution {
-    pub listing: Pubkey,
-    pub wallet: Pubkey,
-    pub bps: u16,
+    pub fn bps_for_sale(&self) -> u16 {
+        10000 - self.retained_bps
+    }
+
+    // Seller proceeds for the bps sold so far, priced off the full-NFT `price_lamports`.
+    pub fn sold_price_lamports(&self) -> Result<u64> {
+        Ok(math::principal_through(self.price_lamports, self.bps_sold).ok_or(ErrorCode::MathOverflow)?)
+    }
+
+    // Custody fee collected for the bps sold so far.
+    pub fn sold_custody_fee_lamports(&self) -> Result<u64> {
+        Ok(math::fee_through(self.price_lamports, self.custody_fee_lamports, self.bps_for_sale(), self.bps_sold)
+            .ok_or(ErrorCode::MathOverflow)?)
+    }
+
+    // (principal, custody fee share) the next contribution of `bps` pays at the current price.
+    pub fn contribution_cost(&self, bps: u16) -> Result<(u64, u64)> {
+        Ok(math::contribution_cost(
+            self.price_lamports,
+            self.custody_fee_lamports,
+            self.bps_for_sale(),
+            self.bps_sold,
+            bps,
+        )
+        .ok_or(ErrorCode::MathOverflow)?)
+    }
+
+    // Checks a contribution of `bps` at `now` from `wallet`, which already holds `wallet_bps`, and
+    // prices it: (principal, custody fee share). Shared by `contribute` and `quote_contribution`.
+    pub fn price_contribution(
+        &mut self,
+        wallet: &Pubkey,
+        wallet_bps: u16,
+        bps: u16,
+        presale_proof: Option<&PresaleProof>,
+        now: i64,
+    ) -> Result<(u64, u64)> {
+        require!(self.status == ListingStatus::Open, ErrorCode::InvalidListingStatus);
+        require!((1..=10000).contains(&bps), ErrorCode::InvalidBps);
+        require!(now < self.deadline - 60, ErrorCode::ListingExpired);
+        let total_bps = wallet_bps.checked_add(bps).ok_or(ErrorCode::MathOverflow)?;
+
+        // Community-first window: until the presale ends, only allowlisted wallets may contribute,
+        // up to the per-wallet cap encoded in their leaf (0 = uncapped).
+        if let Some(presale) = &self.presale {
+            if now < presale.end_time {
+                let proof = presale_proof.ok_or(ErrorCode::NotAllowlisted)?;
+                require!(
+                    verify_presale_proof(&presale.merkle_root, wallet, proof.max_bps, &proof.proof),
+                    ErrorCode::NotAllowlisted
+                );
+                require!(proof.max_bps == 0 || total_bps <= proof.max_bps, ErrorCode::ExceedsWalletCap);
+            }
+        }
+
+        // Dutch auctions reprice the listing to the current price before each contribution. The
+        // last price paid becomes the clearing price; earlier contributors claim back the difference.
+        self.reprice_auction(now)?;
+
+        let remaining_bps = self.bps_for_sale().checked_sub(self.bps_sold).ok_or(ErrorCode::MathOverflow)?;
+        require!(bps <= remaining_bps, ErrorCode::ExceedsAvailable);
+
+        // Seller-chosen distribution limits. The minimum is waived for the final bps of the raise so
+        // a listing can always fill; the cap applies to the wallet's accumulated position.
+        require!(
+            bps >= self.min_bps_per_contribution || bps == remaining_bps,
+            ErrorCode::BelowMinContribution
+        );
+        require!(
+            self.max_bps_per_wallet == 0 || total_bps <= self.max_bps_per_wallet,
+            ErrorCode::ExceedsWalletCap
+        );
+
+        self.contribution_cost(bps)
+    }
+
+    // Moves price, fee and raise to the auction's current price, in whole lamports per bps so that
+    // every position can be settled exactly at the clearing price.
+    pub fn reprice_auction(&mut self, now: i64) -> Result<()> {
+        let Some(auction) = &self.auction else {
+            return Ok(());
+        };
+        let unit_price = auction.price_at(now)? / 10000;
+        let unit_fee = unit_price
+            .checked_mul(self.custody_fee_bps as u64)
+            .ok_or(ErrorCode::MathOverflow)?
+            / 10000;
+        let bps_for_sale = self.bps_for_sale() as u64;
+
+        self.price_lamports = unit_price.checked_mul(10000).ok_or(ErrorCode::MathOverflow)?;
+        self.custody_fee_lamports = unit_fee.checked_mul(bps_for_sale).ok_or(ErrorCode::MathOverflow)?;
+        self.total_raise_lamports = unit_price
+            .checked_mul(bps_for_sale)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_add(self.custody_fee_lamports)
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    // Refund conditions:
+    // 1) Listing never funded: after deadline.
+    // 2) Listing funded (or closed above its minimum fill) but not executed: after execution window.
+    pub fn is_refundable(&self, now: i64) -> bool {
+        match (&self.status, self.execution_window_start()) {
+            (ListingStatus::Open | ListingStatus::Funded, Some(window_start)) => now >= window_start + 86400,
+            (ListingStatus::Open, None) => now >= self.deadline,
+            (ListingStatus::Expired, _) => true,
+            _ => false,
+        }
+    }
+
+    // Books a refund paid out of the listing; `wind_down` moves the status along afterwards.
+    pub fn record_refund(&mut self, bps: u16, lamports: u64) -> Result<()> {
+        self.refunded_bps = self.refunded_bps.checked_add(bps).ok_or(ErrorCode::MathOverflow)?;
+        self.refunded_lamports = self.refunded_lamports.checked_add(lamports).ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    // A failed listing is settled once every sold bps has been paid back and the NFT is back with
+    // the seller. Only then can the listing be closed.
+    pub fn refunds_settled(&self) -> bool {
+        self.nft_reclaimed && self.refunded_bps == self.bps_sold
+    }
+
+    pub fn min_fill_reached(&self) -> bool {
+        self.min_fill_bps > 0 && self.bps_sold >= self.min_fill_bps
+    }
+
+    // Start of the 86400s execution window: when the listing became fully funded, or its deadline
+    // if it closed above `min_fill_bps`. `None` while the listing cannot (yet) be executed.
+    pub fn execution_window_start(&self) -> Option<i64> {
+        match self.status {
+            ListingStatus::Funded if self.funded_at > 0 => Some(self.funded_at),
+            ListingStatus::Open if self.min_fill_reached() => Some(self.deadline),
+            _ => None,
+        }
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct CollectionAllowlist {
+    pub collection: Pubkey,
+    pub limits: CollectionLimits,
+    pub bump: u8,
+    pub version: u8,
+}
+
+impl CollectionAllowlist {
+    pub const VERSION: u8 = 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum DecayCurve {
+    Linear,
+    Stepwise { step_seconds: i64 },
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct CreateListingArgs {
+    // Total price for NFT in lamports, 1000000000 = 1 SOL
+    pub price_lamports: u64,
+    // Seconds until deadline (3600-604800), 86400 = 1 day
+    pub deadline_offset: i64,
+    // Basis points the seller keeps (0-9999), 4000 = 40%
+    pub retained_bps: u16,
+    // Sold bps that still execute at the deadline (0 = full fill only), 9500 = 95%
+    pub min_fill_bps: u16,
+    // Decaying price starting at `price_lamports`
+    pub auction: Option<DutchAuctionParams>,
+    // Allowlist Merkle root and presale end timestamp
+    pub presale: Option<Presale>,
+    // Cap on a wallet's accumulated bps (0 = uncapped), 2500 = 25%
+    pub max_bps_per_wallet: u16,
+    // Smallest single contribution (0 = no minimum), 100 = 1%
+    pub min_bps_per_contribution: u16,
+    // Late contributions extend the deadline up to a hard cap
+    pub soft_close: Option<SoftCloseParams>,
+    // Seller-posted pool for keeper bounties, 10000000 = 0.01 SOL
+    pub keeper_tip_lamports: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct DutchAuctionParams {
+    pub floor_price_lamports: u64,
+    pub curve: DecayCurve,
+    // Seconds over which the price decays to the floor; must end before the listing deadline.
+    pub duration: i64,
+}
+
+impl DutchAuctionParams {
+    pub fn validate(&self, start_price: u64, deadline_offset: i64) -> Result<()> {
+        // Prices are charged in whole lamports per bps.
+        require!(
+            self.floor_price_lamports >= 10000 && self.floor_price_lamports <= start_price,
+            ErrorCode::InvalidAuction
+        );
+        require!(self.duration > 0 && self.duration <= deadline_offset, ErrorCode::InvalidAuction);
+        if let DecayCurve::Stepwise { step_seconds } = self.curve {
+            require!(step_seconds > 0 && step_seconds <= self.duration, ErrorCode::InvalidAuction);
+        }
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct DutchAuction {
+    pub start_price_lamports: u64,
+    pub floor_price_lamports: u64,
+    pub curve: DecayCurve,
+    pub duration: i64,
+    pub start_time: i64,
+}
+
+impl DutchAuction {
+    // Full-NFT price at `now`, decaying from the start price to the floor over `duration`.
+    pub fn price_at(&self, now: i64) -> Result<u64> {
+        let elapsed = now.saturating_sub(self.start_time).clamp(0, self.duration);
+        let (progress, total) = match self.curve {
+            DecayCurve::Linear => (elapsed, self.duration),
+            DecayCurve::Stepwise { step_seconds } => {
+                (elapsed / step_seconds, self.duration / step_seconds)
+            }
+        };
+        let spread = self.start_price_lamports
+            .checked_sub(self.floor_price_lamports)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let decay = (spread as u128)
+            .checked_mul(progress as u128)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_div(total as u128)
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(self.start_price_lamports - decay as u64)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct Presale {
+    // Root of a blake3 Merkle tree over `presale_leaf(wallet, max_bps)` leaves.
+    pub merkle_root: [u8; 32],
+    pub end_time: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct PresaleProof {
+    // Per-wallet bps cap committed in the leaf; 0 = uncapped.
+    pub max_bps: u16,
+    pub proof: Vec<[u8; 32]>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct SoftCloseParams {
+    // Contributions within this many seconds of the deadline extend it; must exceed the 60s cutoff.
+    pub trigger_seconds: i64,
+    pub extension_seconds: i64,
+    // Total extension allowed beyond the original deadline.
+    pub max_extension_seconds: i64,
+}
+
+impl SoftCloseParams {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.trigger_seconds > 60
+                && self.extension_seconds > 0
+                && (self.extension_seconds..=604800).contains(&self.max_extension_seconds),
+            ErrorCode::InvalidDeadline
+        );
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct SoftClose {
+    pub trigger_seconds: i64,
+    pub extension_seconds: i64,
+    pub hard_deadline: i64,
+}
+
+// Upper bound for a per-collection custody fee override, 1000 = 10%
+pub const MAX_CUSTODY_FEE_BPS: u16 = 1000;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Default, InitSpace)]
+pub struct CollectionLimits {
+    pub max_price_lamports: Option<u64>,
+    pub min_deadline_offset: Option<i64>,
+    pub max_deadline_offset: Option<i64>,
+    pub custody_fee_bps: Option<u16>,
+}
+
+impl CollectionLimits {
+    pub fn validate(&self) -> Result<()> {
+        if let Some(max_price) = self.max_price_lamports {
+            require!(max_price > 0, ErrorCode::InvalidAmount);
+        }
+        let min_offset = self.min_deadline_offset.unwrap_or(3600);
+        let max_offset = self.max_deadline_offset.unwrap_or(604800);
+        require!(
+            (3600..=604800).contains(&min_offset)
+                && (3600..=604800).contains(&max_offset)
+                && min_offset <= max_offset,
+            ErrorCode::InvalidDeadline
+        );
+        if let Some(fee_bps) = self.custody_fee_bps {
+            require!(fee_bps <= MAX_CUSTODY_FEE_BPS, ErrorCode::InvalidFee);
+        }
+        Ok(())
+    }
+}
+
+// Metaplex caps the creators array of a metadata account at 5 entries.
+pub const MAX_CREATORS: usize = mpl_token_metadata::MAX_CREATOR_LIMIT;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct ListingCreator {
+    pub address: Pubkey,
+    pub verified: bool,
+    pub share: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Contribution {
+    pub listing: Pubkey,
+    pub wallet: Pubkey,
+    pub bps: u16,
     pub principal_lamports: u64,
     pub fee_lamports: u64,
+    /// Refunds now close the position; only positions refunded before they did carry this flag.
     pub refund_claimed: bool,
     pub bump: u8,
+    pub version: u8,
 }
 
 impl Contribution {
-    pub const LEN: usize = 32 + 32 + 2 + 8 + 8 + 1 + 1;
+    pub const VERSION: u8 = 1;
+
+    // A refund returns everything the position paid in: principal plus custody fee share.
+    pub fn refund_amount(&self) -> Result<u64> {
+        Ok(self.principal_lamports
+            .checked_add(self.fee_lamports)
+            .ok_or(ErrorCode::MathOverflow)?)
+    }
+
+    // Starts the position over for `wallet` on `listing`.
+    pub fn open(&mut self, listing: Pubkey, wallet: Pubkey, bump: u8) {
+        self.listing = listing;
+        self.wallet = wallet;
+        self.bps = 0;
+        self.principal_lamports = 0;
+        self.fee_lamports = 0;
+        self.refund_claimed = false;
+        self.bump = bump;
+        self.version = Contribution::VERSION;
+    }
+
+    // A position refunded before refunds closed positions outlives its listing, and a relisting of
+    // the same mint finds it again at the same address. It carries no weight there, and starts over
+    // if the wallet contributes again.
+    pub fn is_stale(&self) -> bool {
+        self.refund_claimed
+    }
+
+    // Bps that carry voting and reward weight.
+    pub fn weight_bps(&self) -> u16 {
+        if self.is_stale() { 0 } else { self.bps }
+    }
+
+    // Bps this position paid for. Execution adds the bps the seller kept to the seller's own
+    // position, and those cost nothing.
+    pub fn paid_bps(&self, listing: &Listing) -> Result<u16> {
+        if listing.status == ListingStatus::Custodied && self.wallet == listing.nft_seller {
+            let retained_bps = 10000u16.checked_sub(listing.bps_sold).ok_or(ErrorCode::MathOverflow)?;
+            return Ok(self.bps.checked_sub(retained_bps).ok_or(ErrorCode::MathOverflow)?);
+        }
+        Ok(self.bps)
+    }
 }
 
 #[account]
+#[derive(InitSpace)]
 pub struct Proposal {
     pub listing: Pubkey,
     pub proposer: Pubkey,
@@ -532,41 +1664,53 @@ pub struct Proposal {
     pub no_bps: u16,
     pub status: ProposalStatus,
     pub bump: u8,
+    pub version: u8,
 }
 
 impl Proposal {
-    pub const LEN: usize = 32 + 32 + 4 + 8 + 8 + 2 + 2 + 1 + 1;
+    pub const VERSION: u8 = 1;
 }
 
 #[account]
+#[derive(InitSpace)]
 pub struct VoteRecord {
     pub proposal: Pubkey,
     pub voter: Pubkey,
     pub bps_voted: u16,
     pub vote: Vote,
     pub bump: u8,
+    pub version: u8,
 }
 
 impl VoteRecord {
-    pub const LEN: usize = 32 + 32 + 2 + 1 + 1;
+    pub const VERSION: u8 = 1;
 }
 
 #[account]
+#[derive(InitSpace)]
 pub struct RewardRegistry {
     pub listing: Pubkey,
     pub reward_mint: Pubkey,
     pub total_amount: u64,
     pub claimed_amount: u64,
     pub bump: u8,
+    pub version: u8,
 }
 
 impl RewardRegistry {
-    pub const LEN: usize = 32 + 32 + 8 + 8 + 1;
+    pub const VERSION: u8 = 1;
+
+    // What a holder of `bps` can claim now: their share of everything ever deposited, less what
+    // they already claimed, capped by what is still in the vault.
+    pub fn claimable(&self, bps: u16, already_claimed: u64) -> Result<u64> {
+        Ok(math::claimable_reward(self.total_amount, self.claimed_amount, bps, already_claimed)
+            .ok_or(ErrorCode::MathOverflow)?)
+    }
 }
 
 // ============== ENUMS ==============
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
 pub enum ListingStatus {
     Open,
     Funded,
@@ -576,7 +1720,7 @@ pub enum ListingStatus {
     Sold,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
 pub enum ProposalStatus {
     Active,
     Approved,
@@ -586,13 +1730,491 @@ pub enum ProposalStatus {
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum KeeperAction {
+    ExecutePurchase,
+    CrankRefunds,
+    FinalizeProposal,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
 pub enum Vote {
     Yes,
     No,
 }
 
+// ============== LISTING LIFECYCLE ==============
+//
+// `create_listing` starts every listing `Open`; after that `Listing::transition` is the only writer
+// of `status`. The legal transitions, their guards and the events they emit:
+//
+//   Fund     Open           -> Funded     the last bps for sale is sold          ListingFunded
+//   Execute  Open | Funded  -> Custodied  inside the execution window            PurchaseExecuted
+//   Expire   Open | Funded  -> Expired    deadline, or execution window, passed  ListingExpired
+//   Settle   Expired        -> Refunded   all sold bps refunded, NFT reclaimed   ListingRefunded
+//
+// An `Open` listing is executable once its deadline passes above `min_fill_bps`, so it can be
+// executed or expired without ever being `Funded`. `Custodied` and `Refunded` are terminal. `Sold`
+// is reserved for executing an approved sale proposal, which the program does not do yet, so no
+// transition leads to it.
+
+pub enum Transition {
+    Fund,
+    // Carries the settlement, which only `settle_purchase` knows.
+    Execute(PurchaseExecuted),
+    Expire,
+    Settle,
+}
+
+impl Transition {
+    pub fn target(&self) -> ListingStatus {
+        match self {
+            Transition::Fund => ListingStatus::Funded,
+            Transition::Execute(_) => ListingStatus::Custodied,
+            Transition::Expire => ListingStatus::Expired,
+            Transition::Settle => ListingStatus::Refunded,
+        }
+    }
+}
+
+/// Event of an applied `Transition`, emitted by the calling instruction.
+pub enum TransitionEvent {
+    Funded(ListingFunded),
+    Executed(PurchaseExecuted),
+    Expired(ListingExpired),
+    Refunded(ListingRefunded),
+}
+
+impl ListingStatus {
+    pub fn can_transition_to(&self, to: &ListingStatus) -> bool {
+        use ListingStatus::*;
+        matches!(
+            (self, to),
+            (Open, Funded) | (Open | Funded, Custodied) | (Open | Funded, Expired) | (Expired, Refunded)
+        )
+    }
+}
+
+impl Listing {
+    // Fails unless the listing can move to `to` at `now`: the transition must be legal from the
+    // current status and its guard must hold.
+    pub fn check_transition(&self, to: &ListingStatus, now: i64) -> Result<()> {
+        require!(self.status.can_transition_to(to), ErrorCode::InvalidListingStatus);
+        match to {
+            ListingStatus::Funded => {
+                require!(self.bps_sold == self.bps_for_sale(), ErrorCode::InvalidListingStatus);
+            }
+            // Executable once fully funded, or after the deadline if the raise reached
+            // `min_fill_bps`. If nobody executes within the window, contributors can refund, so a
+            // funded listing cannot be held hostage.
+            ListingStatus::Custodied => {
+                let window_start = self.execution_window_start().ok_or(ErrorCode::InvalidListingStatus)?;
+                require!(now >= window_start, ErrorCode::ListingNotExpired);
+                require!(now <= window_start + 86400, ErrorCode::ExecutionWindowExpired);
+            }
+            ListingStatus::Expired => match self.execution_window_start() {
+                Some(window_start) => {
+                    require!(now >= window_start + 86400, ErrorCode::ExecutionWindowNotExpired);
+                }
+                None => {
+                    require!(self.status == ListingStatus::Open, ErrorCode::ExecutionWindowNotExpired);
+                    require!(now >= self.deadline, ErrorCode::ListingNotExpired);
+                }
+            },
+            ListingStatus::Refunded => {
+                require!(self.refunds_settled(), ErrorCode::InvalidListingStatus);
+            }
+            ListingStatus::Open | ListingStatus::Sold => {}
+        }
+        Ok(())
+    }
+
+    // Applies `transition` at `now` and returns the event to emit.
+    pub fn transition(&mut self, listing: Pubkey, transition: Transition, now: i64) -> Result<TransitionEvent> {
+        self.check_transition(&transition.target(), now)?;
+        self.status = transition.target();
+        Ok(match transition {
+            Transition::Fund => {
+                self.funded_at = now;
+                TransitionEvent::Funded(ListingFunded {
+                    listing,
+                    bps_sold: self.bps_sold,
+                    price_lamports: self.price_lamports,
+                    custody_fee_lamports: self.custody_fee_lamports,
+                    contributor_count: self.contributor_count,
+                    timestamp: now,
+                })
+            }
+            Transition::Execute(executed) => TransitionEvent::Executed(executed),
+            Transition::Expire => TransitionEvent::Expired(ListingExpired {
+                listing,
+                bps_sold: self.bps_sold,
+                timestamp: now,
+            }),
+            Transition::Settle => TransitionEvent::Refunded(ListingRefunded {
+                listing,
+                refunded_bps: self.refunded_bps,
+                refunded_lamports: self.refunded_lamports,
+                timestamp: now,
+            }),
+        })
+    }
+
+    // Moves a failed listing along after a refund or the NFT reclaim: `Expired` on the first of
+    // them, then `Refunded` once nothing is left to return.
+    pub fn wind_down(&mut self, listing: Pubkey, now: i64) -> Result<Vec<TransitionEvent>> {
+        let mut events = Vec::new();
+        if self.status != ListingStatus::Expired {
+            events.push(self.transition(listing, Transition::Expire, now)?);
+        }
+        if self.refunds_settled() {
+            events.push(self.transition(listing, Transition::Settle, now)?);
+        }
+        Ok(events)
+    }
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+impl Listing {
+    /// A 1 SOL listing at the protocol's 1% custody fee with nothing sold, for tests in this
+    /// workspace; set anything else with struct update syntax.
+    pub fn fixture(status: ListingStatus) -> Self {
+        Self {
+            nft_mint: Pubkey::new_unique(),
+            nft_seller: Pubkey::new_unique(),
+            price_lamports: 1_000_000_000,
+            custody_fee_lamports: 10_000_000,
+            total_raise_lamports: 1_010_000_000,
+            bps_sold: 0,
+            deadline: 0,
+            funded_at: 0,
+            status,
+            vault: Pubkey::new_unique(),
+            bump: 255,
+            proposal_count: 0,
+            collection: None,
+            creators: Vec::new(),
+            royalty_bps: 0,
+            retained_bps: 0,
+            min_fill_bps: 0,
+            custody_fee_bps: 100,
+            auction: None,
+            presale: None,
+            max_bps_per_wallet: 0,
+            min_bps_per_contribution: 0,
+            contributor_count: 0,
+            soft_close: None,
+            keeper_tip_lamports: 0,
+            refunded_bps: 0,
+            refunded_lamports: 0,
+            nft_reclaimed: false,
+            escrow_bump: 254,
+            version: Listing::VERSION,
+        }
+    }
+}
+
+// ============== EVENTS ==============
+// Emitted with `emit_cpi!` so they survive log truncation; every timestamp is the cluster clock
+// at the time of the transition.
+
+#[event]
+pub struct ConfigInitialized {
+    pub authority: Pubkey,
+    pub fee_vault: Pubkey,
+    pub custody_fee_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConfigUpdated {
+    pub authority: Pubkey,
+    pub allowlist_enabled: bool,
+    pub royalties_enabled: bool,
+    pub keeper_bounty_lamports: u64,
+    pub timestamp: i64,
+}
+
+/// `limits` is `None` when the collection was removed from the allowlist.
+#[event]
+pub struct CollectionAllowlistUpdated {
+    pub collection: Pubkey,
+    pub limits: Option<CollectionLimits>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ListingCreated {
+    pub listing: Pubkey,
+    pub nft_mint: Pubkey,
+    pub seller: Pubkey,
+    pub price_lamports: u64,
+    pub custody_fee_lamports: u64,
+    pub total_raise_lamports: u64,
+    pub retained_bps: u16,
+    pub min_fill_bps: u16,
+    pub deadline: i64,
+    pub auction: bool,
+    pub presale: bool,
+    pub keeper_tip_lamports: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ContributionMade {
+    pub listing: Pubkey,
+    pub contributor: Pubkey,
+    pub bps: u16,
+    pub principal_lamports: u64,
+    pub fee_lamports: u64,
+    /// The contributor's accumulated position after this contribution.
+    pub wallet_bps: u16,
+    pub bps_sold: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ListingFunded {
+    pub listing: Pubkey,
+    pub bps_sold: u16,
+    pub price_lamports: u64,
+    pub custody_fee_lamports: u64,
+    pub contributor_count: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PurchaseExecuted {
+    pub listing: Pubkey,
+    pub executor: Pubkey,
+    pub bps_sold: u16,
+    pub retained_bps: u16,
+    /// Price of the sold bps, royalties included.
+    pub price_lamports: u64,
+    /// Custody fee sent to the fee vault, net of any keeper bounty shortfall.
+    pub custody_fee_lamports: u64,
+    pub royalty_lamports: u64,
+    pub seller_proceeds_lamports: u64,
+    /// Unspent keeper tip returned to the seller alongside the proceeds.
+    pub keeper_tip_refund_lamports: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ListingExpired {
+    pub listing: Pubkey,
+    pub bps_sold: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RefundProcessed {
+    pub listing: Pubkey,
+    pub contributor: Pubkey,
+    pub bps: u16,
+    pub amount_lamports: u64,
+    pub refunded_bps: u16,
+    pub refunded_lamports: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ListingRefunded {
+    pub listing: Pubkey,
+    pub refunded_bps: u16,
+    pub refunded_lamports: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct NftReclaimed {
+    pub listing: Pubkey,
+    pub nft_mint: Pubkey,
+    pub seller: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ListingClosed {
+    pub listing: Pubkey,
+    pub seller: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ListingMigrated {
+    pub listing: Pubkey,
+    pub previous_version: u8,
+    pub version: u8,
+    /// Account size after the migration, discriminator included.
+    pub space: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AccountMigrated {
+    pub account: Pubkey,
+    pub previous_version: u8,
+    pub version: u8,
+    /// Account size after the migration, discriminator included.
+    pub space: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuctionRebateClaimed {
+    pub listing: Pubkey,
+    pub contributor: Pubkey,
+    pub amount_lamports: u64,
+    pub clearing_price_lamports: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalCreated {
+    pub listing: Pubkey,
+    pub proposal: Pubkey,
+    pub proposal_id: u32,
+    pub proposer: Pubkey,
+    pub sale_price_lamports: u64,
+    pub vote_deadline: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VoteCast {
+    pub listing: Pubkey,
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub vote: Vote,
+    pub bps: u16,
+    pub yes_bps: u16,
+    pub no_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalResolved {
+    pub listing: Pubkey,
+    pub proposal: Pubkey,
+    pub status: ProposalStatus,
+    pub yes_bps: u16,
+    pub no_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardRegistered {
+    pub listing: Pubkey,
+    pub registry: Pubkey,
+    pub reward_mint: Pubkey,
+    pub depositor: Pubkey,
+    pub amount: u64,
+    pub total_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardClaimed {
+    pub listing: Pubkey,
+    pub registry: Pubkey,
+    pub claimer: Pubkey,
+    pub amount: u64,
+    pub claimed_total: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CreatorRoyaltyPaid {
+    pub listing: Pubkey,
+    pub creator: Pubkey,
+    pub share: u8,
+    pub sale_price_lamports: u64,
+    pub amount_lamports: u64,
+}
+
+#[event]
+pub struct KeeperBountyPaid {
+    pub listing: Pubkey,
+    pub keeper: Pubkey,
+    pub action: KeeperAction,
+    pub from_tip_lamports: u64,
+    pub from_fee_lamports: u64,
+}
+
+#[event]
+pub struct SoftCloseTriggered {
+    pub listing: Pubkey,
+    pub contributor: Pubkey,
+    pub bps: u16,
+    pub deadline: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DeadlineExtended {
+    pub listing: Pubkey,
+    pub previous_deadline: i64,
+    pub new_deadline: i64,
+    pub hard_deadline: i64,
+}
+
+// ============== VIEW RETURN TYPES ==============
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct ContributionQuote {
+    pub bps: u16,
+    pub principal_lamports: u64,
+    pub fee_lamports: u64,
+    pub total_lamports: u64,
+    /// Full-NFT price the quote was computed at, after auction repricing.
+    pub price_lamports: u64,
+    pub remaining_bps: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct RefundQuote {
+    /// The listing has failed and this position has not been refunded yet.
+    pub refundable: bool,
+    pub refund_claimed: bool,
+    pub bps: u16,
+    pub principal_lamports: u64,
+    pub fee_lamports: u64,
+    pub total_lamports: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct RewardQuote {
+    pub bps: u16,
+    /// Everything ever deposited into the registry.
+    pub total_amount: u64,
+    /// Already claimed by this wallet.
+    pub claimed_amount: u64,
+    pub claimable_amount: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct ListingSummary {
+    pub status: ListingStatus,
+    pub price_lamports: u64,
+    pub custody_fee_lamports: u64,
+    pub total_raise_lamports: u64,
+    pub bps_for_sale: u16,
+    pub bps_sold: u16,
+    pub remaining_bps: u16,
+    pub contributor_count: u32,
+    pub deadline: i64,
+    pub execution_window_start: Option<i64>,
+    pub refundable: bool,
+    /// Contributor SOL and keeper tips held by the escrow PDA, excluding its rent reserve.
+    pub escrowed_lamports: u64,
+    pub refunded_bps: u16,
+    pub refunded_lamports: u64,
+    pub nft_reclaimed: bool,
+}
+
 // ============== CONTEXT STRUCTS ==============
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct InitializeConfig<'info> {
     #[account(
@@ -600,7 +2222,7 @@ pub struct InitializeConfig<'info> {
         seeds = [b"config"],
         bump,
         payer = authority,
-        space = 8 + Config::LEN
+        space = 8 + Config::INIT_SPACE
     )]
     pub config: Account<'info, Config>,
     #[account(mut)]
@@ -610,6 +2232,69 @@ pub struct InitializeConfig<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(collection_mint: Pubkey)]
+pub struct AddCollection<'info> {
+    #[account(seeds = [b"config"], bump = config.bump, has_one = authority @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        seeds = [b"allowlist", collection_mint.as_ref()],
+        bump,
+        payer = authority,
+        space = 8 + CollectionAllowlist::INIT_SPACE
+    )]
+    pub collection_allowlist: Account<'info, CollectionAllowlist>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateCollection<'info> {
+    #[account(seeds = [b"config"], bump = config.bump, has_one = authority @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"allowlist", collection_allowlist.collection.as_ref()],
+        bump = collection_allowlist.bump
+    )]
+    pub collection_allowlist: Account<'info, CollectionAllowlist>,
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RemoveCollection<'info> {
+    #[account(seeds = [b"config"], bump = config.bump, has_one = authority @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"allowlist", collection_allowlist.collection.as_ref()],
+        bump = collection_allowlist.bump,
+        close = authority
+    )]
+    pub collection_allowlist: Account<'info, CollectionAllowlist>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
 #[derive(Accounts)]
 pub struct CreateListing<'info> {
     #[account(seeds = [b"config"], bump = config.bump)]
@@ -619,10 +2304,35 @@ pub struct CreateListing<'info> {
         seeds = [b"listing", nft_mint.key().as_ref()],
         bump,
         payer = seller,
-        space = 8 + Listing::LEN
+        space = 8 + Listing::INIT_SPACE
     )]
     pub listing: Account<'info, Listing>,
+    /// Escrow PDA for contributor SOL; created by the seller's rent deposit.
+    #[account(
+        mut,
+        seeds = [b"escrow", listing.key().as_ref()],
+        bump
+    )]
+    pub escrow: SystemAccount<'info>,
     pub nft_mint: Account<'info, Mint>,
+    /// CHECK: Token Metadata PDA for `nft_mint`; owner and contents are verified in the handler.
+    #[account(
+        seeds = [b"metadata", mpl_token_metadata::ID.as_ref(), nft_mint.key().as_ref()],
+        seeds::program = mpl_token_metadata::ID,
+        bump
+    )]
+    pub nft_metadata: UncheckedAccount<'info>,
+    /// CHECK: Master edition PDA for `nft_mint`; only read when the mint authority is not revoked.
+    #[account(
+        seeds = [b"metadata", mpl_token_metadata::ID.as_ref(), nft_mint.key().as_ref(), b"edition"],
+        seeds::program = mpl_token_metadata::ID,
+        bump
+    )]
+    pub nft_master_edition: UncheckedAccount<'info>,
+    /// CHECK: allowlist PDA of the NFT's verified collection, `[b"allowlist", collection]`; required
+    /// whenever the NFT has a verified collection and read if the entry exists. Checked in the handler,
+    /// since the collection is only known once the metadata is decoded.
+    pub collection_allowlist: Option<UncheckedAccount<'info>>,
     #[account(
         mut,
         constraint = seller_nft_account.mint == nft_mint.key() @ ErrorCode::InvalidMint,
@@ -646,31 +2356,82 @@ pub struct CreateListing<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
+pub struct Contribute<'info> {
+    #[account(
+        mut,
+        seeds = [b"listing", listing.nft_mint.as_ref()],
+        bump = listing.bump
+    )]
+    pub listing: Account<'info, Listing>,
+    /// Holds contributor SOL for the listing.
+    #[account(
+        mut,
+        seeds = [b"escrow", listing.key().as_ref()],
+        bump = listing.escrow_bump
+    )]
+    pub escrow: SystemAccount<'info>,
+    #[account(
+        init_if_needed,
+        seeds = [b"contribution", listing.key().as_ref(), contributor.key().as_ref()],
+        bump,
+        payer = contributor,
+        space = 8 + Contribution::INIT_SPACE
+    )]
+    pub contribution: Account<'info, Contribution>,
+    #[account(mut)]
+    pub contributor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
 
+#[event_cpi]
 #[derive(Accounts)]
-pub 
-
-pub struct Contribute<'info> {
+pub struct ContributeAndExecute<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
     #[account(
         mut,
         seeds = [b"listing", listing.nft_mint.as_ref()],
         bump = listing.bump
     )]
     pub listing: Account<'info, Listing>,
+    /// Holds contributor SOL for the listing.
+    #[account(
+        mut,
+        seeds = [b"escrow", listing.key().as_ref()],
+        bump = listing.escrow_bump
+    )]
+    pub escrow: SystemAccount<'info>,
     #[account(
         init_if_needed,
         seeds = [b"contribution", listing.key().as_ref(), contributor.key().as_ref()],
         bump,
         payer = contributor,
-        space = 8 + Contribution::LEN
+        space = 8 + Contribution::INIT_SPACE
     )]
     pub contribution: Account<'info, Contribution>,
+    /// Pays the contribution and, if it completes the raise, receives the keeper bounty.
     #[account(mut)]
     pub contributor: Signer<'info>,
+    /// CHECK: Seller receives lamports; does not need to sign for non-custodial execution.
+    #[account(mut, constraint = seller.key() == listing.nft_seller @ ErrorCode::Unauthorized)]
+    pub seller: UncheckedAccount<'info>,
+    /// CHECK: Fee vault validated against config
+    #[account(mut, constraint = fee_vault.key() == config.fee_vault @ ErrorCode::InvalidFeeVault)]
+    pub fee_vault: UncheckedAccount<'info>,
+    /// CHECK: Seller's retained position; required when the seller keeps any bps. Created by
+    /// `settle_purchase` only if this contribution completes the raise.
+    #[account(
+        mut,
+        seeds = [b"contribution", listing.key().as_ref(), seller.key().as_ref()],
+        bump
+    )]
+    pub seller_contribution: Option<UncheckedAccount<'info>>,
     pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct ExecutePurchase<'info> {
     #[account(seeds = [b"config"], bump = config.bump)]
@@ -681,6 +2442,13 @@ pub struct ExecutePurchase<'info> {
         bump = listing.bump,
     )]
     pub listing: Account<'info, Listing>,
+    /// Holds contributor SOL for the listing.
+    #[account(
+        mut,
+        seeds = [b"escrow", listing.key().as_ref()],
+        bump = listing.escrow_bump
+    )]
+    pub escrow: SystemAccount<'info>,
     pub nft_mint: Account<'info, Mint>,
     /// CHECK: Seller receives lamports; does not need to sign for non-custodial execution.
     #[account(mut, constraint = seller.key() == listing.nft_seller @ ErrorCode::Unauthorized)]
@@ -688,9 +2456,21 @@ pub struct ExecutePurchase<'info> {
     /// CHECK: Fee vault validated against config
     #[account(mut, constraint = fee_vault.key() == config.fee_vault @ ErrorCode::InvalidFeeVault)]
     pub fee_vault: UncheckedAccount<'info>,
+    /// CHECK: Seller's retained position; required when the seller keeps any bps. Created by
+    /// `settle_purchase` if missing.
+    #[account(
+        mut,
+        seeds = [b"contribution", listing.key().as_ref(), seller.key().as_ref()],
+        bump
+    )]
+    pub seller_contribution: Option<UncheckedAccount<'info>>,
+    /// Permissionless caller; receives the keeper bounty.
+    #[account(mut)]
+    pub executor: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct ProcessRefund<'info> {
     #[account(
@@ -698,7 +2478,67 @@ pub struct ProcessRefund<'info> {
         seeds = [b"listing", listing.nft_mint.as_ref()],
         bump = listing.bump
     )]
-    pub listing: Account<'info, Listing>,
+    pub listing: Account<'info, Listing>,
+    /// Holds contributor SOL for the listing.
+    #[account(
+        mut,
+        seeds = [b"escrow", listing.key().as_ref()],
+        bump = listing.escrow_bump
+    )]
+    pub escrow: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"contribution", listing.key().as_ref(), contributor.key().as_ref()],
+        bump = contribution.bump,
+        constraint = contribution.wallet == contributor.key() @ ErrorCode::Unauthorized,
+        close = contributor
+    )]
+    pub contribution: Account<'info, Contribution>,
+    #[account(mut)]
+    pub contributor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CrankRefunds<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"listing", listing.nft_mint.as_ref()],
+        bump = listing.bump
+    )]
+    pub listing: Account<'info, Listing>,
+    /// Holds contributor SOL for the listing.
+    #[account(
+        mut,
+        seeds = [b"escrow", listing.key().as_ref()],
+        bump = listing.escrow_bump
+    )]
+    pub escrow: SystemAccount<'info>,
+    /// Receives the keeper bounty.
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimAuctionRebate<'info> {
+    #[account(
+        mut,
+        seeds = [b"listing", listing.nft_mint.as_ref()],
+        bump = listing.bump
+    )]
+    pub listing: Account<'info, Listing>,
+    /// Holds contributor SOL for the listing.
+    #[account(
+        mut,
+        seeds = [b"escrow", listing.key().as_ref()],
+        bump = listing.escrow_bump
+    )]
+    pub escrow: SystemAccount<'info>,
     #[account(
         mut,
         seeds = [b"contribution", listing.key().as_ref(), contributor.key().as_ref()],
@@ -711,7 +2551,9 @@ pub struct ProcessRefund<'info> {
     pub system_program: Program<'info, System>,
 }
 
-struct ReclaimNft<'info> {
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ReclaimNft<'info> {
     #[account(seeds = [b"config"], bump = config.bump)]
     pub config: Account<'info, Config>,
     #[account(
@@ -738,6 +2580,61 @@ struct ReclaimNft<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CloseListing<'info> {
+    #[account(
+        mut,
+        seeds = [b"listing", listing.nft_mint.as_ref()],
+        bump = listing.bump,
+        close = seller
+    )]
+    pub listing: Account<'info, Listing>,
+    /// Holds contributor SOL for the listing.
+    #[account(
+        mut,
+        seeds = [b"escrow", listing.key().as_ref()],
+        bump = listing.escrow_bump
+    )]
+    pub escrow: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", listing.nft_mint.as_ref()],
+        bump,
+        constraint = vault.key() == listing.vault @ ErrorCode::InvalidVault
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(mut, constraint = seller.key() == listing.nft_seller @ ErrorCode::Unauthorized)]
+    pub seller: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MigrateListing<'info> {
+    /// CHECK: read as raw data, since a listing older than `Listing::VERSION` may be too short to
+    /// decode; the handler checks the discriminator once the account has been grown.
+    #[account(mut, owner = crate::ID)]
+    pub listing: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    /// CHECK: read as raw data, since an account older than its type's version is too short to
+    /// decode; the handler dispatches on the discriminator.
+    #[account(mut, owner = crate::ID)]
+    pub account: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
 #[derive(Accounts)]
 pub struct CreateProposal<'info> {
     #[account(
@@ -757,7 +2654,7 @@ pub struct CreateProposal<'info> {
         seeds = [b"proposal", listing.key().as_ref(), &listing.proposal_count.to_le_bytes()],
         bump,
         payer = proposer,
-        space = 8 + Proposal::LEN
+        space = 8 + Proposal::INIT_SPACE
     )]
     pub proposal: Account<'info, Proposal>,
     #[account(mut)]
@@ -765,6 +2662,38 @@ pub struct CreateProposal<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FinalizeProposal<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"listing", listing.nft_mint.as_ref()],
+        bump = listing.bump
+    )]
+    pub listing: Account<'info, Listing>,
+    /// Holds contributor SOL for the listing.
+    #[account(
+        mut,
+        seeds = [b"escrow", listing.key().as_ref()],
+        bump = listing.escrow_bump
+    )]
+    pub escrow: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"proposal", listing.key().as_ref(), &proposal.proposal_id.to_le_bytes()],
+        bump = proposal.bump,
+        constraint = proposal.listing == listing.key() @ ErrorCode::InvalidProposal
+    )]
+    pub proposal: Account<'info, Proposal>,
+    /// Receives the keeper bounty.
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
 #[derive(Accounts)]
 pub struct CastVote<'info> {
     #[account(seeds = [b"listing", listing.nft_mint.as_ref()], bump = listing.bump)]
@@ -787,7 +2716,7 @@ pub struct CastVote<'info> {
         seeds = [b"vote", proposal.key().as_ref(), voter.key().as_ref()],
         bump,
         payer = voter,
-        space = 8 + VoteRecord::LEN
+        space = 8 + VoteRecord::INIT_SPACE
     )]
     pub vote_record: Account<'info, VoteRecord>,
     #[account(mut)]
@@ -795,6 +2724,7 @@ pub struct CastVote<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct RegisterReward<'info> {
     #[account(seeds = [b"listing", listing.nft_mint.as_ref()], bump = listing.bump)]
@@ -805,7 +2735,7 @@ pub struct RegisterReward<'info> {
         seeds = [b"reward", listing.key().as_ref(), reward_mint.key().as_ref()],
         bump,
         payer = depositor,
-        space = 8 + RewardRegistry::LEN
+        space = 8 + RewardRegistry::INIT_SPACE
     )]
     pub reward_registry: Account<'info, RewardRegistry>,
     #[account(
@@ -828,6 +2758,7 @@ pub struct RegisterReward<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct ClaimReward<'info> {
     #[account(seeds = [b"listing", listing.nft_mint.as_ref()], bump = listing.bump)]
@@ -866,7 +2797,7 @@ pub struct ClaimReward<'info> {
         seeds = [b"claim", reward_registry.key().as_ref(), claimer.key().as_ref()],
         bump,
         payer = claimer,
-        space = 8 + ClaimRecord::LEN
+        space = 8 + ClaimRecord::INIT_SPACE
     )]
     pub claim_record: Account<'info, ClaimRecord>,
     pub token_program: Program<'info, Token>,
@@ -874,13 +2805,613 @@ pub struct ClaimReward<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct QuoteContribution<'info> {
+    #[account(seeds = [b"listing", listing.nft_mint.as_ref()], bump = listing.bump)]
+    pub listing: Account<'info, Listing>,
+    /// CHECK: the wallet the quote is for; only its key is read.
+    pub contributor: UncheckedAccount<'info>,
+    /// CHECK: the wallet's position; may not exist yet, read only if owned by this program.
+    #[account(seeds = [b"contribution", listing.key().as_ref(), contributor.key().as_ref()], bump)]
+    pub contribution: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct QuoteRefund<'info> {
+    #[account(seeds = [b"listing", listing.nft_mint.as_ref()], bump = listing.bump)]
+    pub listing: Account<'info, Listing>,
+    #[account(
+        seeds = [b"contribution", listing.key().as_ref(), contribution.wallet.as_ref()],
+        bump = contribution.bump
+    )]
+    pub contribution: Account<'info, Contribution>,
+}
+
+#[derive(Accounts)]
+pub struct QuoteClaimableReward<'info> {
+    #[account(seeds = [b"listing", listing.nft_mint.as_ref()], bump = listing.bump)]
+    pub listing: Account<'info, Listing>,
+    #[account(
+        seeds = [b"contribution", listing.key().as_ref(), contribution.wallet.as_ref()],
+        bump = contribution.bump
+    )]
+    pub contribution: Account<'info, Contribution>,
+    #[account(
+        seeds = [b"reward", listing.key().as_ref(), reward_registry.reward_mint.as_ref()],
+        bump = reward_registry.bump,
+        constraint = reward_registry.listing == listing.key() @ ErrorCode::InvalidRewardRegistry
+    )]
+    pub reward_registry: Account<'info, RewardRegistry>,
+    /// CHECK: the wallet's claim record; may not exist yet, read only if owned by this program.
+    #[account(seeds = [b"claim", reward_registry.key().as_ref(), contribution.wallet.as_ref()], bump)]
+    pub claim_record: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ListingSummaryView<'info> {
+    #[account(seeds = [b"listing", listing.nft_mint.as_ref()], bump = listing.bump)]
+    pub listing: Account<'info, Listing>,
+    #[account(seeds = [b"escrow", listing.key().as_ref()], bump = listing.escrow_bump)]
+    pub escrow: SystemAccount<'info>,
+}
+
+// ============== SHARED INSTRUCTION LOGIC ==============
+
+/// Events produced by `apply_contribution`, emitted by the calling instruction.
+pub struct ContributionEvents {
+    pub contribution: ContributionMade,
+    pub funded: Option<TransitionEvent>,
+    pub soft_close: Option<SoftCloseTriggered>,
+    pub deadline_extended: Option<DeadlineExtended>,
+}
+
+/// Contribution logic shared by `contribute` and `contribute_and_execute`.
+pub fn apply_contribution<'info>(
+    listing: &mut Account<'info, Listing>,
+    contribution: &mut Account<'info, Contribution>,
+    contribution_bump: u8,
+    contributor: &Signer<'info>,
+    escrow: &ListingEscrow<'_, 'info>,
+    bps: u16,
+    presale_proof: Option<PresaleProof>,
+) -> Result<ContributionEvents> {
+    let clock = Clock::get()?;
+    if contribution.is_stale() {
+        contribution.open(listing.key(), contributor.key(), contribution_bump);
+    }
+
+    let (principal, fee_share) = listing.price_contribution(
+        &contributor.key(),
+        contribution.bps,
+        bps,
+        presale_proof.as_ref(),
+        clock.unix_timestamp,
+    )?;
+    let bps_for_sale = listing.bps_for_sale();
+    let total_payment = principal
+        .checked_add(fee_share)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    escrow.deposit(&contributor.to_account_info(), total_payment)?;
+
+    // Support multiple contributions (top-ups) from the same wallet for the same listing.
+    // The PDA seed ties the contribution to (listing, contributor), so only the contributor can pay in.
+    let first_contribution = contribution.bps == 0;
+    if first_contribution {
+        // First contribution initialization
+        contribution.open(listing.key(), contributor.key(), contribution_bump);
+    } else {
+        // Safety: contribution must belong to this listing + wallet
+        require!(contribution.listing == listing.key(), ErrorCode::InvalidContribution);
+        require!(contribution.wallet == contributor.key(), ErrorCode::InvalidContribution);
+    }
+
+    // Accumulate
+    contribution.bps = contribution.bps.checked_add(bps).ok_or(ErrorCode::MathOverflow)?;
+    contribution.principal_lamports = contribution.principal_lamports.checked_add(principal).ok_or(ErrorCode::MathOverflow)?;
+    contribution.fee_lamports = contribution.fee_lamports.checked_add(fee_share).ok_or(ErrorCode::MathOverflow)?;
+
+    listing.bps_sold = listing.bps_sold.checked_add(bps).ok_or(ErrorCode::MathOverflow)?;
+    if first_contribution {
+        listing.contributor_count = listing.contributor_count.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+    }
+
+    let mut events = ContributionEvents {
+        contribution: ContributionMade {
+            listing: listing.key(),
+            contributor: contributor.key(),
+            bps,
+            principal_lamports: principal,
+            fee_lamports: fee_share,
+            wallet_bps: contribution.bps,
+            bps_sold: listing.bps_sold,
+            timestamp: clock.unix_timestamp,
+        },
+        funded: None,
+        soft_close: None,
+        deadline_extended: None,
+    };
+
+    if listing.bps_sold == bps_for_sale {
+        let listing_key = listing.key();
+        events.funded = Some(listing.transition(listing_key, Transition::Fund, clock.unix_timestamp)?);
+    } else if let Some(soft_close) = listing.soft_close.clone() {
+        // Anti-sniping: a contribution in the final `trigger_seconds` pushes the deadline out,
+        // never past the hard deadline fixed at listing creation.
+        if clock.unix_timestamp >= listing.deadline - soft_close.trigger_seconds {
+            let previous_deadline = listing.deadline;
+            events.soft_close = Some(SoftCloseTriggered {
+                listing: listing.key(),
+                contributor: contributor.key(),
+                bps,
+                deadline: previous_deadline,
+                timestamp: clock.unix_timestamp,
+            });
+
+            let new_deadline = previous_deadline
+                .checked_add(soft_close.extension_seconds)
+                .ok_or(ErrorCode::MathOverflow)?
+                .min(soft_close.hard_deadline);
+            if new_deadline > previous_deadline {
+                listing.deadline = new_deadline;
+                events.deadline_extended = Some(DeadlineExtended {
+                    listing: listing.key(),
+                    previous_deadline,
+                    new_deadline,
+                    hard_deadline: soft_close.hard_deadline,
+                });
+            }
+        }
+    }
+
+    Ok(events)
+}
+
+/// Events produced by `settle_purchase`, emitted by the calling instruction.
+pub struct SettlementEvents {
+    pub royalties: Vec<CreatorRoyaltyPaid>,
+    pub keeper_bounty: Option<KeeperBountyPaid>,
+    pub executed: TransitionEvent,
+}
+
+/// Purchase settlement shared by `execute_purchase` and `contribute_and_execute`.
+#[allow(clippy::too_many_arguments)]
+pub fn settle_purchase<'info>(
+    config: &Account<'info, Config>,
+    listing: &mut Account<'info, Listing>,
+    escrow: &ListingEscrow<'_, 'info>,
+    seller: &AccountInfo<'info>,
+    fee_vault: &AccountInfo<'info>,
+    seller_contribution: Option<&AccountInfo<'info>>,
+    seller_contribution_bump: Option<u8>,
+    executor: &AccountInfo<'info>,
+    creator_accounts: &[AccountInfo<'info>],
+) -> Result<SettlementEvents> {
+    let clock = Clock::get()?;
+    listing.check_transition(&ListingStatus::Custodied, clock.unix_timestamp)?;
+
+    // A partial fill executes at a proportionally reduced price and fee.
+    let price = listing.sold_price_lamports()?;
+    let fee = listing.sold_custody_fee_lamports()?;
+
+    // Ensure sufficient escrowed lamports (defensive; should be true if contributions succeeded).
+    require!(
+        escrow.escrowed()? >= price.checked_add(fee).ok_or(ErrorCode::MathOverflow)?,
+        ErrorCode::InsufficientListingLamports
+    );
+
+    let royalty_events = pay_creator_royalties(
+        escrow,
+        &listing.creators,
+        listing.royalty_bps,
+        price,
+        creator_accounts,
+    )?;
+    let royalties = royalty_events
+        .iter()
+        .try_fold(0u64, |total, event| total.checked_add(event.amount_lamports))
+        .ok_or(ErrorCode::MathOverflow)?;
+    // Unsold bps stay with the seller as a regular position: full voting and reward weight,
+    // but zero principal, so there is nothing to refund. The position is only created here, once
+    // the purchase settles, and its rent comes out of the seller's proceeds.
+    let retained_bps = 10000u16.checked_sub(listing.bps_sold).ok_or(ErrorCode::MathOverflow)?;
+    let seller_position = match retained_bps {
+        0 => None,
+        _ => Some((
+            seller_contribution.ok_or(ErrorCode::InvalidContribution)?,
+            seller_contribution_bump.ok_or(ErrorCode::InvalidContribution)?,
+        )),
+    };
+    let position_rent = match seller_position {
+        Some((account, _)) if account.owner != &crate::ID => Rent::get()?
+            .minimum_balance(8 + Contribution::INIT_SPACE)
+            .saturating_sub(account.lamports()),
+        _ => 0,
+    };
+    let seller_proceeds = price
+        .checked_sub(royalties)
+        .and_then(|proceeds| proceeds.checked_sub(position_rent))
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    escrow.pay_out(seller, seller_proceeds)?;
+
+    // Whoever executes is paid the keeper bounty: from the seller's tip pool first, with any
+    // shortfall taken out of the custody fee.
+    let bounty = config.keeper_bounty_lamports;
+    let from_tip = bounty.min(listing.keeper_tip_lamports);
+    let from_fee = (bounty - from_tip).min(fee);
+    let fee_to_vault = fee - from_fee;
+
+    escrow.pay_out(fee_vault, fee_to_vault)?;
+
+    let keeper_bounty = pay_keeper_bounty(
+        listing,
+        escrow,
+        executor,
+        KeeperAction::ExecutePurchase,
+        from_tip,
+        from_fee,
+    )?;
+
+    // The rest of the tip goes back to the seller, less one bounty held for the first proposal
+    // finalized on the custodied listing, so finalize bounties cannot drain it proposal by proposal.
+    let tip_reserve = config.keeper_bounty_lamports.min(listing.keeper_tip_lamports);
+    let tip_refund = listing.keeper_tip_lamports - tip_reserve;
+    escrow.pay_out(seller, tip_refund)?;
+    listing.keeper_tip_lamports = tip_reserve;
+
+    let listing_key = listing.key();
+    if let Some((account, bump)) = seller_position {
+        let mut position = if account.owner == &crate::ID {
+            Contribution::try_deserialize(&mut &account.try_borrow_data()?[..])?
+        } else {
+            create_contribution_account(escrow, account, &listing_key, seller.key, bump, position_rent)?;
+            Contribution::try_deserialize_unchecked(&mut &account.try_borrow_data()?[..])?
+        };
+        if position.bps == 0 || position.is_stale() {
+            position.open(listing_key, seller.key(), bump);
+        }
+        position.bps = position.bps
+            .checked_add(retained_bps)
+            .ok_or(ErrorCode::MathOverflow)?;
+        position.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+    }
+    let executed = PurchaseExecuted {
+        listing: listing_key,
+        executor: executor.key(),
+        bps_sold: listing.bps_sold,
+        retained_bps,
+        price_lamports: price,
+        custody_fee_lamports: fee_to_vault,
+        royalty_lamports: royalties,
+        seller_proceeds_lamports: seller_proceeds,
+        keeper_tip_refund_lamports: tip_refund,
+        timestamp: clock.unix_timestamp,
+    };
+
+    Ok(SettlementEvents {
+        royalties: royalty_events,
+        keeper_bounty,
+        executed: listing.transition(listing_key, Transition::Execute(executed), clock.unix_timestamp)?,
+    })
+}
+
+// ============== HELPERS ==============
+
+/// An account type ending in a `version` byte, brought up to date by `migrate_listing` or
+/// `migrate_account`.
+pub trait Versioned: AccountSerialize + AccountDeserialize + Space {
+    const CURRENT_VERSION: u8;
+
+    fn version_mut(&mut self) -> &mut u8;
+}
+
+macro_rules! versioned {
+    ($($account:ident),* $(,)?) => {
+        $(
+            impl Versioned for $account {
+                const CURRENT_VERSION: u8 = $account::VERSION;
+
+                fn version_mut(&mut self) -> &mut u8 {
+                    &mut self.version
+                }
+            }
+        )*
+    };
+}
+
+versioned!(Config, CollectionAllowlist, Listing, Contribution, Proposal, VoteRecord, RewardRegistry, ClaimRecord);
+
+/// Grows a `T` account to `8 + T::INIT_SPACE`, `payer` covering the extra rent, and stamps
+/// `T::CURRENT_VERSION`; returns the version it had. The new bytes are zeroed, so an account that
+/// predates its version byte reads as version 0. Fails with `already_migrated` if it is current.
+pub fn migrate_versioned<'info, T: Versioned>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    already_migrated: ErrorCode,
+) -> Result<u8> {
+    let space = 8 + T::INIT_SPACE;
+    if account.data_len() < space {
+        let shortfall = Rent::get()?.minimum_balance(space).saturating_sub(account.lamports());
+        if shortfall > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    anchor_lang::system_program::Transfer { from: payer.clone(), to: account.clone() },
+                ),
+                shortfall,
+            )?;
+        }
+        account.realloc(space, true)?;
+    }
+
+    let mut state = T::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+    let previous_version = *state.version_mut();
+    if previous_version >= T::CURRENT_VERSION {
+        return Err(already_migrated.into());
+    }
+    *state.version_mut() = T::CURRENT_VERSION;
+    state.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+    Ok(previous_version)
+}
+
+/// Lamports held by an account, split into the rent-exempt reserve the account
+/// needs to stay alive and the escrowed funds that instructions may pay out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LamportLedger {
+    pub balance: u64,
+    pub rent_reserve: u64,
+}
+
+impl LamportLedger {
+    pub fn new(balance: u64, rent_reserve: u64) -> Self {
+        Self { balance, rent_reserve }
+    }
+
+    pub fn for_account(account: &AccountInfo) -> Result<Self> {
+        let rent_reserve = Rent::get()?.minimum_balance(account.data_len());
+        Ok(Self::new(account.lamports(), rent_reserve))
+    }
+
+    pub fn escrowed(&self) -> u64 {
+        self.balance.saturating_sub(self.rent_reserve)
+    }
+
+    pub fn debit(&mut self, amount: u64) -> Result<()> {
+        require!(amount <= self.escrowed(), ErrorCode::InsufficientListingLamports);
+        self.balance -= amount;
+        Ok(())
+    }
+}
+
+/// A listing's escrow PDA (`[b"escrow", listing]`): a data-less, system-owned account holding
+/// contributor SOL and the keeper tip pool. Only the program can sign transfers out of it.
+pub struct ListingEscrow<'a, 'info> {
+    pub account: &'a AccountInfo<'info>,
+    pub listing: Pubkey,
+    pub bump: u8,
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> ListingEscrow<'a, 'info> {
+    pub fn new(
+        account: &'a AccountInfo<'info>,
+        listing: &Account<Listing>,
+        system_program: &'a AccountInfo<'info>,
+    ) -> Self {
+        Self {
+            account,
+            listing: listing.key(),
+            bump: listing.escrow_bump,
+            system_program,
+        }
+    }
+
+    // Lamports held for the listing, excluding the escrow's own rent-exempt reserve.
+    pub fn escrowed(&self) -> Result<u64> {
+        Ok(LamportLedger::for_account(self.account)?.escrowed())
+    }
+
+    pub fn deposit(&self, from: &AccountInfo<'info>, amount: u64) -> Result<()> {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                self.system_program.clone(),
+                anchor_lang::system_program::Transfer {
+                    from: from.clone(),
+                    to: self.account.clone(),
+                },
+            ),
+            amount,
+        )
+    }
+
+    // Pays out of the escrowed balance; the rent-exempt reserve is never touched.
+    pub fn pay_out(&self, to: &AccountInfo<'info>, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        LamportLedger::for_account(self.account)?.debit(amount)?;
+        self.transfer(to, amount)
+    }
+
+    // Empties the escrow, reserve included. Only for closing the listing.
+    pub fn drain(&self, to: &AccountInfo<'info>) -> Result<()> {
+        let balance = self.account.lamports();
+        if balance == 0 {
+            return Ok(());
+        }
+        self.transfer(to, balance)
+    }
+
+    fn transfer(&self, to: &AccountInfo<'info>, amount: u64) -> Result<()> {
+        let bump = [self.bump];
+        let seeds: &[&[u8]] = &[b"escrow", self.listing.as_ref(), &bump];
+        anchor_lang::system_program::transfer(
+            CpiContext::new_with_signer(
+                self.system_program.clone(),
+                anchor_lang::system_program::Transfer {
+                    from: self.account.clone(),
+                    to: to.clone(),
+                },
+                &[seeds],
+            ),
+            amount,
+        )
+    }
+}
+
+/// Splits the creator royalty off a sale paid out of the listing escrow and returns one event per
+/// creator paid.
+/// Only primary execution sells the NFT today; settling an approved sale proposal, which would
+/// pay royalties the same way, is out of scope (see `ListingStatus::Sold`).
+/// `creator_accounts` must hold one writable account per entry in `creators`, in the same order.
+/// Rounding dust stays with the seller.
+pub fn pay_creator_royalties<'info>(
+    escrow: &ListingEscrow<'_, 'info>,
+    creators: &[ListingCreator],
+    royalty_bps: u16,
+    sale_price: u64,
+    creator_accounts: &[AccountInfo<'info>],
+) -> Result<Vec<CreatorRoyaltyPaid>> {
+    if royalty_bps == 0 || creators.is_empty() {
+        return Ok(Vec::new());
+    }
+    require!(creator_accounts.len() >= creators.len(), ErrorCode::InvalidCreatorAccount);
+
+    let royalty = sale_price
+        .checked_mul(royalty_bps as u64)
+        .ok_or(ErrorCode::MathOverflow)?
+        .checked_div(10000)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    let mut paid = Vec::with_capacity(creators.len());
+    for (creator, creator_account) in creators.iter().zip(creator_accounts.iter()) {
+        require_keys_eq!(creator_account.key(), creator.address, ErrorCode::InvalidCreatorAccount);
+        require!(creator_account.is_writable, ErrorCode::InvalidCreatorAccount);
+
+        let amount = royalty
+            .checked_mul(creator.share as u64)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_div(100)
+            .ok_or(ErrorCode::MathOverflow)?;
+        if amount == 0 {
+            continue;
+        }
+
+        escrow.pay_out(creator_account, amount)?;
+        paid.push(CreatorRoyaltyPaid {
+            listing: escrow.listing,
+            creator: creator.address,
+            share: creator.share,
+            sale_price_lamports: sale_price,
+            amount_lamports: amount,
+        });
+    }
+
+    Ok(paid)
+}
+
+/// Presale allowlist leaf: `blake3(0x00 || wallet || max_bps_le)`. The prefix byte keeps leaves
+/// and inner nodes in separate domains.
+pub fn presale_leaf(wallet: &Pubkey, max_bps: u16) -> [u8; 32] {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&[0x00]);
+    hasher.update(wallet.as_ref());
+    hasher.update(&max_bps.to_le_bytes());
+    *hasher.finalize().as_bytes()
+}
+
+/// Inner node: `blake3(0x01 || min(a, b) || max(a, b))`, so proofs carry no left/right flags.
+pub fn presale_node(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&[0x01]);
+    hasher.update(left);
+    hasher.update(right);
+    *hasher.finalize().as_bytes()
+}
+
+pub fn verify_presale_proof(
+    root: &[u8; 32],
+    wallet: &Pubkey,
+    max_bps: u16,
+    proof: &[[u8; 32]],
+) -> bool {
+    let computed = proof
+        .iter()
+        .fold(presale_leaf(wallet, max_bps), |node, sibling| presale_node(&node, sibling));
+    &computed == root
+}
+
+/// Allocates the `Contribution` PDA of `wallet` on `listing`, funding `rent` out of the escrow.
+/// Works whether or not the address already holds lamports.
+pub fn create_contribution_account<'info>(
+    escrow: &ListingEscrow<'_, 'info>,
+    account: &AccountInfo<'info>,
+    listing: &Pubkey,
+    wallet: &Pubkey,
+    bump: u8,
+    rent: u64,
+) -> Result<()> {
+    escrow.pay_out(account, rent)?;
+    let bump = [bump];
+    let seeds: &[&[u8]] = &[b"contribution", listing.as_ref(), wallet.as_ref(), &bump];
+    anchor_lang::system_program::allocate(
+        CpiContext::new_with_signer(
+            escrow.system_program.clone(),
+            anchor_lang::system_program::Allocate { account_to_allocate: account.clone() },
+            &[seeds],
+        ),
+        (8 + Contribution::INIT_SPACE) as u64,
+    )?;
+    anchor_lang::system_program::assign(
+        CpiContext::new_with_signer(
+            escrow.system_program.clone(),
+            anchor_lang::system_program::Assign { account_to_assign: account.clone() },
+            &[seeds],
+        ),
+        &crate::ID,
+    )
+}
+
+/// Pays a keeper out of the listing escrow: `from_tip` is drawn from the seller-posted tip pool,
+/// `from_fee` from custody fee lamports already held in escrow.
+pub fn pay_keeper_bounty<'info>(
+    listing: &mut Account<Listing>,
+    escrow: &ListingEscrow<'_, 'info>,
+    keeper: &AccountInfo<'info>,
+    action: KeeperAction,
+    from_tip: u64,
+    from_fee: u64,
+) -> Result<Option<KeeperBountyPaid>> {
+    let amount = from_tip.checked_add(from_fee).ok_or(ErrorCode::MathOverflow)?;
+    if amount == 0 {
+        return Ok(None);
+    }
+
+    listing.keeper_tip_lamports = listing.keeper_tip_lamports
+        .checked_sub(from_tip)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    escrow.pay_out(keeper, amount)?;
+
+    Ok(Some(KeeperBountyPaid {
+        listing: listing.key(),
+        keeper: keeper.key(),
+        action,
+        from_tip_lamports: from_tip,
+        from_fee_lamports: from_fee,
+    }))
+}
+
 // ============== ERROR CODES ==============
 
 #[error_code]
 pub enum ErrorCode {
     #[msg("Math overflow occurred")]
     MathOverflow,
-    #[msg("Invalid fee - must be 100 bps")]
+    #[msg("Invalid fee - the protocol fee must be 100 bps, a collection override at most 1000 bps")]
     InvalidFee,
     #[msg("Invalid amount")]
     InvalidAmount,
@@ -932,20 +3463,387 @@ pub enum ErrorCode {
     InsufficientListingLamports,
     #[msg("Invalid claim record")]
     InvalidClaimRecord,
-
+    #[msg("Mint is not a non-fungible token")]
+    InvalidNftMint,
+    #[msg("Invalid token metadata account")]
+    InvalidMetadata,
+    #[msg("Collection is not allowlisted")]
+    CollectionNotAllowed,
+    #[msg("Creator account missing or does not match listing creators")]
+    InvalidCreatorAccount,
+    #[msg("Invalid Dutch auction parameters")]
+    InvalidAuction,
+    #[msg("Listing is not a Dutch auction")]
+    NotAnAuction,
+    #[msg("Contributor is not on the presale allowlist")]
+    NotAllowlisted,
+    #[msg("Contribution exceeds the per-wallet cap")]
+    ExceedsWalletCap,
+    #[msg("Contribution is below the listing minimum")]
+    BelowMinContribution,
+    #[msg("Invalid remaining accounts")]
+    InvalidRemainingAccounts,
+    #[msg("Voting has not ended")]
+    VotingNotEnded,
+    #[msg("NFT already reclaimed")]
+    AlreadyReclaimed,
+    #[msg("Listing is already at the current version")]
+    ListingAlreadyMigrated,
+    #[msg("Allowlist account does not match the NFT's verified collection")]
+    InvalidAllowlistEntry,
+    #[msg("A seller who retains bps must contribute and execute in separate instructions")]
+    SellerMustExecuteSeparately,
+    #[msg("Account is already at the current version")]
+    AccountAlreadyMigrated,
 
 }
 
 #[account]
+#[derive(InitSpace)]
 pub struct ClaimRecord {
     pub registry: Pubkey,
     pub claimer: Pubkey,
     pub claimed_amount: u64,
     pub bump: u8,
+    pub version: u8,
 }
 
 impl ClaimRecord {
-    pub const LEN: usize = 32 + 32 + 8 + 1;
+    pub const VERSION: u8 = 1;
 }
 
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Rent-exempt minimum of the data-less escrow PDA.
+    const RENT_RESERVE: u64 = 890_880;
+
+    #[test]
+    fn full_refunds_drain_escrow_to_rent_reserve() {
+        let contributions = [1_020_000_000u64, 510_000_000, 255_000_000];
+        let keeper_tip = 30_000;
+        let mut ledger = LamportLedger::new(
+            RENT_RESERVE + keeper_tip + contributions.iter().sum::<u64>(),
+            RENT_RESERVE,
+        );
+
+        for amount in contributions {
+            ledger.debit(amount).unwrap();
+        }
+        ledger.debit(keeper_tip).unwrap();
+
+        assert_eq!(ledger.balance, RENT_RESERVE);
+        assert_eq!(ledger.escrowed(), 0);
+        assert!(ledger.debit(1).is_err());
+        assert_eq!(ledger.balance, RENT_RESERVE);
+    }
+
+    #[test]
+    fn execution_payouts_drain_escrow_to_rent_reserve() {
+        let (price, fee, royalties, bounty) = (2_000_000_000u64, 40_000_000u64, 100_000_000u64, 10_000u64);
+        let mut ledger = LamportLedger::new(RENT_RESERVE + price + fee, RENT_RESERVE);
+
+        ledger.debit(royalties).unwrap();
+        ledger.debit(price - royalties).unwrap();
+        ledger.debit(fee - bounty).unwrap();
+        assert!(ledger.debit(bounty + 1).is_err());
+        ledger.debit(bounty).unwrap();
+
+        assert_eq!(ledger.balance, RENT_RESERVE);
+        assert_eq!(ledger.escrowed(), 0);
+    }
+
+    #[test]
+    fn underfunded_escrow_cannot_pay_out_of_rent() {
+        let mut ledger = LamportLedger::new(RENT_RESERVE - 1, RENT_RESERVE);
+        assert_eq!(ledger.escrowed(), 0);
+        assert!(ledger.debit(1).is_err());
+        assert!(ledger.debit(0).is_ok());
+        assert_eq!(ledger.balance, RENT_RESERVE - 1);
+    }
+
+    // Serialized size of `account`, discriminator included.
+    fn serialized_len<T: AccountSerialize>(account: &T) -> usize {
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        data.len()
+    }
+
+    #[test]
+    fn largest_listing_fills_its_space() {
+        let mut state = listing(ListingStatus::Open);
+        state.collection = Some(Pubkey::new_unique());
+        state.creators = vec![ListingCreator { address: Pubkey::new_unique(), verified: true, share: 20 }; MAX_CREATORS];
+        state.auction = Some(DutchAuction {
+            start_price_lamports: 10_000_000_000,
+            floor_price_lamports: 1_000_000_000,
+            curve: DecayCurve::Stepwise { step_seconds: 600 },
+            duration: 86400,
+            start_time: DEADLINE - 86400,
+        });
+        state.presale = Some(Presale { merkle_root: [7; 32], end_time: DEADLINE - 3600 });
+        state.soft_close = Some(SoftClose { trigger_seconds: 300, extension_seconds: 300, hard_deadline: DEADLINE + 3600 });
+        assert_eq!(serialized_len(&state), 8 + Listing::INIT_SPACE);
+        // Version 0 listings were allocated 8 + 488 bytes, 7 short of the largest of them (which
+        // had no version byte yet).
+        assert_eq!(Listing::INIT_SPACE, 496);
+    }
+
+    #[test]
+    fn fixed_size_accounts_fill_their_space() {
+        let key = Pubkey::new_unique();
+        let config = Config {
+            authority: key,
+            custody_fee_bps: 100,
+            fee_vault: key,
+            bump: 255,
+            allowlist_enabled: true,
+            royalties_enabled: true,
+            keeper_bounty_lamports: 5_000,
+            version: Config::VERSION,
+        };
+        assert_eq!(serialized_len(&config), 8 + Config::INIT_SPACE);
+
+        let entry = CollectionAllowlist {
+            collection: key,
+            limits: CollectionLimits {
+                max_price_lamports: Some(1),
+                min_deadline_offset: Some(3600),
+                max_deadline_offset: Some(7200),
+                custody_fee_bps: Some(100),
+            },
+            bump: 255,
+            version: CollectionAllowlist::VERSION,
+        };
+        assert_eq!(serialized_len(&entry), 8 + CollectionAllowlist::INIT_SPACE);
+
+        let contribution = Contribution {
+            listing: key,
+            wallet: key,
+            bps: 10000,
+            principal_lamports: 1,
+            fee_lamports: 1,
+            refund_claimed: false,
+            bump: 255,
+            version: Contribution::VERSION,
+        };
+        assert_eq!(serialized_len(&contribution), 8 + Contribution::INIT_SPACE);
+
+        let proposal = Proposal {
+            listing: key,
+            proposer: key,
+            proposal_id: 1,
+            sale_price_lamports: 1,
+            vote_deadline: DEADLINE,
+            yes_bps: 5000,
+            no_bps: 5000,
+            status: ProposalStatus::Active,
+            bump: 255,
+            version: Proposal::VERSION,
+        };
+        assert_eq!(serialized_len(&proposal), 8 + Proposal::INIT_SPACE);
+
+        let vote = VoteRecord { proposal: key, voter: key, bps_voted: 10000, vote: Vote::No, bump: 255, version: VoteRecord::VERSION };
+        assert_eq!(serialized_len(&vote), 8 + VoteRecord::INIT_SPACE);
+
+        let registry = RewardRegistry {
+            listing: key,
+            reward_mint: key,
+            total_amount: 1,
+            claimed_amount: 1,
+            bump: 255,
+            version: RewardRegistry::VERSION,
+        };
+        assert_eq!(serialized_len(&registry), 8 + RewardRegistry::INIT_SPACE);
+
+        let claim = ClaimRecord { registry: key, claimer: key, claimed_amount: 1, bump: 255, version: ClaimRecord::VERSION };
+        assert_eq!(serialized_len(&claim), 8 + ClaimRecord::INIT_SPACE);
+    }
+
+    const DEADLINE: i64 = 1_000_000;
+    // The last second of the execution window: the listing is executable and refundable alike, so
+    // every guard holds and only the transition table decides.
+    const WINDOW_END: i64 = DEADLINE + 86400;
+
+    const STATUSES: [ListingStatus; 6] = [
+        ListingStatus::Open,
+        ListingStatus::Funded,
+        ListingStatus::Custodied,
+        ListingStatus::Expired,
+        ListingStatus::Refunded,
+        ListingStatus::Sold,
+    ];
+
+    // A fully sold, fully refunded listing funded at its deadline, with its NFT reclaimed.
+    fn listing(status: ListingStatus) -> Listing {
+        Listing {
+            price_lamports: 10_000_000_000,
+            custody_fee_lamports: 100_000_000,
+            total_raise_lamports: 10_100_000_000,
+            bps_sold: 10000,
+            deadline: DEADLINE,
+            funded_at: DEADLINE,
+            min_fill_bps: 5000,
+            contributor_count: 1,
+            refunded_bps: 10000,
+            refunded_lamports: 10_100_000_000,
+            nft_reclaimed: true,
+            ..Listing::fixture(status)
+        }
+    }
+
+    fn transition_to(status: &ListingStatus) -> Transition {
+        match status {
+            ListingStatus::Funded => Transition::Fund,
+            ListingStatus::Custodied => Transition::Execute(PurchaseExecuted {
+                listing: Pubkey::default(),
+                executor: Pubkey::default(),
+                bps_sold: 10000,
+                retained_bps: 0,
+                price_lamports: 10_000_000_000,
+                custody_fee_lamports: 100_000_000,
+                royalty_lamports: 0,
+                seller_proceeds_lamports: 10_000_000_000,
+                keeper_tip_refund_lamports: 0,
+                timestamp: WINDOW_END,
+            }),
+            ListingStatus::Expired => Transition::Expire,
+            ListingStatus::Refunded => Transition::Settle,
+            ListingStatus::Open | ListingStatus::Sold => unreachable!("no transition leads to this status"),
+        }
+    }
+
+    fn assert_rejected(result: Result<()>, code: ErrorCode) {
+        assert_eq!(result.unwrap_err(), anchor_lang::error::Error::from(code));
+    }
+
+    #[test]
+    fn only_the_lifecycle_transitions_are_legal() {
+        let legal = [
+            (ListingStatus::Open, ListingStatus::Funded),
+            (ListingStatus::Open, ListingStatus::Custodied),
+            (ListingStatus::Funded, ListingStatus::Custodied),
+            (ListingStatus::Open, ListingStatus::Expired),
+            (ListingStatus::Funded, ListingStatus::Expired),
+            (ListingStatus::Expired, ListingStatus::Refunded),
+        ];
+        for (i, from) in STATUSES.iter().enumerate() {
+            for (j, to) in STATUSES.iter().enumerate() {
+                let is_legal = legal.iter().any(|(a, b)| a == from && b == to);
+                assert_eq!(from.can_transition_to(to), is_legal, "transition {i} -> {j}");
+
+                let state = listing(from.clone());
+                let result = state.check_transition(to, WINDOW_END);
+                if is_legal {
+                    assert!(result.is_ok(), "transition {i} -> {j} should pass its guard");
+                } else {
+                    assert_rejected(result, ErrorCode::InvalidListingStatus);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn transitions_apply_only_when_legal() {
+        for (i, from) in STATUSES.iter().enumerate() {
+            for (j, to) in STATUSES.iter().enumerate() {
+                if matches!(to, ListingStatus::Open | ListingStatus::Sold) {
+                    continue;
+                }
+                let mut state = listing(from.clone());
+                let result = state.transition(Pubkey::new_unique(), transition_to(to), WINDOW_END);
+                if from.can_transition_to(to) {
+                    assert!(result.is_ok() && state.status == *to, "transition {i} -> {j} should apply");
+                } else {
+                    assert!(result.is_err() && state.status == *from, "transition {i} -> {j} should be rejected");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn transitions_emit_their_event() {
+        let key = Pubkey::new_unique();
+        let mut state = listing(ListingStatus::Open);
+        state.funded_at = 0;
+        match state.transition(key, Transition::Fund, DEADLINE - 100).unwrap() {
+            TransitionEvent::Funded(event) => assert_eq!((event.listing, event.timestamp), (key, DEADLINE - 100)),
+            _ => panic!("expected ListingFunded"),
+        }
+        assert_eq!(state.funded_at, DEADLINE - 100);
+
+        let mut state = listing(ListingStatus::Funded);
+        match state.transition(key, transition_to(&ListingStatus::Custodied), WINDOW_END).unwrap() {
+            TransitionEvent::Executed(event) => assert_eq!(event.seller_proceeds_lamports, 10_000_000_000),
+            _ => panic!("expected PurchaseExecuted"),
+        }
+
+        let mut state = listing(ListingStatus::Funded);
+        match state.transition(key, Transition::Expire, WINDOW_END).unwrap() {
+            TransitionEvent::Expired(event) => assert_eq!((event.listing, event.bps_sold), (key, 10000)),
+            _ => panic!("expected ListingExpired"),
+        }
+        match state.transition(key, Transition::Settle, WINDOW_END).unwrap() {
+            TransitionEvent::Refunded(event) => assert_eq!(event.refunded_lamports, 10_100_000_000),
+            _ => panic!("expected ListingRefunded"),
+        }
+    }
+
+    #[test]
+    fn guards_reject_legal_transitions_at_the_wrong_time() {
+        // Funding needs every bps for sale sold.
+        let mut open = listing(ListingStatus::Open);
+        open.bps_sold = 9999;
+        assert_rejected(open.check_transition(&ListingStatus::Funded, DEADLINE - 100), ErrorCode::InvalidListingStatus);
+
+        // Execution needs an open window.
+        let funded = listing(ListingStatus::Funded);
+        assert_rejected(funded.check_transition(&ListingStatus::Custodied, DEADLINE - 1), ErrorCode::ListingNotExpired);
+        assert_rejected(funded.check_transition(&ListingStatus::Custodied, WINDOW_END + 1), ErrorCode::ExecutionWindowExpired);
+        open.min_fill_bps = 0;
+        assert_rejected(open.check_transition(&ListingStatus::Custodied, WINDOW_END), ErrorCode::InvalidListingStatus);
+
+        // Expiry needs the deadline, or the execution window, to have passed.
+        assert_rejected(funded.check_transition(&ListingStatus::Expired, WINDOW_END - 1), ErrorCode::ExecutionWindowNotExpired);
+        let min_filled = listing(ListingStatus::Open);
+        assert_rejected(min_filled.check_transition(&ListingStatus::Expired, WINDOW_END - 1), ErrorCode::ExecutionWindowNotExpired);
+        assert_rejected(open.check_transition(&ListingStatus::Expired, DEADLINE - 1), ErrorCode::ListingNotExpired);
+        assert!(open.check_transition(&ListingStatus::Expired, DEADLINE).is_ok());
+        let mut unfunded = listing(ListingStatus::Funded);
+        unfunded.funded_at = 0;
+        assert_rejected(unfunded.check_transition(&ListingStatus::Expired, WINDOW_END), ErrorCode::ExecutionWindowNotExpired);
+
+        // Settling needs every refund paid and the NFT back with the seller.
+        let mut expired = listing(ListingStatus::Expired);
+        expired.refunded_bps = 9999;
+        assert_rejected(expired.check_transition(&ListingStatus::Refunded, WINDOW_END), ErrorCode::InvalidListingStatus);
+        expired.refunded_bps = 10000;
+        expired.nft_reclaimed = false;
+        assert_rejected(expired.check_transition(&ListingStatus::Refunded, WINDOW_END), ErrorCode::InvalidListingStatus);
+    }
+
+    #[test]
+    fn wind_down_expires_then_settles() {
+        let key = Pubkey::new_unique();
+        let mut state = listing(ListingStatus::Open);
+        state.min_fill_bps = 0;
+        state.nft_reclaimed = false;
+        let events = state.wind_down(key, DEADLINE).unwrap();
+        assert!(events.len() == 1 && matches!(events[0], TransitionEvent::Expired(_)));
+        assert!(state.status == ListingStatus::Expired);
+
+        // Already expired: only the settlement is left.
+        state.nft_reclaimed = true;
+        let events = state.wind_down(key, DEADLINE).unwrap();
+        assert!(events.len() == 1 && matches!(events[0], TransitionEvent::Refunded(_)));
+        assert!(state.status == ListingStatus::Refunded);
+
+        // Terminal statuses cannot be wound down.
+        assert_rejected(state.wind_down(key, DEADLINE).map(|_| ()), ErrorCode::InvalidListingStatus);
+        let mut custodied = listing(ListingStatus::Custodied);
+        assert_rejected(custodied.wind_down(key, WINDOW_END).map(|_| ()), ErrorCode::InvalidListingStatus);
+    }
+}
@@ -0,0 +1,245 @@
+//! Fee, share and reward arithmetic. Nothing here touches accounts or the runtime, so it can be
+//! tested off-chain; every function returns `None` on overflow and callers map that to
+//! `ErrorCode::MathOverflow`.
+//!
+//! Contributions are priced off running totals: a contribution pays what the raise owes after it
+//! minus what it owed before. Rounding never leaves dust behind, so the payments of a full raise
+//! add up to exactly `total_raise_lamports`.
+
+/// Basis points in a whole NFT.
+pub const BPS: u16 = 10000;
+
+/// `amount * numerator / denominator`, rounded down. The product is taken in u128, so only a
+/// quotient that does not fit u64 (or a zero denominator) fails.
+pub fn mul_div(amount: u64, numerator: u64, denominator: u64) -> Option<u64> {
+    let quotient = (amount as u128 * numerator as u128).checked_div(denominator as u128)?;
+    u64::try_from(quotient).ok()
+}
+
+/// `bps` of `amount`, rounded down.
+pub fn bps_share(amount: u64, bps: u16) -> Option<u64> {
+    mul_div(amount, bps as u64, BPS as u64)
+}
+
+/// What a new listing raises from contributors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Raise {
+    /// Price of the bps offered to contributors; goes to the seller on execution.
+    pub sale_price_lamports: u64,
+    /// Custody fee, charged on the sale price only.
+    pub custody_fee_lamports: u64,
+    pub total_raise_lamports: u64,
+}
+
+/// The raise of a listing priced at `price_lamports` for the whole NFT, of which the seller keeps
+/// `retained_bps`.
+pub fn raise(price_lamports: u64, retained_bps: u16, custody_fee_bps: u16) -> Option<Raise> {
+    let sale_price_lamports = bps_share(price_lamports, BPS.checked_sub(retained_bps)?)?;
+    let custody_fee_lamports = bps_share(sale_price_lamports, custody_fee_bps)?;
+    Some(Raise {
+        sale_price_lamports,
+        custody_fee_lamports,
+        total_raise_lamports: sale_price_lamports.checked_add(custody_fee_lamports)?,
+    })
+}
+
+/// Principal owed for the first `bps_sold` of a listing priced at `price_lamports`.
+pub fn principal_through(price_lamports: u64, bps_sold: u16) -> Option<u64> {
+    bps_share(price_lamports, bps_sold)
+}
+
+/// Custody fee owed for the first `bps_sold` of `bps_for_sale`. The fee accrues in proportion to
+/// principal rather than bps, so no contribution pays more fee than principal, and selling every
+/// bps for sale owes exactly `custody_fee_lamports`.
+pub fn fee_through(price_lamports: u64, custody_fee_lamports: u64, bps_for_sale: u16, bps_sold: u16) -> Option<u64> {
+    let sale_price = principal_through(price_lamports, bps_for_sale)?;
+    if sale_price == 0 {
+        return Some(0);
+    }
+    mul_div(custody_fee_lamports, principal_through(price_lamports, bps_sold)?, sale_price)
+}
+
+/// (principal, custody fee share) a contribution of `bps` pays once `bps_sold` are already sold.
+pub fn contribution_cost(
+    price_lamports: u64,
+    custody_fee_lamports: u64,
+    bps_for_sale: u16,
+    bps_sold: u16,
+    bps: u16,
+) -> Option<(u64, u64)> {
+    let sold_after = bps_sold.checked_add(bps)?;
+    let principal = principal_through(price_lamports, sold_after)?
+        .checked_sub(principal_through(price_lamports, bps_sold)?)?;
+    let fee = fee_through(price_lamports, custody_fee_lamports, bps_for_sale, sold_after)?
+        .checked_sub(fee_through(price_lamports, custody_fee_lamports, bps_for_sale, bps_sold)?)?;
+    Some((principal, fee))
+}
+
+/// What a holder of `bps` can claim from a reward registry: their share of everything ever
+/// deposited, less what they already claimed, capped by what is still unclaimed overall.
+pub fn claimable_reward(total_amount: u64, claimed_amount: u64, bps: u16, already_claimed: u64) -> Option<u64> {
+    let entitled = bps_share(total_amount, bps)?;
+    let remaining = total_amount.checked_sub(claimed_amount)?;
+    Some(entitled.saturating_sub(already_claimed).min(remaining))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MAX_CUSTODY_FEE_BPS;
+    use proptest::prelude::*;
+    use proptest::sample::Index;
+
+    const CASES: u32 = 2000;
+    const MAX_PRICE: u64 = u64::MAX / BPS as u64;
+
+    // Prices across every magnitude, with the edges over-represented.
+    fn price() -> impl Strategy<Value = u64> {
+        prop_oneof![
+            1 => Just(MAX_PRICE),
+            1 => 1..=BPS as u64,
+            1 => 1..=1_000_000_000u64,
+            1 => MAX_PRICE - 1_000_000..=MAX_PRICE,
+            2 => 1..=MAX_PRICE,
+        ]
+    }
+
+    fn retained_bps() -> impl Strategy<Value = u16> {
+        prop_oneof![Just(0), 0..BPS]
+    }
+
+    fn fee_bps() -> impl Strategy<Value = u16> {
+        prop_oneof![1 => Just(100), 1 => Just(MAX_CUSTODY_FEE_BPS), 2 => 0..=MAX_CUSTODY_FEE_BPS]
+    }
+
+    // Splits `total` bps into contributions: many tiny ones, a few large ones, or anything between.
+    fn split(total: u16) -> impl Strategy<Value = Vec<u16>> {
+        let largest = prop_oneof![Just(3), Just(total), 1..=total];
+        (largest, prop::collection::vec(any::<u16>(), 1..64)).prop_map(move |(largest, draws)| {
+            let mut parts = Vec::new();
+            let mut left = total;
+            for draw in draws.iter().cycle() {
+                if left == 0 {
+                    break;
+                }
+                let bps = 1 + draw % largest.min(left);
+                parts.push(bps);
+                left -= bps;
+            }
+            parts
+        })
+    }
+
+    // A listing's price, retained bps and fee rate, with its bps for sale split into contributions.
+    fn listing() -> impl Strategy<Value = (u64, u16, u16, Vec<u16>)> {
+        (price(), retained_bps(), fee_bps()).prop_flat_map(|(price, retained, fee_bps)| {
+            (Just(price), Just(retained), Just(fee_bps), split(BPS - retained))
+        })
+    }
+
+    // A reward deposit before a claim: usually none, sometimes small, now and then anything that fits.
+    fn deposit() -> impl Strategy<Value = Option<u64>> {
+        prop::option::weighted(0.3, prop_oneof![4 => 0..=1_000_000_000u64, 1 => any::<u64>()])
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(CASES))]
+
+        #[test]
+        fn fees_never_exceed_principal((price, retained, fee_bps, parts) in listing()) {
+            let raise = raise(price, retained, fee_bps).unwrap();
+            prop_assert!(raise.custody_fee_lamports <= raise.sale_price_lamports);
+
+            let bps_for_sale = BPS - retained;
+            let mut sold = 0;
+            for bps in parts {
+                let (principal, fee) = contribution_cost(price, raise.custody_fee_lamports, bps_for_sale, sold, bps).unwrap();
+                prop_assert!(fee <= principal, "{bps} bps after {sold}");
+                sold += bps;
+            }
+        }
+
+        #[test]
+        fn contributions_add_up_to_the_total_raise((price, retained, fee_bps, parts) in listing()) {
+            let raise = raise(price, retained, fee_bps).unwrap();
+            let bps_for_sale = BPS - retained;
+
+            let (mut sold, mut principals, mut fees) = (0, 0u64, 0u64);
+            for bps in parts {
+                let (principal, fee) = contribution_cost(price, raise.custody_fee_lamports, bps_for_sale, sold, bps).unwrap();
+                principals += principal;
+                fees += fee;
+                sold += bps;
+                // Running totals match what settlement takes out of escrow for a partial fill.
+                prop_assert_eq!(principals, principal_through(price, sold).unwrap());
+                prop_assert_eq!(fees, fee_through(price, raise.custody_fee_lamports, bps_for_sale, sold).unwrap());
+            }
+            prop_assert_eq!((principals, fees), (raise.sale_price_lamports, raise.custody_fee_lamports));
+            prop_assert_eq!(principals + fees, raise.total_raise_lamports);
+        }
+
+        #[test]
+        fn claimed_rewards_never_exceed_the_total(
+            holders in split(BPS),
+            steps in prop::collection::vec((deposit(), any::<Index>()), 1..20),
+        ) {
+            let mut claimed_by = vec![0u64; holders.len()];
+            let (mut total, mut claimed) = (0u64, 0u64);
+
+            for (deposit, holder) in steps {
+                if let Some(amount) = deposit {
+                    total += amount.min(u64::MAX - total);
+                }
+                let holder = holder.index(holders.len());
+                let amount = claimable_reward(total, claimed, holders[holder], claimed_by[holder]).unwrap();
+                claimed_by[holder] += amount;
+                claimed += amount;
+                prop_assert!(claimed <= total);
+                prop_assert!(claimed_by[holder] <= bps_share(total, holders[holder]).unwrap());
+            }
+
+            // Once everyone has claimed, all that is left is rounding dust of under one unit per holder.
+            for (holder, bps) in holders.iter().enumerate() {
+                let amount = claimable_reward(total, claimed, *bps, claimed_by[holder]).unwrap();
+                claimed_by[holder] += amount;
+                claimed += amount;
+            }
+            prop_assert!(claimed <= total && total - claimed < holders.len() as u64);
+        }
+
+        #[test]
+        fn no_overflow_up_to_the_maximum_price(
+            price in price(),
+            retained in retained_bps(),
+            fee_bps in 0..=BPS,
+            sold in any::<Index>(),
+            bps in any::<Index>(),
+        ) {
+            let raise = raise(price, retained, fee_bps).expect("raise fits");
+            let bps_for_sale = BPS - retained;
+            let sold = sold.index(bps_for_sale as usize + 1) as u16;
+            let bps = bps.index((bps_for_sale - sold) as usize + 1) as u16;
+            prop_assert!(contribution_cost(price, raise.custody_fee_lamports, bps_for_sale, sold, bps).is_some());
+            prop_assert!(fee_through(price, raise.custody_fee_lamports, bps_for_sale, sold).is_some());
+        }
+    }
+
+    #[test]
+    fn no_overflow_at_the_maximum_price() {
+        let raise = raise(MAX_PRICE, 0, BPS).unwrap();
+        assert_eq!(raise.total_raise_lamports, 2 * MAX_PRICE);
+        assert!(claimable_reward(u64::MAX, 0, BPS, 0) == Some(u64::MAX));
+    }
+
+    #[test]
+    fn rounding_dust_goes_to_the_contribution_that_crosses_it() {
+        // 10001 lamports over 10000 bps: one-bps contributions each owe 1 lamport, except the one
+        // that carries the running total past the extra lamport.
+        let raise = raise(10001, 0, 0).unwrap();
+        let costs: Vec<u64> = (0..BPS)
+            .map(|sold| contribution_cost(10001, raise.custody_fee_lamports, BPS, sold, 1).unwrap().0)
+            .collect();
+        assert_eq!(costs.iter().sum::<u64>(), 10001);
+        assert_eq!(costs.iter().filter(|cost| **cost == 2).count(), 1);
+    }
+}
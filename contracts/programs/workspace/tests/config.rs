@@ -0,0 +1,191 @@
+//! Protocol configuration and collection curation.
+
+mod harness;
+
+use anchor_lang::prelude::Pubkey;
+use fracvault_client::{instructions, pda};
+use harness::*;
+use workspace::{CollectionAllowlist, CollectionLimits, ErrorCode};
+
+fn limits() -> CollectionLimits {
+    CollectionLimits {
+        max_price_lamports: Some(20 * SOL),
+        min_deadline_offset: Some(7200),
+        max_deadline_offset: Some(172800),
+        custody_fee_bps: Some(250),
+    }
+}
+
+#[test]
+fn initialize_config_requires_the_protocol_fee() {
+    let mut env = Env::uninitialized();
+    let (authority, fee_vault) = (env.authority, env.fee_vault);
+    assert_error(env.send(instructions::initialize_config(&authority, &fee_vault, 99)), ErrorCode::InvalidFee);
+
+    let receipt = assert_ok(env.send(instructions::initialize_config(&authority, &fee_vault, 100)));
+    let event = receipt.event::<workspace::ConfigInitialized>();
+    assert_eq!((event.authority, event.fee_vault, event.custody_fee_bps), (authority, fee_vault, 100));
+
+    let config = env.config();
+    assert_eq!((config.authority, config.fee_vault, config.custody_fee_bps), (authority, fee_vault, 100));
+    assert!(!config.allowlist_enabled && !config.royalties_enabled);
+    assert_eq!(config.keeper_bounty_lamports, 0);
+}
+
+#[test]
+fn update_config_is_authority_only_and_partial() {
+    let mut env = Env::new();
+    let stranger = env.wallet();
+    assert_error(env.send(instructions::update_config(&stranger, Some(true), None, None)), ErrorCode::Unauthorized);
+
+    let authority = env.authority;
+    assert_ok(env.send(instructions::update_config(&authority, Some(true), Some(true), Some(5_000))));
+    let receipt = assert_ok(env.send(instructions::update_config(&authority, Some(false), None, None)));
+
+    let event = receipt.event::<workspace::ConfigUpdated>();
+    assert!(!event.allowlist_enabled && event.royalties_enabled);
+    assert_eq!(event.keeper_bounty_lamports, 5_000);
+    let config = env.config();
+    assert!(!config.allowlist_enabled && config.royalties_enabled);
+}
+
+#[test]
+fn collection_allowlist_entries_are_validated_and_authority_only() {
+    let mut env = Env::new();
+    let authority = env.authority;
+    let stranger = env.wallet();
+    let collection = Pubkey::new_unique();
+
+    assert_error(env.send(instructions::add_collection(&stranger, &collection, limits())), ErrorCode::Unauthorized);
+    let bad_price = CollectionLimits { max_price_lamports: Some(0), ..limits() };
+    assert_error(env.send(instructions::add_collection(&authority, &collection, bad_price)), ErrorCode::InvalidAmount);
+    let inverted = CollectionLimits { min_deadline_offset: Some(86400), max_deadline_offset: Some(3600), ..limits() };
+    assert_error(env.send(instructions::add_collection(&authority, &collection, inverted)), ErrorCode::InvalidDeadline);
+    let short = CollectionLimits { min_deadline_offset: Some(3599), ..limits() };
+    assert_error(env.send(instructions::add_collection(&authority, &collection, short)), ErrorCode::InvalidDeadline);
+    let fee = CollectionLimits { custody_fee_bps: Some(workspace::MAX_CUSTODY_FEE_BPS + 1), ..limits() };
+    assert_error(env.send(instructions::add_collection(&authority, &collection, fee)), ErrorCode::InvalidFee);
+
+    let receipt = assert_ok(env.send(instructions::add_collection(&authority, &collection, limits())));
+    assert!(receipt.event::<workspace::CollectionAllowlistUpdated>().limits == Some(limits()));
+    let entry: CollectionAllowlist = env.bank.get(&pda::allowlist(&collection).0);
+    assert!(entry.collection == collection && entry.limits == limits());
+
+    let relaxed = CollectionLimits::default();
+    assert_error(env.send(instructions::update_collection(&stranger, &collection, relaxed.clone())), ErrorCode::Unauthorized);
+    let bad_fee = CollectionLimits { custody_fee_bps: Some(5000), ..Default::default() };
+    assert_error(env.send(instructions::update_collection(&authority, &collection, bad_fee)), ErrorCode::InvalidFee);
+    assert_ok(env.send(instructions::update_collection(&authority, &collection, relaxed.clone())));
+    let entry: CollectionAllowlist = env.bank.get(&pda::allowlist(&collection).0);
+    assert!(entry.limits == relaxed);
+
+    assert_error(env.send(instructions::remove_collection(&stranger, &collection)), ErrorCode::Unauthorized);
+    let receipt = assert_ok(env.send(instructions::remove_collection(&authority, &collection)));
+    assert!(receipt.event::<workspace::CollectionAllowlistUpdated>().limits.is_none());
+    assert!(!env.bank.exists(&pda::allowlist(&collection).0));
+}
+
+#[test]
+fn allowlist_mode_gates_listings_by_verified_collection() {
+    let mut env = Env::new();
+    let authority = env.authority;
+    let collection = Pubkey::new_unique();
+    assert_ok(env.send(instructions::add_collection(&authority, &collection, limits())));
+    assert_ok(env.send(instructions::update_config(&authority, Some(true), None, None)));
+
+    let seller = env.wallet();
+    let verified = env.nft(&seller, NftSpec { collection: Some((collection, true)), ..Default::default() });
+    let unverified = env.nft(&seller, NftSpec { collection: Some((collection, false)), ..Default::default() });
+    let args = || ix_data_with(7200, 10 * SOL);
+
+    // Allowlist mode requires an entry, and the entry must match the verified collection.
+    let other = Pubkey::new_unique();
+    assert_ok(env.send(instructions::add_collection(&authority, &other, CollectionLimits::default())));
+    assert_error(env.send(instructions::create_listing(&seller, &verified, None, args())), ErrorCode::InvalidAllowlistEntry);
+    assert_error(
+        env.send(instructions::create_listing(&seller, &verified, Some(&other), args())),
+        ErrorCode::InvalidAllowlistEntry,
+    );
+    assert_error(
+        env.send(instructions::create_listing(&seller, &unverified, Some(&collection), args())),
+        ErrorCode::CollectionNotAllowed,
+    );
+
+    // The entry's limits apply.
+    assert_error(
+        env.send(instructions::create_listing(&seller, &verified, Some(&collection), ix_data_with(7200, 21 * SOL))),
+        ErrorCode::InvalidAmount,
+    );
+    assert_error(
+        env.send(instructions::create_listing(&seller, &verified, Some(&collection), ix_data_with(7199, 10 * SOL))),
+        ErrorCode::InvalidDeadline,
+    );
+    assert_error(
+        env.send(instructions::create_listing(&seller, &verified, Some(&collection), ix_data_with(172801, 10 * SOL))),
+        ErrorCode::InvalidDeadline,
+    );
+
+    assert_ok(env.send(instructions::create_listing(&seller, &verified, Some(&collection), args())));
+    let listing = env.listing(&pda::listing(&verified).0);
+    assert_eq!(listing.collection, Some(collection));
+    // The entry's fee override replaces the protocol fee: 2.5% of 10 SOL.
+    assert_eq!(listing.custody_fee_bps, 250);
+    assert_eq!(listing.custody_fee_lamports, SOL / 4);
+}
+
+#[test]
+fn allowlist_entries_apply_with_allowlist_mode_off() {
+    let mut env = Env::new();
+    let authority = env.authority;
+    let curated = Pubkey::new_unique();
+    assert_ok(env.send(instructions::add_collection(&authority, &curated, limits())));
+    let seller = env.wallet();
+
+    // The seller cannot skip the entry, or pick another collection's, to avoid its limits.
+    let nft = env.nft(&seller, NftSpec { collection: Some((curated, true)), ..Default::default() });
+    assert_error(env.send(instructions::create_listing(&seller, &nft, None, listing_args())), ErrorCode::InvalidAllowlistEntry);
+    let uncurated = Pubkey::new_unique();
+    assert_error(
+        env.send(instructions::create_listing(&seller, &nft, Some(&uncurated), listing_args())),
+        ErrorCode::InvalidAllowlistEntry,
+    );
+    assert_error(
+        env.send(instructions::create_listing(&seller, &nft, Some(&curated), ix_data_with(7200, 21 * SOL))),
+        ErrorCode::InvalidAmount,
+    );
+    assert_ok(env.send(instructions::create_listing(&seller, &nft, Some(&curated), ix_data_with(7200, 10 * SOL))));
+    assert_eq!(env.listing(&pda::listing(&nft).0).custody_fee_bps, 250);
+
+    // A verified collection without an entry lists at the protocol fee.
+    let nft = env.nft(&seller, NftSpec { collection: Some((uncurated, true)), ..Default::default() });
+    assert_ok(env.send(instructions::create_listing(&seller, &nft, Some(&uncurated), listing_args())));
+    assert_eq!(env.listing(&pda::listing(&nft).0).custody_fee_bps, 100);
+}
+
+#[test]
+fn royalty_mode_is_snapshotted_at_listing_time() {
+    let mut env = Env::new();
+    let authority = env.authority;
+    let seller = env.wallet();
+    let creator = mpl_token_metadata::types::Creator { address: Pubkey::new_unique(), verified: true, share: 100 };
+    let spec = NftSpec { creators: vec![creator.clone()], seller_fee_basis_points: 500, ..Default::default() };
+
+    let before = env.nft(&seller, spec.clone());
+    assert_ok(env.send(instructions::create_listing(&seller, &before, None, listing_args())));
+    assert_ok(env.send(instructions::update_config(&authority, None, Some(true), None)));
+    let after = env.nft(&seller, spec);
+    assert_ok(env.send(instructions::create_listing(&seller, &after, None, listing_args())));
+
+    assert_eq!(env.listing(&pda::listing(&before).0).royalty_bps, 0);
+    let listing = env.listing(&pda::listing(&after).0);
+    assert_eq!(listing.royalty_bps, 500);
+    assert!(listing.creators.len() == 1 && listing.creators[0].address == creator.address);
+
+    // Metadata claiming more than 100% royalties is rejected while royalty mode is on.
+    let greedy = env.nft(&seller, NftSpec { seller_fee_basis_points: 10001, ..Default::default() });
+    assert_error(env.send(instructions::create_listing(&seller, &greedy, None, listing_args())), ErrorCode::InvalidMetadata);
+}
+
+fn ix_data_with(deadline_offset: i64, price_lamports: u64) -> workspace::CreateListingArgs {
+    workspace::CreateListingArgs { deadline_offset, price_lamports, ..listing_args() }
+}
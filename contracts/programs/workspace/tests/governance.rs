@@ -0,0 +1,298 @@
+//! Custodied listings: sale proposals, voting, reward distribution and read-only views.
+
+mod harness;
+
+use anchor_lang::prelude::Pubkey;
+use fracvault_client::{instructions, pda};
+use harness::*;
+use workspace::{
+    ClaimRecord, ContributionQuote, ErrorCode, ListingStatus, ListingSummary, Proposal, ProposalStatus, RefundQuote,
+    RewardQuote, RewardRegistry, Vote,
+};
+
+fn propose(env: &mut Env, listing: &Pubkey, proposer: &Pubkey, price: u64, offset: i64) -> Result<Receipt, TxError> {
+    let state = env.listing(listing);
+    env.send(instructions::create_proposal(listing, &state, proposer, price, offset))
+}
+
+fn vote(env: &mut Env, listing: &Pubkey, id: u32, voter: &Pubkey, vote: Vote) -> Result<Receipt, TxError> {
+    env.send(instructions::cast_vote(listing, id, voter, vote))
+}
+
+fn proposal(env: &Env, listing: &Pubkey, id: u32) -> Proposal {
+    env.bank.get(&pda::proposal(listing, id).0)
+}
+
+#[test]
+fn proposals_need_a_custodied_listing_and_a_position() {
+    let mut env = Env::new();
+    let listed = env.list(listing_args());
+    let wallets = env.fund(&listed.key, &[4000, 6000]);
+    assert_error(propose(&mut env, &listed.key, &wallets[0], SOL, 86400), ErrorCode::InvalidListingStatus);
+
+    let executor = env.wallet();
+    assert_ok(env.execute(&listed.key, &executor));
+    assert_error(propose(&mut env, &listed.key, &wallets[0], 0, 86400), ErrorCode::InvalidAmount);
+    assert_error(propose(&mut env, &listed.key, &wallets[0], SOL, 3599), ErrorCode::InvalidDeadline);
+    assert_error(propose(&mut env, &listed.key, &wallets[0], SOL, 604801), ErrorCode::InvalidDeadline);
+
+    let receipt = assert_ok(propose(&mut env, &listed.key, &wallets[0], 20 * SOL, 86400));
+    let created = receipt.event::<workspace::ProposalCreated>();
+    assert_eq!((created.proposal_id, created.vote_deadline), (0, START_TIME + 86400));
+    assert_ok(propose(&mut env, &listed.key, &wallets[1], 30 * SOL, 86400));
+    assert_eq!(env.listing(&listed.key).proposal_count, 2);
+    assert_eq!(proposal(&env, &listed.key, 1).sale_price_lamports, 30 * SOL);
+
+    // Defensive: positions without weight, or recorded for another wallet.
+    let address = pda::contribution(&listed.key, &wallets[0]).0;
+    let mut position = env.contribution(&listed.key, &wallets[0]);
+    position.bps = 0;
+    env.write(&address, &position);
+    assert_error(propose(&mut env, &listed.key, &wallets[0], SOL, 86400), ErrorCode::NoVotingPower);
+    position.bps = 4000;
+    position.wallet = wallets[1];
+    env.write(&address, &position);
+    assert_error(propose(&mut env, &listed.key, &wallets[0], SOL, 86400), ErrorCode::Unauthorized);
+}
+
+#[test]
+fn a_majority_of_yes_votes_approves_a_proposal() {
+    let mut env = Env::new();
+    let (listed, wallets) = env.custodied(&[3000, 2000, 2000, 3000]);
+    assert_ok(propose(&mut env, &listed.key, &wallets[0], 20 * SOL, 86400));
+
+    let receipt = assert_ok(vote(&mut env, &listed.key, 0, &wallets[0], Vote::Yes));
+    assert_eq!(receipt.event::<workspace::VoteCast>().yes_bps, 3000);
+    assert_ok(vote(&mut env, &listed.key, 0, &wallets[1], Vote::No));
+    // Exactly half is not a majority.
+    let receipt = assert_ok(vote(&mut env, &listed.key, 0, &wallets[2], Vote::Yes));
+    assert!(!receipt.emitted::<workspace::ProposalResolved>());
+
+    let receipt = assert_ok(vote(&mut env, &listed.key, 0, &wallets[3], Vote::Yes));
+    let resolved = receipt.event::<workspace::ProposalResolved>();
+    assert!(resolved.status == ProposalStatus::Approved);
+    assert_eq!((resolved.yes_bps, resolved.no_bps), (8000, 2000));
+
+    assert!(proposal(&env, &listed.key, 0).status == ProposalStatus::Approved);
+
+    // Approved proposals are settled; the deadline passing does not reject them.
+    env.bank.warp(86400);
+    let keeper = env.wallet();
+    assert_error(env.send(instructions::finalize_proposal(&listed.key, 0, &keeper)), ErrorCode::InvalidProposalStatus);
+}
+
+#[test]
+fn votes_close_sixty_seconds_before_the_vote_deadline() {
+    let mut env = Env::new();
+    let (listed, wallets) = env.custodied(&[2000, 2000, 6000]);
+    assert_ok(propose(&mut env, &listed.key, &wallets[0], 20 * SOL, 86400));
+    let deadline = proposal(&env, &listed.key, 0).vote_deadline;
+
+    env.bank.warp_to(deadline - 61);
+    assert_ok(vote(&mut env, &listed.key, 0, &wallets[0], Vote::No));
+    env.bank.warp_to(deadline - 60);
+    assert_error(vote(&mut env, &listed.key, 0, &wallets[1], Vote::Yes), ErrorCode::VotingEnded);
+
+    // Defensive: a voter position without weight.
+    let address = pda::contribution(&listed.key, &wallets[2]).0;
+    let mut position = env.contribution(&listed.key, &wallets[2]);
+    position.bps = 0;
+    env.write(&address, &position);
+    env.bank.warp_to(deadline - 61);
+    assert_error(vote(&mut env, &listed.key, 0, &wallets[2], Vote::Yes), ErrorCode::NoVotingPower);
+}
+
+#[test]
+fn voting_on_an_approved_proposal_is_rejected() {
+    let mut env = Env::new();
+    let (listed, wallets) = env.custodied(&[6000, 4000]);
+    assert_ok(propose(&mut env, &listed.key, &wallets[0], 20 * SOL, 86400));
+    assert_ok(vote(&mut env, &listed.key, 0, &wallets[0], Vote::Yes));
+    assert_error(vote(&mut env, &listed.key, 0, &wallets[1], Vote::No), ErrorCode::InvalidProposalStatus);
+}
+
+#[test]
+fn finalize_proposal_rejects_expired_proposals_and_pays_the_keeper() {
+    let mut env = Env::new();
+    let authority = env.authority;
+    assert_ok(env.send(instructions::update_config(&authority, None, None, Some(250_000))));
+    let listed = env.list(workspace::CreateListingArgs { keeper_tip_lamports: 1_000_000, ..listing_args() });
+    let wallets = env.fund(&listed.key, &[10000]);
+    let executor = env.wallet();
+    let seller_before = env.bank.lamports(&listed.seller);
+    let executed = assert_ok(env.execute(&listed.key, &executor)).event::<workspace::PurchaseExecuted>();
+    // The executor takes one bounty, one is held for finalizing, and the rest goes back to the seller.
+    assert_eq!(executed.keeper_tip_refund_lamports, 500_000);
+    assert_eq!(env.bank.lamports(&listed.seller) - seller_before, executed.seller_proceeds_lamports + 500_000);
+    assert_eq!(env.listing(&listed.key).keeper_tip_lamports, 250_000);
+    assert_ok(propose(&mut env, &listed.key, &wallets[0], 20 * SOL, 3600));
+    let deadline = proposal(&env, &listed.key, 0).vote_deadline;
+    let keeper = env.wallet();
+
+    env.bank.warp_to(deadline - 1);
+    assert_error(env.send(instructions::finalize_proposal(&listed.key, 0, &keeper)), ErrorCode::VotingNotEnded);
+
+    env.bank.warp_to(deadline);
+    let before = env.bank.lamports(&keeper);
+    let receipt = assert_ok(env.send(instructions::finalize_proposal(&listed.key, 0, &keeper)));
+    assert!(receipt.event::<workspace::ProposalResolved>().status == ProposalStatus::Rejected);
+    assert_eq!(env.bank.lamports(&keeper) - before, 250_000);
+    assert_eq!(env.listing(&listed.key).keeper_tip_lamports, 0);
+
+    assert_error(env.send(instructions::finalize_proposal(&listed.key, 0, &keeper)), ErrorCode::InvalidProposalStatus);
+
+    // Proposals with short vote windows finalize for free once the held bounty is spent.
+    assert_ok(propose(&mut env, &listed.key, &wallets[0], 20 * SOL, 3600));
+    env.bank.warp_to(proposal(&env, &listed.key, 1).vote_deadline);
+    let before = env.bank.lamports(&keeper);
+    let receipt = assert_ok(env.send(instructions::finalize_proposal(&listed.key, 1, &keeper)));
+    assert!(!receipt.emitted::<workspace::KeeperBountyPaid>());
+    assert_eq!(env.bank.lamports(&keeper), before);
+}
+
+#[test]
+fn proposals_are_bound_to_their_listing() {
+    let mut env = Env::new();
+    let (listed, wallets) = env.custodied(&[10000]);
+    assert_ok(propose(&mut env, &listed.key, &wallets[0], 20 * SOL, 86400));
+
+    // Defensive: a proposal account recording another listing.
+    let address = pda::proposal(&listed.key, 0).0;
+    let mut state = proposal(&env, &listed.key, 0);
+    state.listing = Pubkey::new_unique();
+    env.write(&address, &state);
+    assert_error(vote(&mut env, &listed.key, 0, &wallets[0], Vote::Yes), ErrorCode::InvalidProposal);
+    env.bank.warp(86400);
+    let keeper = env.wallet();
+    assert_error(env.send(instructions::finalize_proposal(&listed.key, 0, &keeper)), ErrorCode::InvalidProposal);
+}
+
+#[test]
+fn rewards_are_claimed_pro_rata() {
+    let mut env = Env::new();
+    let (listed, wallets) = env.custodied(&[2500, 7500]);
+    let depositor = env.wallet();
+    let mint = env.reward_mint(&depositor, 10_000);
+    let register = |env: &mut Env, amount| env.send(instructions::register_reward(&listed.key, &mint, &depositor, amount));
+    let claim = |env: &mut Env, wallet: &Pubkey| env.send(instructions::claim_reward(&listed.key, &mint, wallet));
+
+    assert_error(register(&mut env, 0), ErrorCode::InvalidAmount);
+    let registered = assert_ok(register(&mut env, 4_000)).event::<workspace::RewardRegistered>();
+    assert_eq!(registered.total_amount, 4_000);
+    let registry = pda::reward(&listed.key, &mint).0;
+    assert_eq!(env.bank.token_account(&pda::associated_token(&registry, &mint)).amount, 4_000);
+
+    let claimed = assert_ok(claim(&mut env, &wallets[0])).event::<workspace::RewardClaimed>();
+    assert_eq!((claimed.amount, claimed.claimed_total), (1_000, 1_000));
+    assert_eq!(env.bank.token_account(&pda::associated_token(&wallets[0], &mint)).amount, 1_000);
+    assert_error(claim(&mut env, &wallets[0]), ErrorCode::NothingToClaim);
+
+    // Later deposits top up the same registry; holders claim the difference.
+    assert_ok(register(&mut env, 4_000));
+    let quote: RewardQuote = env.bank.view(instructions::quote_claimable_reward(&listed.key, &mint, &wallets[0])).unwrap();
+    assert!(quote == RewardQuote { bps: 2500, total_amount: 8_000, claimed_amount: 1_000, claimable_amount: 1_000 });
+    assert_eq!(assert_ok(claim(&mut env, &wallets[0])).event::<workspace::RewardClaimed>().claimed_total, 2_000);
+    assert_eq!(assert_ok(claim(&mut env, &wallets[1])).event::<workspace::RewardClaimed>().amount, 6_000);
+
+    let state: RewardRegistry = env.bank.get(&registry);
+    assert_eq!((state.total_amount, state.claimed_amount), (8_000, 8_000));
+    let record: ClaimRecord = env.bank.get(&pda::claim(&registry, &wallets[1]).0);
+    assert_eq!((record.claimer, record.claimed_amount), (wallets[1], 6_000));
+}
+
+#[test]
+fn register_reward_checks_listing_status_and_depositor_account() {
+    let mut env = Env::new();
+    let listed = env.list(listing_args());
+    let depositor = env.wallet();
+    let mint = env.reward_mint(&depositor, 1_000);
+    let ix = instructions::register_reward(&listed.key, &mint, &depositor, 100);
+    assert_error(env.send(ix.clone()), ErrorCode::InvalidListingStatus);
+
+    env.fund(&listed.key, &[10000]);
+    let executor = env.wallet();
+    assert_ok(env.execute(&listed.key, &executor));
+    let depositor_account = pda::associated_token(&depositor, &mint);
+
+    let other_mint = env.reward_mint(&depositor, 1_000);
+    let mut wrong_mint = ix.clone();
+    swap(&mut wrong_mint, &depositor_account, pda::associated_token(&depositor, &other_mint));
+    assert_error(env.send(wrong_mint), ErrorCode::InvalidMint);
+
+    let other = env.wallet();
+    let foreign = env.token_account(&other, &mint, 1_000);
+    let mut wrong_owner = ix.clone();
+    swap(&mut wrong_owner, &depositor_account, foreign);
+    assert_error(env.send(wrong_owner), ErrorCode::Unauthorized);
+
+    assert_ok(env.send(ix));
+}
+
+#[test]
+fn claim_reward_rejects_inconsistent_accounts() {
+    let mut env = Env::new();
+    let (listed, wallets) = env.custodied(&[5000, 5000]);
+    let depositor = env.wallet();
+    let mint = env.reward_mint(&depositor, 1_000);
+    assert_ok(env.send(instructions::register_reward(&listed.key, &mint, &depositor, 1_000)));
+    let claim = |env: &mut Env, wallet: &Pubkey| env.send(instructions::claim_reward(&listed.key, &mint, wallet));
+    assert_ok(claim(&mut env, &wallets[0]));
+
+    // Defensive paths, reached by corrupting accounts the program itself wrote.
+    let registry = pda::reward(&listed.key, &mint).0;
+    let record_address = pda::claim(&registry, &wallets[0]).0;
+    let record: ClaimRecord = env.bank.get(&record_address);
+    env.write(&record_address, &ClaimRecord { claimer: Pubkey::new_unique(), ..record.clone() });
+    assert_error(claim(&mut env, &wallets[0]), ErrorCode::InvalidClaimRecord);
+    env.write(&record_address, &ClaimRecord { registry: Pubkey::new_unique(), ..record.clone() });
+    assert_error(claim(&mut env, &wallets[0]), ErrorCode::InvalidClaimRecord);
+
+    let position_address = pda::contribution(&listed.key, &wallets[1]).0;
+    let position = env.contribution(&listed.key, &wallets[1]);
+    env.write(&position_address, &workspace::Contribution { bps: 0, ..position.clone() });
+    assert_error(claim(&mut env, &wallets[1]), ErrorCode::NoVotingPower);
+    env.write(&position_address, &position);
+
+    let state: RewardRegistry = env.bank.get(&registry);
+    env.write(&registry, &RewardRegistry { listing: Pubkey::new_unique(), ..state.clone() });
+    assert_error(claim(&mut env, &wallets[1]), ErrorCode::InvalidRewardRegistry);
+    env.write(&registry, &state);
+
+    env.edit_listing(&listed.key, |listing| listing.status = ListingStatus::Expired);
+    assert_error(claim(&mut env, &wallets[1]), ErrorCode::InvalidListingStatus);
+}
+
+#[test]
+fn views_return_what_the_instructions_would_do() {
+    let mut env = Env::new();
+    let listed = env.list(listing_args());
+    let wallet = env.wallet();
+
+    let quote: ContributionQuote = env.bank.view(instructions::quote_contribution(&listed.key, &wallet, 2500, None)).unwrap();
+    let made = assert_ok(env.contribute(&listed.key, &wallet, 2500)).event::<workspace::ContributionMade>();
+    assert_eq!((quote.principal_lamports, quote.fee_lamports), (made.principal_lamports, made.fee_lamports));
+    assert_eq!((quote.total_lamports, quote.remaining_bps), (2_525_000_000, 10000));
+
+    let error = env.bank.view::<ContributionQuote>(instructions::quote_contribution(&listed.key, &wallet, 7501, None)).err().expect("view fails");
+    assert_eq!(error.error, anchor_lang::prelude::ProgramError::Custom(ErrorCode::ExceedsAvailable.into()));
+
+    let summary: ListingSummary = env.bank.view(instructions::listing_summary(&listed.key)).unwrap();
+    assert!(summary.status == ListingStatus::Open && !summary.refundable);
+    assert_eq!((summary.bps_sold, summary.remaining_bps, summary.contributor_count), (2500, 7500, 1));
+    assert_eq!(summary.escrowed_lamports, 2_525_000_000);
+
+    let quote: RefundQuote = env.bank.view(instructions::quote_refund(&listed.key, &wallet)).unwrap();
+    assert!(!quote.refundable && quote.total_lamports == 2_525_000_000);
+    env.bank.warp_to(summary.deadline);
+    let quote: RefundQuote = env.bank.view(instructions::quote_refund(&listed.key, &wallet)).unwrap();
+    assert!(quote.refundable);
+    let refunded = assert_ok(env.send(instructions::process_refund(&listed.key, &wallet))).event::<workspace::RefundProcessed>();
+    assert_eq!(refunded.amount_lamports, quote.total_lamports);
+
+    // Views never commit: the listing is still Expired with nothing left in escrow.
+    let summary: ListingSummary = env.bank.view(instructions::listing_summary(&listed.key)).unwrap();
+    assert!(summary.status == ListingStatus::Expired && summary.refundable);
+    assert_eq!((summary.escrowed_lamports, summary.refunded_lamports), (0, 2_525_000_000));
+    let error = env.bank.view::<ContributionQuote>(instructions::quote_contribution(&listed.key, &wallet, 1, None)).err().expect("view fails");
+    assert_eq!(error.error, anchor_lang::prelude::ProgramError::Custom(ErrorCode::InvalidListingStatus.into()));
+}
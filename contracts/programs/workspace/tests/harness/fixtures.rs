@@ -0,0 +1,285 @@
+//! Program fixtures on top of [`Bank`]: an initialized config, funded wallets, NFTs with Token
+//! Metadata accounts, fungible reward mints and listings driven through the client's builders.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::system_program;
+use anchor_spl::token::spl_token;
+use borsh::BorshSerialize;
+use fracvault_client::{instructions, pda};
+use mpl_token_metadata::accounts::{MasterEdition, Metadata};
+use mpl_token_metadata::types::{Collection, Creator, Key};
+use spl_token::state::{Account as TokenAccount, AccountState, Mint};
+use workspace::{CreateListingArgs, Listing};
+
+use super::runtime::{assert_ok, Account, Bank, Receipt, TxError};
+
+pub const SOL: u64 = 1_000_000_000;
+pub const PRICE: u64 = 10 * SOL;
+pub const DEADLINE_OFFSET: i64 = 86400;
+pub const EXECUTION_WINDOW: i64 = 86400;
+
+/// Who may still mint the NFT.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum MintAuthority {
+    Revoked,
+    /// The Metaplex master edition PDA, as for a regular 1/1.
+    MasterEdition,
+    Other(Pubkey),
+}
+
+#[derive(Clone)]
+pub struct NftSpec {
+    pub mint_authority: MintAuthority,
+    pub supply: u64,
+    pub decimals: u8,
+    /// (collection mint, verified)
+    pub collection: Option<(Pubkey, bool)>,
+    pub creators: Vec<Creator>,
+    pub seller_fee_basis_points: u16,
+    /// Write the metadata account (owned by Token Metadata) at all.
+    pub metadata: bool,
+}
+
+impl Default for NftSpec {
+    fn default() -> Self {
+        Self {
+            mint_authority: MintAuthority::Revoked,
+            supply: 1,
+            decimals: 0,
+            collection: None,
+            creators: Vec::new(),
+            seller_fee_basis_points: 0,
+            metadata: true,
+        }
+    }
+}
+
+/// Arguments for a plain 10 SOL listing open for a day: full fill, no auction, no presale.
+pub fn listing_args() -> CreateListingArgs {
+    CreateListingArgs {
+        price_lamports: PRICE,
+        deadline_offset: DEADLINE_OFFSET,
+        retained_bps: 0,
+        min_fill_bps: 0,
+        auction: None,
+        presale: None,
+        max_bps_per_wallet: 0,
+        min_bps_per_contribution: 0,
+        soft_close: None,
+        keeper_tip_lamports: 0,
+    }
+}
+
+/// Points the account meta for `from` at `to`, keeping its signer and writable flags.
+pub fn swap(ix: &mut Instruction, from: &Pubkey, to: Pubkey) {
+    let meta = ix.accounts.iter_mut().find(|meta| meta.pubkey == *from).expect("account in instruction");
+    meta.pubkey = to;
+}
+
+pub struct Env {
+    pub bank: Bank,
+    pub authority: Pubkey,
+    pub fee_vault: Pubkey,
+}
+
+impl Env {
+    /// A bank with the config initialized at the protocol's 1% custody fee.
+    pub fn new() -> Self {
+        let mut env = Self::uninitialized();
+        let ix = instructions::initialize_config(&env.authority, &env.fee_vault, 100);
+        assert_ok(env.send(ix));
+        env
+    }
+
+    pub fn uninitialized() -> Self {
+        let mut bank = Bank::new();
+        let authority = Pubkey::new_unique();
+        bank.airdrop(&authority, 100 * SOL);
+        let fee_vault = Pubkey::new_unique();
+        bank.airdrop(&fee_vault, SOL);
+        Self { bank, authority, fee_vault }
+    }
+
+    pub fn send(&mut self, instruction: Instruction) -> std::result::Result<Receipt, TxError> {
+        self.bank.send(&[instruction])
+    }
+
+    pub fn now(&self) -> i64 {
+        self.bank.now()
+    }
+
+    /// A fresh system account holding 1000 SOL.
+    pub fn wallet(&mut self) -> Pubkey {
+        let wallet = Pubkey::new_unique();
+        self.bank.airdrop(&wallet, 1000 * SOL);
+        wallet
+    }
+
+    pub fn config(&self) -> workspace::Config {
+        self.bank.get(&pda::config().0)
+    }
+
+    pub fn listing(&self, listing: &Pubkey) -> Listing {
+        self.bank.get(listing)
+    }
+
+    pub fn contribution(&self, listing: &Pubkey, wallet: &Pubkey) -> workspace::Contribution {
+        self.bank.get(&pda::contribution(listing, wallet).0)
+    }
+
+    /// Lamports held by a listing's escrow above its rent-exempt reserve.
+    pub fn escrowed(&self, listing: &Pubkey) -> u64 {
+        self.bank.lamports(&pda::escrow(listing).0) - Rent::default().minimum_balance(0)
+    }
+
+    fn rent_exempt(&mut self, address: Pubkey, owner: Pubkey, data: Vec<u8>) {
+        let lamports = Rent::default().minimum_balance(data.len());
+        self.bank.set_account(address, Account { lamports, owner, data, executable: false });
+    }
+
+    fn mint_account(&mut self, mint: Pubkey, authority: COption<Pubkey>, supply: u64, decimals: u8) {
+        let mut data = vec![0; Mint::LEN];
+        let state = Mint { mint_authority: authority, supply, decimals, is_initialized: true, freeze_authority: COption::None };
+        Mint::pack(state, &mut data).unwrap();
+        self.rent_exempt(mint, spl_token::ID, data);
+    }
+
+    /// Writes `owner`'s associated token account for `mint` holding `amount`.
+    pub fn token_account(&mut self, owner: &Pubkey, mint: &Pubkey, amount: u64) -> Pubkey {
+        let address = pda::associated_token(owner, mint);
+        let mut data = vec![0; TokenAccount::LEN];
+        let state = TokenAccount { mint: *mint, owner: *owner, amount, state: AccountState::Initialized, ..TokenAccount::default() };
+        TokenAccount::pack(state, &mut data).unwrap();
+        self.rent_exempt(address, spl_token::ID, data);
+        address
+    }
+
+    /// Creates a mint described by `spec`; `owner` holds one unit in its associated token account.
+    pub fn nft(&mut self, owner: &Pubkey, spec: NftSpec) -> Pubkey {
+        let mint = Pubkey::new_unique();
+        let master_edition = pda::master_edition(&mint).0;
+        let authority = match spec.mint_authority {
+            MintAuthority::Revoked => COption::None,
+            MintAuthority::MasterEdition => COption::Some(master_edition),
+            MintAuthority::Other(authority) => COption::Some(authority),
+        };
+        self.mint_account(mint, authority, spec.supply, spec.decimals);
+        self.token_account(owner, &mint, 1);
+
+        if spec.mint_authority == MintAuthority::MasterEdition {
+            let edition = MasterEdition { key: Key::MasterEditionV2, supply: 0, max_supply: Some(0) };
+            self.rent_exempt(master_edition, mpl_token_metadata::ID, edition.try_to_vec().unwrap());
+        }
+        if spec.metadata {
+            let metadata = Metadata {
+                key: Key::MetadataV1,
+                update_authority: *owner,
+                mint,
+                name: "FracVault Test".into(),
+                symbol: "FVT".into(),
+                uri: "https://example.com/nft.json".into(),
+                seller_fee_basis_points: spec.seller_fee_basis_points,
+                creators: (!spec.creators.is_empty()).then_some(spec.creators),
+                primary_sale_happened: true,
+                is_mutable: true,
+                edition_nonce: None,
+                token_standard: None,
+                collection: spec.collection.map(|(key, verified)| Collection { verified, key }),
+                uses: None,
+                collection_details: None,
+                programmable_config: None,
+            };
+            self.rent_exempt(pda::metadata(&mint).0, mpl_token_metadata::ID, metadata.try_to_vec().unwrap());
+        }
+        mint
+    }
+
+    /// A fungible mint with `amount` tokens in `holder`'s associated token account.
+    pub fn reward_mint(&mut self, holder: &Pubkey, amount: u64) -> Pubkey {
+        let mint = Pubkey::new_unique();
+        self.mint_account(mint, COption::Some(*holder), amount, 6);
+        self.token_account(holder, &mint, amount);
+        mint
+    }
+
+    /// Lists a fresh NFT for a fresh seller.
+    pub fn list(&mut self, args: CreateListingArgs) -> Listed {
+        let seller = self.wallet();
+        let nft_mint = self.nft(&seller, NftSpec::default());
+        let listing = assert_ok(self.send(instructions::create_listing(&seller, &nft_mint, None, args)));
+        let key = pda::listing(&nft_mint).0;
+        assert!(listing.emitted::<workspace::ListingCreated>());
+        Listed { key, seller, nft_mint }
+    }
+
+    pub fn contribute(&mut self, listing: &Pubkey, wallet: &Pubkey, bps: u16) -> std::result::Result<Receipt, TxError> {
+        self.send(instructions::contribute(listing, wallet, bps, None))
+    }
+
+    /// Funds `listing` in full from a new contributor per entry of `split`, returning them in order.
+    pub fn fund(&mut self, listing: &Pubkey, split: &[u16]) -> Vec<Pubkey> {
+        split
+            .iter()
+            .map(|bps| {
+                let wallet = self.wallet();
+                assert_ok(self.contribute(listing, &wallet, *bps));
+                wallet
+            })
+            .collect()
+    }
+
+    pub fn execute(&mut self, listing: &Pubkey, executor: &Pubkey) -> std::result::Result<Receipt, TxError> {
+        let state = self.listing(listing);
+        let fee_vault = self.fee_vault;
+        self.send(instructions::execute_purchase(listing, &state, &fee_vault, executor))
+    }
+
+    pub fn reclaim(&mut self, listing: &Pubkey) -> std::result::Result<Receipt, TxError> {
+        let state = self.listing(listing);
+        self.send(instructions::reclaim_nft(listing, &state))
+    }
+
+    /// Lists, fully funds and executes a listing; returns it with its contributors.
+    pub fn custodied(&mut self, split: &[u16]) -> (Listed, Vec<Pubkey>) {
+        let listed = self.list(listing_args());
+        let contributors = self.fund(&listed.key, split);
+        let executor = self.wallet();
+        assert_ok(self.execute(&listed.key, &executor));
+        (listed, contributors)
+    }
+
+    /// Overwrites a program account's state in place, for reaching branches that valid
+    /// instruction sequences cannot (defensive checks against corrupted or foreign accounts).
+    pub fn write<T: AccountSerialize>(&mut self, address: &Pubkey, state: &T) {
+        let mut account = self.bank.account(address).cloned().expect("account exists");
+        let mut data = Vec::new();
+        state.try_serialize(&mut data).unwrap();
+        account.data[..data.len()].copy_from_slice(&data);
+        self.bank.set_account(*address, account);
+    }
+
+    pub fn edit_listing(&mut self, listing: &Pubkey, edit: impl FnOnce(&mut Listing)) {
+        let mut state = self.listing(listing);
+        edit(&mut state);
+        self.write(listing, &state);
+    }
+
+    pub fn system_account(&self, address: &Pubkey) -> bool {
+        self.bank.account(address).is_some_and(|account| account.owner == system_program::ID)
+    }
+}
+
+impl Default for Env {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct Listed {
+    pub key: Pubkey,
+    pub seller: Pubkey,
+    pub nft_mint: Pubkey,
+}
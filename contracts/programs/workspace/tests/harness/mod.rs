@@ -0,0 +1,8 @@
+//! Shared by the integration test binaries; each uses a different subset.
+#![allow(dead_code, unused_imports)]
+
+pub mod fixtures;
+pub mod runtime;
+
+pub use fixtures::*;
+pub use runtime::{assert_error, assert_ok, Account, Bank, Receipt, TxError, START_TIME};
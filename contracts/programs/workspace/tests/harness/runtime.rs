@@ -0,0 +1,616 @@
+//! An in-process bank for the program: accounts live in a map, instructions run through the
+//! program's Anchor `entry` on the host, and syscalls are answered by [`Stubs`].
+//!
+//! - `Clock` and `Rent` come from the bank, so tests set `unix_timestamp` directly.
+//! - `emit_cpi!` self-invocations are captured as events; return data is captured for views.
+//! - CPIs into the System, SPL Token and Associated Token programs are emulated only for the
+//!   instructions the program issues (create/assign/transfer/allocate; transfer/close/initialize;
+//!   create), with their signer and ownership checks.
+//!
+//! Each instruction sees its accounts laid out as the SBF loader serializes them (original data
+//! length before the key, data length before the data, realloc headroom after it), so `realloc`,
+//! `assign` and account closing behave as on chain. A transaction commits only if every
+//! instruction succeeds.
+
+use std::collections::HashMap;
+use std::slice;
+use std::sync::{Mutex, MutexGuard, Once};
+
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::{ProgramResult, MAX_PERMITTED_DATA_INCREASE};
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program_pack::{IsInitialized, Pack};
+use anchor_lang::solana_program::program_stubs::{self, SyscallStubs};
+use anchor_lang::solana_program::bpf_loader;
+use anchor_lang::system_program;
+use anchor_lang::{AccountDeserialize, Discriminator};
+use anchor_spl::token::spl_token;
+use spl_token::error::TokenError;
+use spl_token::state::{Account as TokenAccount, AccountState};
+
+pub const START_TIME: i64 = 1_700_000_000;
+
+const NATIVE_LOADER: Pubkey = pubkey!("NativeLoader1111111111111111111111111111111");
+
+/// Stored account state.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Account {
+    pub lamports: u64,
+    pub owner: Pubkey,
+    pub data: Vec<u8>,
+    pub executable: bool,
+}
+
+/// A failed transaction: the program error and the logs leading up to it.
+#[derive(Debug)]
+pub struct TxError {
+    pub error: ProgramError,
+    pub logs: Vec<String>,
+}
+
+/// What a successful transaction produced.
+#[derive(Debug, Default)]
+pub struct Receipt {
+    /// `emit_cpi!` payloads (discriminator + borsh) in emission order.
+    pub events: Vec<Vec<u8>>,
+}
+
+impl Receipt {
+    /// Every emitted event of type `E`.
+    pub fn events<E: AnchorDeserialize + Discriminator>(&self) -> Vec<E> {
+        self.events
+            .iter()
+            .filter(|data| data.starts_with(E::DISCRIMINATOR))
+            .map(|data| E::try_from_slice(&data[E::DISCRIMINATOR.len()..]).expect("event decodes"))
+            .collect()
+    }
+
+    /// The single emitted event of type `E`.
+    pub fn event<E: AnchorDeserialize + Discriminator>(&self) -> E {
+        let mut events = self.events::<E>();
+        assert_eq!(events.len(), 1, "expected exactly one event of this type");
+        events.remove(0)
+    }
+
+    pub fn emitted<E: AnchorDeserialize + Discriminator>(&self) -> bool {
+        self.events.iter().any(|data| data.starts_with(E::DISCRIMINATOR))
+    }
+}
+
+/// Per-execution state the syscall stubs read and write.
+struct Runtime {
+    clock: Clock,
+    events: Vec<Vec<u8>>,
+    return_data: Option<(Pubkey, Vec<u8>)>,
+    logs: Vec<String>,
+}
+
+static RUNTIME: Mutex<Runtime> = Mutex::new(Runtime {
+    clock: Clock { slot: 0, epoch_start_timestamp: 0, epoch: 0, leader_schedule_epoch: 0, unix_timestamp: 0 },
+    events: Vec::new(),
+    return_data: None,
+    logs: Vec::new(),
+});
+
+/// Syscall stubs are process-wide, so banks in concurrently running tests take turns.
+static SERIAL: Mutex<()> = Mutex::new(());
+static INSTALL: Once = Once::new();
+
+fn runtime() -> MutexGuard<'static, Runtime> {
+    RUNTIME.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+pub struct Bank {
+    accounts: HashMap<Pubkey, Account>,
+    clock: Clock,
+    _serial: MutexGuard<'static, ()>,
+}
+
+impl Bank {
+    pub fn new() -> Self {
+        let serial = SERIAL.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        INSTALL.call_once(|| {
+            program_stubs::set_syscall_stubs(Box::new(Stubs));
+        });
+
+        let mut bank = Self {
+            accounts: HashMap::new(),
+            clock: Clock { slot: 1, unix_timestamp: START_TIME, ..Clock::default() },
+            _serial: serial,
+        };
+        for (program, loader) in [
+            (system_program::ID, NATIVE_LOADER),
+            (spl_token::ID, bpf_loader::ID),
+            (anchor_spl::associated_token::ID, bpf_loader::ID),
+            (mpl_token_metadata::ID, bpf_loader::ID),
+            (workspace::ID, bpf_loader::ID),
+        ] {
+            bank.set_account(program, Account { lamports: 1, owner: loader, data: Vec::new(), executable: true });
+        }
+        bank
+    }
+
+    pub fn now(&self) -> i64 {
+        self.clock.unix_timestamp
+    }
+
+    /// Moves the cluster clock to `unix_timestamp` (and one slot forward).
+    pub fn warp_to(&mut self, unix_timestamp: i64) {
+        self.clock.unix_timestamp = unix_timestamp;
+        self.clock.slot += 1;
+    }
+
+    pub fn warp(&mut self, seconds: i64) {
+        self.warp_to(self.clock.unix_timestamp + seconds);
+    }
+
+    pub fn set_account(&mut self, address: Pubkey, account: Account) {
+        self.accounts.insert(address, account);
+    }
+
+    pub fn account(&self, address: &Pubkey) -> Option<&Account> {
+        self.accounts.get(address)
+    }
+
+    pub fn exists(&self, address: &Pubkey) -> bool {
+        self.accounts.contains_key(address)
+    }
+
+    pub fn lamports(&self, address: &Pubkey) -> u64 {
+        self.accounts.get(address).map_or(0, |account| account.lamports)
+    }
+
+    pub fn airdrop(&mut self, address: &Pubkey, lamports: u64) {
+        self.accounts.entry(*address).or_default().lamports += lamports;
+    }
+
+    /// Decodes an Anchor account; panics if it is missing or malformed.
+    pub fn get<T: AccountDeserialize>(&self, address: &Pubkey) -> T {
+        let account = self.accounts.get(address).unwrap_or_else(|| panic!("account {address} does not exist"));
+        T::try_deserialize(&mut account.data.as_slice()).expect("account decodes")
+    }
+
+    /// Every program account of type `T`, as `getProgramAccounts` with a discriminator filter.
+    pub fn program_accounts<T: AccountDeserialize + Discriminator>(&self) -> Vec<(Pubkey, T)> {
+        let mut accounts: Vec<(Pubkey, T)> = self
+            .accounts
+            .iter()
+            .filter(|(_, account)| account.owner == workspace::ID && account.data.starts_with(T::DISCRIMINATOR))
+            .map(|(address, account)| (*address, T::try_deserialize(&mut account.data.as_slice()).expect("account decodes")))
+            .collect();
+        accounts.sort_by_key(|(address, _)| *address);
+        accounts
+    }
+
+    pub fn token_account(&self, address: &Pubkey) -> TokenAccount {
+        let account = self.accounts.get(address).unwrap_or_else(|| panic!("token account {address} does not exist"));
+        TokenAccount::unpack(&account.data).expect("token account decodes")
+    }
+
+    /// Runs `instructions` as one transaction; nothing is committed unless all succeed.
+    pub fn send(&mut self, instructions: &[Instruction]) -> std::result::Result<Receipt, TxError> {
+        let mut accounts = self.accounts.clone();
+        self.begin();
+        for instruction in instructions {
+            if let Err(error) = execute(&mut accounts, instruction) {
+                return Err(TxError { error, logs: std::mem::take(&mut runtime().logs) });
+            }
+        }
+        self.accounts = accounts;
+        Ok(Receipt { events: std::mem::take(&mut runtime().events) })
+    }
+
+    /// Runs a view instruction without committing anything and decodes its return data.
+    pub fn view<T: AnchorDeserialize>(&mut self, instruction: Instruction) -> std::result::Result<T, TxError> {
+        let mut accounts = self.accounts.clone();
+        self.begin();
+        if let Err(error) = execute(&mut accounts, &instruction) {
+            return Err(TxError { error, logs: std::mem::take(&mut runtime().logs) });
+        }
+        let (program_id, data) = runtime().return_data.take().expect("view set return data");
+        assert_eq!(program_id, workspace::ID);
+        Ok(T::try_from_slice(&data).expect("return data decodes"))
+    }
+
+    fn begin(&self) {
+        let mut runtime = runtime();
+        runtime.clock = self.clock.clone();
+        runtime.events.clear();
+        runtime.return_data = None;
+        runtime.logs.clear();
+    }
+}
+
+impl Default for Bank {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Asserts that a transaction failed with the given program error code.
+#[track_caller]
+pub fn assert_error(result: std::result::Result<Receipt, TxError>, code: impl Into<u32>) {
+    let code = code.into();
+    match result {
+        Err(TxError { error: ProgramError::Custom(actual), .. }) if actual == code => {}
+        Err(TxError { error, logs }) => panic!("expected error {code}, got {error:?}\n{}", logs.join("\n")),
+        Ok(_) => panic!("expected error {code}, but the transaction succeeded"),
+    }
+}
+
+/// Unwraps a successful transaction, printing the program logs if it failed.
+#[track_caller]
+pub fn assert_ok(result: std::result::Result<Receipt, TxError>) -> Receipt {
+    match result {
+        Ok(receipt) => receipt,
+        Err(TxError { error, logs }) => panic!("transaction failed: {error:?}\n{}", logs.join("\n")),
+    }
+}
+
+// ---- Account memory ----
+
+/// Bytes before the data: original data length (u32), key, owner, padding, data length (u64).
+const HEADER: usize = 80;
+const KEY: usize = 4;
+const OWNER: usize = 36;
+const DATA_LEN: usize = 72;
+
+/// One account serialized the way the SBF loader lays it out, so `realloc` can grow the data in
+/// place and read the original length in front of the key.
+struct Region {
+    words: Vec<u64>,
+    lamports: Box<u64>,
+}
+
+impl Region {
+    fn new(address: &Pubkey, account: &Account) -> Self {
+        let size = HEADER + account.data.len() + MAX_PERMITTED_DATA_INCREASE;
+        let mut region = Self { words: vec![0; size.div_ceil(8)], lamports: Box::new(account.lamports) };
+        let bytes = region.bytes_mut();
+        bytes[..KEY].copy_from_slice(&(account.data.len() as u32).to_le_bytes());
+        bytes[KEY..KEY + 32].copy_from_slice(address.as_ref());
+        bytes[OWNER..OWNER + 32].copy_from_slice(account.owner.as_ref());
+        bytes[DATA_LEN..HEADER].copy_from_slice(&(account.data.len() as u64).to_le_bytes());
+        bytes[HEADER..HEADER + account.data.len()].copy_from_slice(&account.data);
+        region
+    }
+
+    fn bytes(&self) -> &[u8] {
+        // SAFETY: a `u64` buffer viewed as bytes.
+        unsafe { slice::from_raw_parts(self.words.as_ptr() as *const u8, self.words.len() * 8) }
+    }
+
+    fn bytes_mut(&mut self) -> &mut [u8] {
+        // SAFETY: a `u64` buffer viewed as bytes.
+        unsafe { slice::from_raw_parts_mut(self.words.as_mut_ptr() as *mut u8, self.words.len() * 8) }
+    }
+
+    fn read(&self, executable: bool) -> Account {
+        let bytes = self.bytes();
+        let len = u64::from_le_bytes(bytes[DATA_LEN..HEADER].try_into().unwrap()) as usize;
+        Account {
+            lamports: *self.lamports,
+            owner: Pubkey::try_from(&bytes[OWNER..OWNER + 32]).unwrap(),
+            data: bytes[HEADER..HEADER + len].to_vec(),
+            executable,
+        }
+    }
+
+    /// # Safety
+    /// The returned view aliases the region; it must not outlive it, and the region must not be
+    /// read while the view is alive.
+    unsafe fn info<'a>(&mut self, is_signer: bool, is_writable: bool, executable: bool) -> AccountInfo<'a> {
+        let base = self.words.as_mut_ptr() as *mut u8;
+        let len = u64::from_le_bytes(self.bytes()[DATA_LEN..HEADER].try_into().unwrap()) as usize;
+        AccountInfo::new(
+            &*(base.add(KEY) as *const Pubkey),
+            is_signer,
+            is_writable,
+            &mut *(self.lamports.as_mut() as *mut u64),
+            slice::from_raw_parts_mut(base.add(HEADER), len),
+            &*(base.add(OWNER) as *const Pubkey),
+            executable,
+            u64::MAX,
+        )
+    }
+}
+
+/// Executes one top-level instruction against `accounts`, writing changes back on success.
+fn execute(accounts: &mut HashMap<Pubkey, Account>, instruction: &Instruction) -> std::result::Result<(), ProgramError> {
+    assert_eq!(instruction.program_id, workspace::ID, "only program instructions are executed");
+
+    // One region per distinct account; duplicate metas share it, as on chain.
+    let mut keys: Vec<Pubkey> = Vec::new();
+    for meta in &instruction.accounts {
+        if !keys.contains(&meta.pubkey) {
+            keys.push(meta.pubkey);
+        }
+    }
+    let before: Vec<Account> = keys.iter().map(|key| accounts.get(key).cloned().unwrap_or_default()).collect();
+    let mut regions: Vec<Region> = keys.iter().zip(&before).map(|(key, account)| Region::new(key, account)).collect();
+    let flags = |key: &Pubkey| {
+        let metas = instruction.accounts.iter().filter(|meta| meta.pubkey == *key);
+        metas.fold((false, false), |(signer, writable), meta| (signer || meta.is_signer, writable || meta.is_writable))
+    };
+
+    let result = {
+        let views: Vec<AccountInfo> = keys
+            .iter()
+            .zip(regions.iter_mut())
+            .zip(&before)
+            .map(|((key, region), account)| {
+                let (is_signer, is_writable) = flags(key);
+                // SAFETY: the views are dropped at the end of this block, before regions are read.
+                unsafe { region.info(is_signer, is_writable, account.executable) }
+            })
+            .collect();
+        let infos: Vec<AccountInfo> = instruction
+            .accounts
+            .iter()
+            .map(|meta| views[keys.iter().position(|key| *key == meta.pubkey).unwrap()].clone())
+            .collect();
+        workspace::entry(&instruction.program_id, &infos, &instruction.data)
+    };
+    result?;
+
+    let after: Vec<Account> = regions.iter().zip(&before).map(|(region, account)| region.read(account.executable)).collect();
+
+    // The runtime rules a real bank would enforce on the outcome.
+    let total = |accounts: &[Account]| accounts.iter().map(|account| account.lamports as u128).sum::<u128>();
+    assert_eq!(total(&before), total(&after), "instruction created or destroyed lamports");
+    let rent = Rent::default();
+    for ((key, old), new) in keys.iter().zip(&before).zip(&after) {
+        if !flags(key).1 {
+            assert_eq!(old, new, "read-only account {key} was modified");
+        }
+        if new.lamports > 0 && !new.executable {
+            assert!(rent.is_exempt(new.lamports, new.data.len()), "account {key} is not rent exempt");
+        }
+    }
+
+    for (key, account) in keys.into_iter().zip(after) {
+        // Accounts left without lamports are garbage collected.
+        if account.lamports == 0 {
+            accounts.remove(&key);
+        } else {
+            accounts.insert(key, account);
+        }
+    }
+    Ok(())
+}
+
+// ---- Syscalls ----
+
+struct Stubs;
+
+impl SyscallStubs for Stubs {
+    fn sol_log(&self, message: &str) {
+        runtime().logs.push(message.to_owned());
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        // SAFETY: the sysvar getter passes a pointer to an uninitialized `Clock`.
+        unsafe { (var_addr as *mut Clock).write(runtime().clock.clone()) };
+        0
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        // SAFETY: the sysvar getter passes a pointer to an uninitialized `Rent`.
+        unsafe { (var_addr as *mut Rent).write(Rent::default()) };
+        0
+    }
+
+    fn sol_set_return_data(&self, data: &[u8]) {
+        runtime().return_data = Some((workspace::ID, data.to_vec()));
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let signers = signers_seeds
+            .iter()
+            .map(|seeds| Pubkey::create_program_address(seeds, &workspace::ID))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|_| ProgramError::InvalidSeeds)?;
+
+        let mut accounts = Vec::with_capacity(instruction.accounts.len());
+        for meta in &instruction.accounts {
+            let info = account_infos
+                .iter()
+                .find(|info| *info.key == meta.pubkey)
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
+            if meta.is_signer && !info.is_signer && !signers.contains(&meta.pubkey) {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            if meta.is_writable && !info.is_writable {
+                return Err(ProgramError::InvalidArgument);
+            }
+            accounts.push(info);
+        }
+
+        match instruction.program_id {
+            id if id == system_program::ID => system(&instruction.data, &accounts),
+            id if id == spl_token::ID => token(&instruction.data, &accounts),
+            id if id == anchor_spl::associated_token::ID => associated_token(&instruction.data, &accounts),
+            id if id == workspace::ID && instruction.data.starts_with(EVENT_IX_TAG_LE) => {
+                runtime().events.push(instruction.data[EVENT_IX_TAG_LE.len()..].to_vec());
+                Ok(())
+            }
+            _ => Err(ProgramError::IncorrectProgramId),
+        }
+    }
+}
+
+fn read_u64(data: &[u8], offset: usize) -> std::result::Result<u64, ProgramError> {
+    data.get(offset..offset + 8)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or(ProgramError::InvalidInstructionData)
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> std::result::Result<Pubkey, ProgramError> {
+    data.get(offset..offset + 32)
+        .map(|bytes| Pubkey::try_from(bytes).unwrap())
+        .ok_or(ProgramError::InvalidInstructionData)
+}
+
+fn account<'a, 'info>(accounts: &[&'a AccountInfo<'info>], index: usize) -> std::result::Result<&'a AccountInfo<'info>, ProgramError> {
+    accounts.get(index).copied().ok_or(ProgramError::NotEnoughAccountKeys)
+}
+
+fn move_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> ProgramResult {
+    let mut from_lamports = from.try_borrow_mut_lamports()?;
+    // SystemError::ResultWithNegativeLamports
+    **from_lamports = from_lamports.checked_sub(amount).ok_or(ProgramError::Custom(1))?;
+    drop(from_lamports);
+    **to.try_borrow_mut_lamports()? += amount;
+    Ok(())
+}
+
+fn is_unused(info: &AccountInfo) -> bool {
+    info.lamports() == 0 && info.data_is_empty() && *info.owner == system_program::ID
+}
+
+/// SystemInstruction, bincode-encoded: u32 tag, then fields.
+fn system(data: &[u8], accounts: &[&AccountInfo]) -> ProgramResult {
+    let tag = data.get(..4).map(|tag| u32::from_le_bytes(tag.try_into().unwrap()));
+    match tag {
+        // CreateAccount { lamports, space, owner }
+        Some(0) => {
+            let (from, to) = (account(accounts, 0)?, account(accounts, 1)?);
+            if !is_unused(to) {
+                // SystemError::AccountAlreadyInUse
+                return Err(ProgramError::Custom(0));
+            }
+            move_lamports(from, to, read_u64(data, 4)?)?;
+            to.realloc(read_u64(data, 12)? as usize, true)?;
+            to.assign(&read_pubkey(data, 20)?);
+            Ok(())
+        }
+        // Assign { owner }
+        Some(1) => {
+            let target = account(accounts, 0)?;
+            if *target.owner != system_program::ID {
+                return Err(ProgramError::IllegalOwner);
+            }
+            target.assign(&read_pubkey(data, 4)?);
+            Ok(())
+        }
+        // Transfer { lamports }: only data-less, system-owned accounts can be debited.
+        Some(2) => {
+            let (from, to) = (account(accounts, 0)?, account(accounts, 1)?);
+            if *from.owner != system_program::ID || !from.data_is_empty() {
+                return Err(ProgramError::InvalidArgument);
+            }
+            move_lamports(from, to, read_u64(data, 4)?)
+        }
+        // Allocate { space }
+        Some(8) => {
+            let target = account(accounts, 0)?;
+            if *target.owner != system_program::ID || !target.data_is_empty() {
+                return Err(ProgramError::Custom(0));
+            }
+            target.realloc(read_u64(data, 4)? as usize, true)
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+fn unpack_token(info: &AccountInfo) -> std::result::Result<TokenAccount, ProgramError> {
+    if *info.owner != spl_token::ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    TokenAccount::unpack(&info.try_borrow_data()?)
+}
+
+fn pack_token(state: TokenAccount, info: &AccountInfo) -> ProgramResult {
+    TokenAccount::pack(state, &mut info.try_borrow_mut_data()?)
+}
+
+fn transfer_tokens(source: &AccountInfo, destination: &AccountInfo, authority: &AccountInfo, amount: u64) -> ProgramResult {
+    let mut from = unpack_token(source)?;
+    let mut to = unpack_token(destination)?;
+    if from.mint != to.mint {
+        return Err(TokenError::MintMismatch.into());
+    }
+    if from.owner != *authority.key {
+        return Err(TokenError::OwnerMismatch.into());
+    }
+    from.amount = from.amount.checked_sub(amount).ok_or(TokenError::InsufficientFunds)?;
+    if source.key == destination.key {
+        return Ok(());
+    }
+    to.amount = to.amount.checked_add(amount).ok_or(TokenError::Overflow)?;
+    pack_token(from, source)?;
+    pack_token(to, destination)
+}
+
+/// SPL Token instructions: u8 tag, then fields.
+fn token(data: &[u8], accounts: &[&AccountInfo]) -> ProgramResult {
+    match data.first() {
+        // Transfer { amount }
+        Some(3) => transfer_tokens(account(accounts, 0)?, account(accounts, 1)?, account(accounts, 2)?, read_u64(data, 1)?),
+        // CloseAccount
+        Some(9) => {
+            let (target, destination, authority) = (account(accounts, 0)?, account(accounts, 1)?, account(accounts, 2)?);
+            let state = unpack_token(target)?;
+            if state.amount != 0 {
+                return Err(TokenError::NonNativeHasBalance.into());
+            }
+            if state.close_authority.unwrap_or(state.owner) != *authority.key {
+                return Err(TokenError::OwnerMismatch.into());
+            }
+            move_lamports(target, destination, target.lamports())?;
+            target.try_borrow_mut_data()?.fill(0);
+            Ok(())
+        }
+        // InitializeAccount3 { owner }
+        Some(18) => {
+            let (target, mint) = (account(accounts, 0)?, account(accounts, 1)?);
+            if *target.owner != spl_token::ID || *mint.owner != spl_token::ID {
+                return Err(ProgramError::IncorrectProgramId);
+            }
+            if TokenAccount::unpack_unchecked(&target.try_borrow_data()?)?.is_initialized() {
+                return Err(TokenError::AlreadyInUse.into());
+            }
+            let state = TokenAccount {
+                mint: *mint.key,
+                owner: read_pubkey(data, 1)?,
+                state: AccountState::Initialized,
+                ..TokenAccount::default()
+            };
+            pack_token(state, target)
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+/// Associated Token Account program: Create (0) only, which `init_if_needed` issues for a
+/// missing account.
+fn associated_token(data: &[u8], accounts: &[&AccountInfo]) -> ProgramResult {
+    if data.first().is_some_and(|tag| *tag != 0) {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let (payer, target, wallet, mint) = (account(accounts, 0)?, account(accounts, 1)?, account(accounts, 2)?, account(accounts, 3)?);
+    let expected = Pubkey::find_program_address(
+        &[wallet.key.as_ref(), spl_token::ID.as_ref(), mint.key.as_ref()],
+        &anchor_spl::associated_token::ID,
+    )
+    .0;
+    if *target.key != expected {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if !target.data_is_empty() || *target.owner != system_program::ID {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let required = Rent::default().minimum_balance(TokenAccount::LEN);
+    move_lamports(payer, target, required.saturating_sub(target.lamports()))?;
+    target.realloc(TokenAccount::LEN, true)?;
+    target.assign(&spl_token::ID);
+    let state = TokenAccount { mint: *mint.key, owner: *wallet.key, state: AccountState::Initialized, ..TokenAccount::default() };
+    pack_token(state, target)
+}
@@ -0,0 +1,635 @@
+//! Listing creation, contributions and purchase execution.
+
+mod harness;
+
+use anchor_lang::prelude::{AccountMeta, Pubkey, Rent};
+use anchor_lang::Space;
+use fracvault_client::{instructions, pda};
+use harness::*;
+use mpl_token_metadata::types::Creator;
+use workspace::{
+    presale_leaf, presale_node, Contribution, ContributionQuote, CreateListingArgs, DecayCurve, DutchAuctionParams, ErrorCode, ListingStatus, Presale, PresaleProof,
+    SoftCloseParams,
+};
+
+fn create(env: &mut Env, args: CreateListingArgs) -> Result<Receipt, TxError> {
+    let seller = env.wallet();
+    let nft = env.nft(&seller, NftSpec::default());
+    env.send(instructions::create_listing(&seller, &nft, None, args))
+}
+
+fn create_with(env: &mut Env, spec: NftSpec) -> Result<Receipt, TxError> {
+    let seller = env.wallet();
+    let nft = env.nft(&seller, spec);
+    env.send(instructions::create_listing(&seller, &nft, None, listing_args()))
+}
+
+#[test]
+fn create_listing_escrows_the_nft_and_prices_the_raise() {
+    let mut env = Env::new();
+    let args = CreateListingArgs { retained_bps: 2000, keeper_tip_lamports: 1_000_000, ..listing_args() };
+    let listed = env.list(args);
+
+    let listing = env.listing(&listed.key);
+    assert!(listing.status == ListingStatus::Open);
+    assert_eq!(listing.nft_seller, listed.seller);
+    assert_eq!(listing.deadline, START_TIME + DEADLINE_OFFSET);
+    // 80% of 10 SOL is offered; the 1% fee is charged on that portion only.
+    assert_eq!(listing.custody_fee_lamports, 80_000_000);
+    assert_eq!(listing.total_raise_lamports, 8 * SOL + 80_000_000);
+    assert_eq!(listing.bps_for_sale(), 8000);
+
+    assert_eq!(env.bank.token_account(&listing.vault).amount, 1);
+    assert_eq!(env.bank.token_account(&pda::associated_token(&listed.seller, &listed.nft_mint)).amount, 0);
+    assert_eq!(env.escrowed(&listed.key), 1_000_000);
+}
+
+#[test]
+fn create_listing_validates_its_arguments() {
+    let mut env = Env::new();
+    let cases = [
+        (CreateListingArgs { price_lamports: 0, ..listing_args() }, ErrorCode::InvalidAmount),
+        (CreateListingArgs { deadline_offset: 3599, ..listing_args() }, ErrorCode::InvalidDeadline),
+        (CreateListingArgs { deadline_offset: 604801, ..listing_args() }, ErrorCode::InvalidDeadline),
+        (CreateListingArgs { retained_bps: 10000, ..listing_args() }, ErrorCode::InvalidBps),
+        (CreateListingArgs { retained_bps: 5000, min_fill_bps: 5001, ..listing_args() }, ErrorCode::InvalidBps),
+        (CreateListingArgs { retained_bps: 5000, max_bps_per_wallet: 5001, ..listing_args() }, ErrorCode::InvalidBps),
+        (CreateListingArgs { retained_bps: 5000, min_bps_per_contribution: 5001, ..listing_args() }, ErrorCode::InvalidBps),
+        (
+            CreateListingArgs { max_bps_per_wallet: 100, min_bps_per_contribution: 200, ..listing_args() },
+            ErrorCode::InvalidBps,
+        ),
+        (CreateListingArgs { price_lamports: u64::MAX, ..listing_args() }, ErrorCode::MathOverflow),
+        (
+            CreateListingArgs {
+                presale: Some(Presale { merkle_root: [0; 32], end_time: START_TIME }),
+                ..listing_args()
+            },
+            ErrorCode::InvalidDeadline,
+        ),
+        (
+            CreateListingArgs {
+                presale: Some(Presale { merkle_root: [0; 32], end_time: START_TIME + DEADLINE_OFFSET + 1 }),
+                ..listing_args()
+            },
+            ErrorCode::InvalidDeadline,
+        ),
+        (
+            CreateListingArgs {
+                soft_close: Some(SoftCloseParams { trigger_seconds: 60, extension_seconds: 300, max_extension_seconds: 600 }),
+                ..listing_args()
+            },
+            ErrorCode::InvalidDeadline,
+        ),
+        (
+            CreateListingArgs {
+                soft_close: Some(SoftCloseParams { trigger_seconds: 600, extension_seconds: 300, max_extension_seconds: 299 }),
+                ..listing_args()
+            },
+            ErrorCode::InvalidDeadline,
+        ),
+        (CreateListingArgs { auction: Some(auction(9_999, 3600)), ..listing_args() }, ErrorCode::InvalidAuction),
+        (CreateListingArgs { auction: Some(auction(PRICE + 1, 3600)), ..listing_args() }, ErrorCode::InvalidAuction),
+        (CreateListingArgs { auction: Some(auction(SOL, DEADLINE_OFFSET + 1)), ..listing_args() }, ErrorCode::InvalidAuction),
+        (
+            CreateListingArgs {
+                auction: Some(DutchAuctionParams {
+                    curve: DecayCurve::Stepwise { step_seconds: 3601 },
+                    ..auction(SOL, 3600)
+                }),
+                ..listing_args()
+            },
+            ErrorCode::InvalidAuction,
+        ),
+    ];
+    for (args, code) in cases {
+        assert_error(create(&mut env, args), code);
+    }
+}
+
+#[test]
+fn create_listing_only_accepts_real_nfts() {
+    let mut env = Env::new();
+    assert_error(create_with(&mut env, NftSpec { decimals: 1, ..Default::default() }), ErrorCode::InvalidNftMint);
+    assert_error(create_with(&mut env, NftSpec { supply: 2, ..Default::default() }), ErrorCode::InvalidNftMint);
+    let other = Pubkey::new_unique();
+    assert_error(
+        create_with(&mut env, NftSpec { mint_authority: MintAuthority::Other(other), ..Default::default() }),
+        ErrorCode::InvalidNftMint,
+    );
+    assert_error(create_with(&mut env, NftSpec { metadata: false, ..Default::default() }), ErrorCode::InvalidMetadata);
+    let creators = vec![Creator { address: Pubkey::new_unique(), verified: true, share: 100 }; 6];
+    assert_error(create_with(&mut env, NftSpec { creators, ..Default::default() }), ErrorCode::InvalidMetadata);
+
+    // A live mint authority is fine when it is the master edition.
+    assert_ok(create_with(&mut env, NftSpec { mint_authority: MintAuthority::MasterEdition, ..Default::default() }));
+}
+
+#[test]
+fn create_listing_checks_the_seller_token_account() {
+    let mut env = Env::new();
+    let seller = env.wallet();
+    let nft = env.nft(&seller, NftSpec::default());
+
+    // Someone else's account for the same mint.
+    let other = env.wallet();
+    let foreign = env.token_account(&other, &nft, 1);
+    let mut ix = instructions::create_listing(&seller, &nft, None, listing_args());
+    swap(&mut ix, &pda::associated_token(&seller, &nft), foreign);
+    assert_error(env.send(ix), ErrorCode::InvalidNftOwnership);
+
+    // The seller's account for a different mint.
+    let decoy = env.nft(&seller, NftSpec::default());
+    let mut ix = instructions::create_listing(&seller, &nft, None, listing_args());
+    swap(&mut ix, &pda::associated_token(&seller, &nft), pda::associated_token(&seller, &decoy));
+    assert_error(env.send(ix), ErrorCode::InvalidMint);
+
+    // The right account, but empty.
+    env.token_account(&seller, &nft, 0);
+    assert_error(env.send(instructions::create_listing(&seller, &nft, None, listing_args())), ErrorCode::InvalidNftOwnership);
+}
+
+#[test]
+fn contribute_records_positions_and_funds_the_listing() {
+    let mut env = Env::new();
+    let listed = env.list(listing_args());
+    let wallet = env.wallet();
+    let before = env.bank.lamports(&wallet);
+
+    let receipt = assert_ok(env.contribute(&listed.key, &wallet, 2500));
+    let made = receipt.event::<workspace::ContributionMade>();
+    assert_eq!((made.bps, made.principal_lamports, made.fee_lamports), (2500, 2_500_000_000, 25_000_000));
+    assert!(!receipt.emitted::<workspace::ListingFunded>());
+
+    // Top-ups accumulate on the same position.
+    assert_ok(env.contribute(&listed.key, &wallet, 2500));
+    let position = env.contribution(&listed.key, &wallet);
+    assert_eq!((position.bps, position.principal_lamports, position.fee_lamports), (5000, 5 * SOL, 50_000_000));
+    let rent = env.bank.lamports(&pda::contribution(&listed.key, &wallet).0);
+    assert_eq!(before - env.bank.lamports(&wallet), 5 * SOL + 50_000_000 + rent);
+
+    let last = env.wallet();
+    env.bank.warp(3600);
+    let receipt = assert_ok(env.contribute(&listed.key, &last, 5000));
+    let funded = receipt.event::<workspace::ListingFunded>();
+    assert_eq!((funded.bps_sold, funded.contributor_count), (10000, 2));
+
+    let listing = env.listing(&listed.key);
+    assert!(listing.status == ListingStatus::Funded);
+    assert_eq!(listing.funded_at, START_TIME + 3600);
+    assert_eq!(env.escrowed(&listed.key), listing.total_raise_lamports);
+}
+
+#[test]
+fn contribute_rejects_invalid_amounts_and_late_contributions() {
+    let mut env = Env::new();
+    let listed = env.list(listing_args());
+    let wallet = env.wallet();
+    assert_error(env.contribute(&listed.key, &wallet, 0), ErrorCode::InvalidBps);
+    assert_error(env.contribute(&listed.key, &wallet, 10001), ErrorCode::InvalidBps);
+    assert_ok(env.contribute(&listed.key, &wallet, 6000));
+    assert_error(env.contribute(&listed.key, &wallet, 4001), ErrorCode::ExceedsAvailable);
+
+    // Contributions close 60 seconds before the deadline.
+    let deadline = env.listing(&listed.key).deadline;
+    env.bank.warp_to(deadline - 60);
+    assert_error(env.contribute(&listed.key, &wallet, 100), ErrorCode::ListingExpired);
+    env.bank.warp_to(deadline - 61);
+    assert_ok(env.contribute(&listed.key, &wallet, 4000));
+
+    // Funded listings take no more contributions.
+    let late = env.wallet();
+    assert_error(env.contribute(&listed.key, &late, 1), ErrorCode::InvalidListingStatus);
+}
+
+#[test]
+fn contribute_enforces_seller_distribution_limits() {
+    let mut env = Env::new();
+    let args = CreateListingArgs { max_bps_per_wallet: 3000, min_bps_per_contribution: 1000, ..listing_args() };
+    let listed = env.list(args);
+    let whale = env.wallet();
+
+    assert_error(env.contribute(&listed.key, &whale, 999), ErrorCode::BelowMinContribution);
+    assert_ok(env.contribute(&listed.key, &whale, 2000));
+    // The cap applies to the wallet's accumulated position.
+    assert_error(env.contribute(&listed.key, &whale, 1001), ErrorCode::ExceedsWalletCap);
+    assert_ok(env.contribute(&listed.key, &whale, 1000));
+
+    env.fund(&listed.key, &[3000, 3000]);
+    // The minimum is waived for the final bps of the raise.
+    let last = env.wallet();
+    assert_ok(env.contribute(&listed.key, &last, 1000));
+    assert!(env.listing(&listed.key).status == ListingStatus::Funded);
+
+    let listed = env.list(CreateListingArgs { min_bps_per_contribution: 1000, ..listing_args() });
+    env.fund(&listed.key, &[5000, 4500]);
+    let last = env.wallet();
+    assert_ok(env.contribute(&listed.key, &last, 500));
+}
+
+#[test]
+fn contribute_rejects_positions_that_do_not_belong_to_the_listing() {
+    let mut env = Env::new();
+    let listed = env.list(listing_args());
+    let wallet = env.wallet();
+    assert_ok(env.contribute(&listed.key, &wallet, 1000));
+    let address = pda::contribution(&listed.key, &wallet).0;
+
+    let mut position = env.contribution(&listed.key, &wallet);
+    position.listing = Pubkey::new_unique();
+    env.write(&address, &position);
+    assert_error(env.contribute(&listed.key, &wallet, 1000), ErrorCode::InvalidContribution);
+
+    position.listing = listed.key;
+    position.wallet = Pubkey::new_unique();
+    env.write(&address, &position);
+    assert_error(env.contribute(&listed.key, &wallet, 1000), ErrorCode::InvalidContribution);
+}
+
+#[test]
+fn presale_is_limited_to_allowlisted_wallets_until_it_ends() {
+    let mut env = Env::new();
+    let (alice, bob, carol) = (env.wallet(), env.wallet(), env.wallet());
+    let (alice_leaf, bob_leaf) = (presale_leaf(&alice, 2000), presale_leaf(&bob, 0));
+    let end_time = START_TIME + 3600;
+    let args = CreateListingArgs {
+        presale: Some(Presale { merkle_root: presale_node(&alice_leaf, &bob_leaf), end_time }),
+        ..listing_args()
+    };
+    let listed = env.list(args);
+    let proof = |max_bps, sibling| Some(PresaleProof { max_bps, proof: vec![sibling] });
+
+    assert_error(env.contribute(&listed.key, &carol, 1000), ErrorCode::NotAllowlisted);
+    let forged = instructions::contribute(&listed.key, &carol, 1000, proof(0, bob_leaf));
+    assert_error(env.send(forged), ErrorCode::NotAllowlisted);
+    // The leaf commits to the cap, so a wallet cannot claim a larger one.
+    let inflated = instructions::contribute(&listed.key, &alice, 1000, proof(3000, bob_leaf));
+    assert_error(env.send(inflated), ErrorCode::NotAllowlisted);
+
+    assert_ok(env.send(instructions::contribute(&listed.key, &alice, 1500, proof(2000, bob_leaf))));
+    let over = instructions::contribute(&listed.key, &alice, 501, proof(2000, bob_leaf));
+    assert_error(env.send(over), ErrorCode::ExceedsWalletCap);
+    assert_ok(env.send(instructions::contribute(&listed.key, &bob, 5000, proof(0, alice_leaf))));
+
+    env.bank.warp_to(end_time);
+    assert_ok(env.contribute(&listed.key, &carol, 1000));
+    assert_ok(env.contribute(&listed.key, &alice, 1000));
+}
+
+fn quote(env: &mut Env, listing: &Pubkey, wallet: &Pubkey, bps: u16, proof: Option<PresaleProof>) -> Result<ContributionQuote, TxError> {
+    env.bank.view(instructions::quote_contribution(listing, wallet, bps, proof))
+}
+
+fn assert_rejected(result: Result<ContributionQuote, TxError>, code: ErrorCode) {
+    assert_eq!(result.err().expect("view fails").error, anchor_lang::prelude::ProgramError::Custom(code.into()));
+}
+
+#[test]
+fn quotes_apply_the_checks_contribute_does() {
+    let mut env = Env::new();
+    let (alice, bob, carol) = (env.wallet(), env.wallet(), env.wallet());
+    let (alice_leaf, bob_leaf) = (presale_leaf(&alice, 2000), presale_leaf(&bob, 0));
+    let end_time = START_TIME + 3600;
+    let args = CreateListingArgs {
+        presale: Some(Presale { merkle_root: presale_node(&alice_leaf, &bob_leaf), end_time }),
+        max_bps_per_wallet: 3000,
+        min_bps_per_contribution: 1000,
+        ..listing_args()
+    };
+    let listed = env.list(args);
+    let proof = || Some(PresaleProof { max_bps: 2000, proof: vec![bob_leaf] });
+
+    assert_rejected(quote(&mut env, &listed.key, &carol, 1000, None), ErrorCode::NotAllowlisted);
+    assert_rejected(quote(&mut env, &listed.key, &alice, 500, proof()), ErrorCode::BelowMinContribution);
+    let quoted = quote(&mut env, &listed.key, &alice, 1500, proof()).unwrap();
+    let made = assert_ok(env.send(instructions::contribute(&listed.key, &alice, 1500, proof()))).event::<workspace::ContributionMade>();
+    assert_eq!((quoted.principal_lamports, quoted.fee_lamports), (made.principal_lamports, made.fee_lamports));
+    // Caps count the wallet's existing position: 2000 bps during the presale, 3000 after it.
+    assert_rejected(quote(&mut env, &listed.key, &alice, 1000, proof()), ErrorCode::ExceedsWalletCap);
+
+    env.bank.warp_to(end_time);
+    assert_rejected(quote(&mut env, &listed.key, &alice, 2000, None), ErrorCode::ExceedsWalletCap);
+    assert_eq!(quote(&mut env, &listed.key, &alice, 1500, None).unwrap().remaining_bps, 8500);
+    quote(&mut env, &listed.key, &carol, 1000, None).unwrap();
+}
+
+#[test]
+fn soft_close_extends_the_deadline_up_to_the_hard_cap() {
+    let mut env = Env::new();
+    let soft_close = SoftCloseParams { trigger_seconds: 600, extension_seconds: 300, max_extension_seconds: 500 };
+    let listed = env.list(CreateListingArgs { soft_close: Some(soft_close), ..listing_args() });
+    let deadline = env.listing(&listed.key).deadline;
+    let wallet = env.wallet();
+
+    env.bank.warp_to(deadline - 601);
+    let receipt = assert_ok(env.contribute(&listed.key, &wallet, 100));
+    assert!(!receipt.emitted::<workspace::SoftCloseTriggered>());
+
+    env.bank.warp_to(deadline - 600);
+    let receipt = assert_ok(env.contribute(&listed.key, &wallet, 100));
+    let extended = receipt.event::<workspace::DeadlineExtended>();
+    assert_eq!((extended.previous_deadline, extended.new_deadline), (deadline, deadline + 300));
+    assert_eq!(extended.hard_deadline, deadline + 500);
+
+    env.bank.warp_to(deadline);
+    let receipt = assert_ok(env.contribute(&listed.key, &wallet, 100));
+    assert_eq!(receipt.event::<workspace::DeadlineExtended>().new_deadline, deadline + 500);
+
+    // At the hard deadline the trigger still fires, but there is nothing left to extend.
+    env.bank.warp_to(deadline + 400);
+    let receipt = assert_ok(env.contribute(&listed.key, &wallet, 100));
+    assert!(receipt.emitted::<workspace::SoftCloseTriggered>());
+    assert!(!receipt.emitted::<workspace::DeadlineExtended>());
+    assert_eq!(env.listing(&listed.key).deadline, deadline + 500);
+}
+
+#[test]
+fn execute_purchase_pays_seller_and_fee_vault() {
+    let mut env = Env::new();
+    let listed = env.list(listing_args());
+    env.fund(&listed.key, &[4000, 6000]);
+    let (seller_before, vault_before) = (env.bank.lamports(&listed.seller), env.bank.lamports(&env.fee_vault));
+
+    let executor = env.wallet();
+    let receipt = assert_ok(env.execute(&listed.key, &executor));
+    let executed = receipt.event::<workspace::PurchaseExecuted>();
+    assert_eq!((executed.price_lamports, executed.custody_fee_lamports), (PRICE, PRICE / 100));
+    assert_eq!((executed.bps_sold, executed.retained_bps), (10000, 0));
+
+    assert_eq!(env.bank.lamports(&listed.seller) - seller_before, PRICE);
+    assert_eq!(env.bank.lamports(&env.fee_vault) - vault_before, PRICE / 100);
+    assert_eq!(env.escrowed(&listed.key), 0);
+    assert!(env.listing(&listed.key).status == ListingStatus::Custodied);
+    // Custody keeps the NFT in the vault.
+    assert_eq!(env.bank.token_account(&env.listing(&listed.key).vault).amount, 1);
+
+    assert_error(env.execute(&listed.key, &executor), ErrorCode::InvalidListingStatus);
+}
+
+#[test]
+fn execute_purchase_window_closes_86400_seconds_after_funding() {
+    for (offset, succeeds) in [(86400, true), (86401, false)] {
+        let mut env = Env::new();
+        let listed = env.list(listing_args());
+        env.bank.warp(1234);
+        env.fund(&listed.key, &[10000]);
+        let funded_at = env.listing(&listed.key).funded_at;
+        assert_eq!(funded_at, START_TIME + 1234);
+
+        env.bank.warp_to(funded_at + offset);
+        let executor = env.wallet();
+        let result = env.execute(&listed.key, &executor);
+        if succeeds {
+            assert_ok(result);
+        } else {
+            assert_error(result, ErrorCode::ExecutionWindowExpired);
+        }
+    }
+}
+
+#[test]
+fn execute_purchase_rejects_unfunded_listings_and_foreign_accounts() {
+    let mut env = Env::new();
+    let listed = env.list(listing_args());
+    let executor = env.wallet();
+    env.fund(&listed.key, &[5000]);
+    assert_error(env.execute(&listed.key, &executor), ErrorCode::InvalidListingStatus);
+    env.fund(&listed.key, &[5000]);
+
+    let state = env.listing(&listed.key);
+    let fee_vault = env.fee_vault;
+    let mut ix = instructions::execute_purchase(&listed.key, &state, &Pubkey::new_unique(), &executor);
+    assert_error(env.send(ix.clone()), ErrorCode::InvalidFeeVault);
+
+    ix = instructions::execute_purchase(&listed.key, &state, &fee_vault, &executor);
+    let impostor = env.wallet();
+    swap(&mut ix, &listed.seller, impostor);
+    assert_error(env.send(ix), ErrorCode::Unauthorized);
+
+    // Defensive: an escrow short of the raise cannot settle.
+    let escrow = pda::escrow(&listed.key).0;
+    let mut account = env.bank.account(&escrow).cloned().unwrap();
+    account.lamports -= 1;
+    env.bank.set_account(escrow, account);
+    assert_error(env.execute(&listed.key, &executor), ErrorCode::InsufficientListingLamports);
+}
+
+#[test]
+fn min_fill_listings_execute_in_the_window_after_the_deadline() {
+    for (offset, expected) in [(-1, Some(ErrorCode::ListingNotExpired)), (0, None), (86400, None), (86401, Some(ErrorCode::ExecutionWindowExpired))] {
+        let mut env = Env::new();
+        let listed = env.list(CreateListingArgs { min_fill_bps: 5000, ..listing_args() });
+        env.fund(&listed.key, &[3000, 3000]);
+        let deadline = env.listing(&listed.key).deadline;
+
+        env.bank.warp_to(deadline + offset);
+        let executor = env.wallet();
+        let result = env.execute(&listed.key, &executor);
+        let Some(code) = expected else {
+            let executed = assert_ok(result).event::<workspace::PurchaseExecuted>();
+            // Partial fills execute at a proportional price; the seller keeps the unsold bps.
+            assert_eq!((executed.bps_sold, executed.retained_bps, executed.price_lamports), (6000, 4000, 6 * SOL));
+            let seller_position = env.contribution(&listed.key, &listed.seller);
+            assert_eq!((seller_position.bps, seller_position.principal_lamports), (4000, 0));
+            continue;
+        };
+        assert_error(result, code);
+    }
+}
+
+#[test]
+fn partial_fills_need_the_seller_position() {
+    let mut env = Env::new();
+    let listed = env.list(CreateListingArgs { min_fill_bps: 5000, ..listing_args() });
+    env.fund(&listed.key, &[6000]);
+    env.bank.warp_to(env.listing(&listed.key).deadline);
+
+    let executor = env.wallet();
+    let state = env.listing(&listed.key);
+    let mut ix = instructions::execute_purchase(&listed.key, &state, &env.fee_vault.clone(), &executor);
+    // An omitted optional account is passed as the program id.
+    swap(&mut ix, &pda::contribution(&listed.key, &listed.seller).0, workspace::ID);
+    assert_error(env.send(ix), ErrorCode::InvalidContribution);
+}
+
+#[test]
+fn retained_bps_become_the_seller_position_on_execution() {
+    let mut env = Env::new();
+    let listed = env.list(CreateListingArgs { retained_bps: 4000, ..listing_args() });
+    env.fund(&listed.key, &[6000]);
+    let seller_before = env.bank.lamports(&listed.seller);
+    let executor = env.wallet();
+    let executor_before = env.bank.lamports(&executor);
+    let executed = assert_ok(env.execute(&listed.key, &executor)).event::<workspace::PurchaseExecuted>();
+
+    // The seller's position is paid for out of the proceeds, not by the executor.
+    assert_eq!((executed.price_lamports, executed.custody_fee_lamports), (6 * SOL, 60_000_000));
+    assert_eq!(executed.seller_proceeds_lamports, 6 * SOL - position_rent());
+    assert_eq!(env.bank.lamports(&listed.seller) - seller_before, 6 * SOL - position_rent());
+    assert_eq!(env.bank.lamports(&executor), executor_before);
+    assert_eq!(env.contribution(&listed.key, &listed.seller).bps, 4000);
+}
+
+#[test]
+fn contribute_and_execute_creates_the_seller_position_only_when_it_settles() {
+    let mut env = Env::new();
+    let listed = env.list(CreateListingArgs { retained_bps: 4000, ..listing_args() });
+    let fee_vault = env.fee_vault;
+    let seller_position = pda::contribution(&listed.key, &listed.seller).0;
+
+    // A contribution that leaves the raise open pays for its own position and nothing else.
+    let wallet = env.wallet();
+    let before = env.bank.lamports(&wallet);
+    let state = env.listing(&listed.key);
+    let receipt = assert_ok(env.send(instructions::contribute_and_execute(&listed.key, &state, &fee_vault, &wallet, 3000, None)));
+    let made = receipt.event::<workspace::ContributionMade>();
+    assert!(!receipt.emitted::<workspace::PurchaseExecuted>());
+    assert!(!env.bank.exists(&seller_position));
+    assert_eq!(before - env.bank.lamports(&wallet), made.principal_lamports + made.fee_lamports + position_rent());
+
+    // A stray deposit at the seller's position address does not block its creation.
+    let system = anchor_lang::system_program::ID;
+    env.bank.set_account(seller_position, Account { lamports: 1_000_000, owner: system, data: vec![], executable: false });
+
+    let last = env.wallet();
+    let seller_before = env.bank.lamports(&listed.seller);
+    let state = env.listing(&listed.key);
+    let receipt = assert_ok(env.send(instructions::contribute_and_execute(&listed.key, &state, &fee_vault, &last, 3000, None)));
+    let executed = receipt.event::<workspace::PurchaseExecuted>();
+    assert_eq!(executed.seller_proceeds_lamports, 6 * SOL - (position_rent() - 1_000_000));
+    assert_eq!(env.bank.lamports(&listed.seller) - seller_before, executed.seller_proceeds_lamports);
+    assert_eq!(env.bank.lamports(&seller_position), position_rent());
+    let position = env.contribution(&listed.key, &listed.seller);
+    assert!(position.wallet == listed.seller && position.bps == 4000 && position.principal_lamports == 0);
+}
+
+fn position_rent() -> u64 {
+    Rent::default().minimum_balance(8 + Contribution::INIT_SPACE)
+}
+
+#[test]
+fn execution_pays_creator_royalties_and_keeper_bounty() {
+    let mut env = Env::new();
+    let authority = env.authority;
+    assert_ok(env.send(instructions::update_config(&authority, None, Some(true), Some(3_000_000))));
+
+    let (first, second) = (env.wallet(), env.wallet());
+    let creators = vec![
+        Creator { address: first, verified: true, share: 70 },
+        Creator { address: second, verified: false, share: 30 },
+    ];
+    let seller = env.wallet();
+    let nft = env.nft(&seller, NftSpec { creators, seller_fee_basis_points: 500, ..Default::default() });
+    let args = CreateListingArgs { keeper_tip_lamports: 1_000_000, ..listing_args() };
+    assert_ok(env.send(instructions::create_listing(&seller, &nft, None, args)));
+    let listing = pda::listing(&nft).0;
+    env.fund(&listing, &[10000]);
+
+    let executor = env.wallet();
+    let state = env.listing(&listing);
+    let mut missing = instructions::execute_purchase(&listing, &state, &env.fee_vault.clone(), &executor);
+    missing.accounts.pop();
+    assert_error(env.send(missing.clone()), ErrorCode::InvalidCreatorAccount);
+    missing.accounts.push(AccountMeta::new(Pubkey::new_unique(), false));
+    assert_error(env.send(missing.clone()), ErrorCode::InvalidCreatorAccount);
+    missing.accounts.pop();
+    missing.accounts.push(AccountMeta::new_readonly(second, false));
+    assert_error(env.send(missing), ErrorCode::InvalidCreatorAccount);
+
+    let (first_before, second_before) = (env.bank.lamports(&first), env.bank.lamports(&second));
+    let (executor_before, vault_before) = (env.bank.lamports(&executor), env.bank.lamports(&env.fee_vault));
+    let receipt = assert_ok(env.execute(&listing, &executor));
+
+    // 5% of 10 SOL, split 70/30.
+    assert_eq!(env.bank.lamports(&first) - first_before, 350_000_000);
+    assert_eq!(env.bank.lamports(&second) - second_before, 150_000_000);
+    let executed = receipt.event::<workspace::PurchaseExecuted>();
+    assert_eq!((executed.royalty_lamports, executed.seller_proceeds_lamports), (500_000_000, 9_500_000_000));
+
+    // The bounty drains the 0.001 SOL tip first and takes the rest out of the custody fee.
+    let bounty = receipt.event::<workspace::KeeperBountyPaid>();
+    assert_eq!((bounty.from_tip_lamports, bounty.from_fee_lamports), (1_000_000, 2_000_000));
+    assert_eq!(env.bank.lamports(&executor) - executor_before, 3_000_000);
+    assert_eq!(env.bank.lamports(&env.fee_vault) - vault_before, PRICE / 100 - 2_000_000);
+    assert_eq!(env.listing(&listing).keeper_tip_lamports, 0);
+    // Royalties, proceeds, fee and bounty leave the escrow holding only its rent-exempt reserve.
+    assert_eq!(env.bank.lamports(&pda::escrow(&listing).0), Rent::default().minimum_balance(0));
+}
+
+#[test]
+fn contribute_and_execute_settles_the_completing_contribution() {
+    let mut env = Env::new();
+    let listed = env.list(listing_args());
+    let wallet = env.wallet();
+    let fee_vault = env.fee_vault;
+
+    // Not completing the raise is a plain contribution.
+    let state = env.listing(&listed.key);
+    let receipt = assert_ok(env.send(instructions::contribute_and_execute(&listed.key, &state, &fee_vault, &wallet, 4000, None)));
+    assert!(!receipt.emitted::<workspace::PurchaseExecuted>());
+    assert!(env.listing(&listed.key).status == ListingStatus::Open);
+
+    let last = env.wallet();
+    let ix = instructions::contribute_and_execute(&listed.key, &state, &Pubkey::new_unique(), &last, 6000, None);
+    assert_error(env.send(ix), ErrorCode::InvalidFeeVault);
+    let mut ix = instructions::contribute_and_execute(&listed.key, &state, &fee_vault, &last, 6000, None);
+    let impostor = env.wallet();
+    swap(&mut ix, &listed.seller, impostor);
+    assert_error(env.send(ix), ErrorCode::Unauthorized);
+
+    let seller_before = env.bank.lamports(&listed.seller);
+    let receipt = assert_ok(env.send(instructions::contribute_and_execute(&listed.key, &state, &fee_vault, &last, 6000, None)));
+    assert!(receipt.emitted::<workspace::ListingFunded>() && receipt.emitted::<workspace::PurchaseExecuted>());
+    assert!(env.listing(&listed.key).status == ListingStatus::Custodied);
+    assert_eq!(env.bank.lamports(&listed.seller) - seller_before, PRICE);
+}
+
+#[test]
+fn contribute_and_execute_rejects_a_seller_who_retains_bps() {
+    let mut env = Env::new();
+    let listed = env.list(CreateListingArgs { retained_bps: 4000, ..listing_args() });
+    let fee_vault = env.fee_vault;
+    assert_ok(env.contribute(&listed.key, &listed.seller, 4000));
+
+    // The seller's position would be both the contribution and the retained position.
+    let state = env.listing(&listed.key);
+    let ix = instructions::contribute_and_execute(&listed.key, &state, &fee_vault, &listed.seller, 2000, None);
+    assert_error(env.send(ix), ErrorCode::SellerMustExecuteSeparately);
+    assert_eq!(env.contribution(&listed.key, &listed.seller).bps, 4000);
+
+    // Contributing and executing separately keeps every bps.
+    assert_ok(env.contribute(&listed.key, &listed.seller, 2000));
+    let executor = env.wallet();
+    assert_ok(env.execute(&listed.key, &executor));
+    assert_eq!(env.contribution(&listed.key, &listed.seller).bps, 10000);
+}
+
+#[test]
+fn dutch_auction_contributions_pay_the_current_price() {
+    let mut env = Env::new();
+    let args = CreateListingArgs {
+        deadline_offset: 2 * 86400,
+        auction: Some(DutchAuctionParams { floor_price_lamports: 5 * SOL, curve: DecayCurve::Linear, duration: 86400 }),
+        ..listing_args()
+    };
+    let listed = env.list(args);
+    let (early, late) = (env.wallet(), env.wallet());
+
+    let made = assert_ok(env.contribute(&listed.key, &early, 5000)).event::<workspace::ContributionMade>();
+    assert_eq!((made.principal_lamports, made.fee_lamports), (5 * SOL, 50_000_000));
+
+    env.bank.warp(43200);
+    let quoted = quote(&mut env, &listed.key, &late, 1000, None).unwrap();
+    assert_eq!(quoted.price_lamports, 7_500_000_000);
+
+    env.bank.warp(43200);
+    let made = assert_ok(env.contribute(&listed.key, &late, 5000)).event::<workspace::ContributionMade>();
+    assert_eq!((made.principal_lamports, made.fee_lamports), (2_500_000_000, 25_000_000));
+    let listing = env.listing(&listed.key);
+    assert!(listing.status == ListingStatus::Funded);
+    assert_eq!(listing.price_lamports, 5 * SOL);
+}
+
+fn auction(floor_price_lamports: u64, duration: i64) -> DutchAuctionParams {
+    DutchAuctionParams { floor_price_lamports, curve: DecayCurve::Linear, duration }
+}
@@ -0,0 +1,161 @@
+//! Account sizes, `migrate_listing` and `migrate_account`.
+
+mod harness;
+
+use anchor_lang::prelude::{Pubkey, Rent};
+use anchor_lang::{AccountDeserialize, AccountSerialize, Space};
+use fracvault_client::{instructions, pda};
+use harness::*;
+use workspace::{Contribution, ErrorCode, Listing};
+
+// What `create_listing` allocated before listings carried a version: 8 bytes short of today.
+const LEGACY_SPACE: usize = 8 + 488;
+
+// Rewrites `listing` the way a pre-version program left it: no version byte, legacy size.
+fn downgrade(env: &mut Env, listing: &Pubkey) {
+    let mut data = Vec::new();
+    env.listing(listing).try_serialize(&mut data).unwrap();
+    data.pop();
+    data.resize(LEGACY_SPACE, 0);
+    let mut account = env.bank.account(listing).cloned().unwrap();
+    account.lamports = Rent::default().minimum_balance(LEGACY_SPACE);
+    account.data = data;
+    env.bank.set_account(*listing, account);
+}
+
+#[test]
+fn new_accounts_are_sized_and_versioned() {
+    let mut env = Env::new();
+    assert_eq!(env.config().version, workspace::Config::VERSION);
+    let listed = env.list(listing_args());
+    let wallets = env.fund(&listed.key, &[1000]);
+
+    assert_eq!(env.bank.account(&listed.key).unwrap().data.len(), 8 + Listing::INIT_SPACE);
+    assert_eq!(env.listing(&listed.key).version, Listing::VERSION);
+    let contribution = env.contribution(&listed.key, &wallets[0]);
+    assert_eq!(contribution.version, workspace::Contribution::VERSION);
+    let address = pda::contribution(&listed.key, &wallets[0]).0;
+    assert_eq!(env.bank.account(&address).unwrap().data.len(), 8 + workspace::Contribution::INIT_SPACE);
+}
+
+#[test]
+fn migrate_listing_grows_a_legacy_listing() {
+    let mut env = Env::new();
+    let listed = env.list(listing_args());
+    let before = env.listing(&listed.key);
+    downgrade(&mut env, &listed.key);
+    assert_eq!(env.listing(&listed.key).version, 0);
+
+    let payer = env.wallet();
+    let balance = env.bank.lamports(&payer);
+    let receipt = assert_ok(env.send(instructions::migrate_listing(&listed.key, &payer)));
+    let event = receipt.event::<workspace::ListingMigrated>();
+    assert_eq!((event.previous_version, event.version), (0, Listing::VERSION));
+    assert_eq!(event.space as usize, 8 + Listing::INIT_SPACE);
+
+    // The payer tops the listing up to rent exemption at the new size.
+    let account = env.bank.account(&listed.key).unwrap().clone();
+    assert_eq!(account.data.len(), 8 + Listing::INIT_SPACE);
+    let rent = Rent::default();
+    assert_eq!(account.lamports, rent.minimum_balance(8 + Listing::INIT_SPACE));
+    assert_eq!(balance - env.bank.lamports(&payer), rent.minimum_balance(8 + Listing::INIT_SPACE) - rent.minimum_balance(LEGACY_SPACE));
+
+    let after = env.listing(&listed.key);
+    assert_eq!(after.version, Listing::VERSION);
+    assert!(after.nft_mint == before.nft_mint && after.price_lamports == before.price_lamports && after.deadline == before.deadline);
+
+    // Migrated listings behave like new ones, and only migrate once.
+    let wallet = env.wallet();
+    assert_ok(env.contribute(&listed.key, &wallet, 1000));
+    assert_error(env.send(instructions::migrate_listing(&listed.key, &payer)), ErrorCode::ListingAlreadyMigrated);
+}
+
+#[test]
+fn migrate_listing_only_accepts_listings() {
+    let mut env = Env::new();
+    let payer = env.wallet();
+    assert_error(
+        env.send(instructions::migrate_listing(&pda::config().0, &payer)),
+        anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch,
+    );
+    let stranger = env.wallet();
+    assert_error(
+        env.send(instructions::migrate_listing(&stranger, &payer)),
+        anchor_lang::error::ErrorCode::ConstraintOwner,
+    );
+}
+
+// Rewrites the position of `wallet` the way a pre-version program left it: exactly sized, no
+// version byte.
+fn downgrade_contribution(env: &mut Env, listing: &Pubkey, wallet: &Pubkey) -> Pubkey {
+    let address = pda::contribution(listing, wallet).0;
+    let mut data = Vec::new();
+    env.contribution(listing, wallet).try_serialize(&mut data).unwrap();
+    data.pop();
+    assert_eq!(data.len(), 8 + Contribution::INIT_SPACE - 1);
+    let mut account = env.bank.account(&address).cloned().unwrap();
+    account.lamports = Rent::default().minimum_balance(data.len());
+    account.data = data;
+    env.bank.set_account(address, account);
+    address
+}
+
+#[test]
+fn migrate_account_grows_a_pre_version_contribution() {
+    let mut env = Env::new();
+    let listed = env.list(listing_args());
+    let wallets = env.fund(&listed.key, &[1000]);
+    let before = env.contribution(&listed.key, &wallets[0]);
+    let address = downgrade_contribution(&mut env, &listed.key, &wallets[0]);
+
+    // One byte short of the versioned layout, the old position does not decode.
+    let legacy = env.bank.account(&address).unwrap().data.clone();
+    assert!(Contribution::try_deserialize(&mut &legacy[..]).is_err());
+
+    let payer = env.wallet();
+    let receipt = assert_ok(env.send(instructions::migrate_account(&address, &payer)));
+    let event = receipt.event::<workspace::AccountMigrated>();
+    assert_eq!((event.account, event.previous_version, event.version), (address, 0, Contribution::VERSION));
+    assert_eq!(event.space as usize, 8 + Contribution::INIT_SPACE);
+
+    let account = env.bank.account(&address).unwrap().clone();
+    assert_eq!(account.data.len(), 8 + Contribution::INIT_SPACE);
+    assert_eq!(account.lamports, Rent::default().minimum_balance(8 + Contribution::INIT_SPACE));
+    let after = env.contribution(&listed.key, &wallets[0]);
+    assert_eq!(after.version, Contribution::VERSION);
+    assert!(after.wallet == before.wallet && after.bps == before.bps && after.principal_lamports == before.principal_lamports);
+
+    assert_error(env.send(instructions::migrate_account(&address, &payer)), ErrorCode::AccountAlreadyMigrated);
+}
+
+#[test]
+fn migrated_positions_keep_accepting_contributions() {
+    let mut env = Env::new();
+    let listed = env.list(listing_args());
+    let wallets = env.fund(&listed.key, &[1000]);
+    let address = downgrade_contribution(&mut env, &listed.key, &wallets[0]);
+    let payer = env.wallet();
+    assert_ok(env.send(instructions::migrate_account(&address, &payer)));
+
+    let wallet = env.wallet();
+    assert_ok(env.contribute(&listed.key, &wallet, 500));
+    assert_ok(env.contribute(&listed.key, &wallets[0], 500));
+    assert_eq!(env.contribution(&listed.key, &wallets[0]).bps, 1500);
+}
+
+#[test]
+fn migrate_account_rejects_listings_and_foreign_accounts() {
+    let mut env = Env::new();
+    let listed = env.list(listing_args());
+    let payer = env.wallet();
+    assert_error(
+        env.send(instructions::migrate_account(&listed.key, &payer)),
+        anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch,
+    );
+    assert_error(env.send(instructions::migrate_account(&pda::config().0, &payer)), ErrorCode::AccountAlreadyMigrated);
+    let stranger = env.wallet();
+    assert_error(
+        env.send(instructions::migrate_account(&stranger, &payer)),
+        anchor_lang::error::ErrorCode::ConstraintOwner,
+    );
+}
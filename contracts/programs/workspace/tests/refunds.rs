@@ -0,0 +1,498 @@
+//! Failed listings: refunds, keeper cranks, auction rebates, NFT reclaim and closing.
+
+mod harness;
+
+use anchor_lang::prelude::{AccountMeta, Pubkey, Rent};
+use anchor_lang::Space;
+use fracvault_client::{instructions, pda};
+use harness::*;
+use workspace::{CreateListingArgs, DecayCurve, DutchAuctionParams, ErrorCode, ListingStatus};
+
+fn refund(env: &mut Env, listing: &Pubkey, wallet: &Pubkey) -> Result<Receipt, TxError> {
+    env.send(instructions::process_refund(listing, wallet))
+}
+
+fn crank(env: &mut Env, listing: &Pubkey, wallets: &[Pubkey]) -> Result<Receipt, TxError> {
+    let cranker = env.wallet();
+    env.send(instructions::crank_refunds(listing, &cranker, wallets))
+}
+
+// Rent of a contribution account, returned with the refund when the position is closed.
+fn position_rent() -> u64 {
+    Rent::default().minimum_balance(8 + workspace::Contribution::INIT_SPACE)
+}
+
+#[test]
+fn open_listings_refund_from_the_deadline() {
+    let mut env = Env::new();
+    let listed = env.list(listing_args());
+    let wallets = env.fund(&listed.key, &[3000, 2000]);
+    let deadline = env.listing(&listed.key).deadline;
+
+    env.bank.warp_to(deadline - 1);
+    assert_error(refund(&mut env, &listed.key, &wallets[0]), ErrorCode::NotRefundable);
+
+    env.bank.warp_to(deadline);
+    let before = env.bank.lamports(&wallets[0]);
+    let receipt = assert_ok(refund(&mut env, &listed.key, &wallets[0]));
+    let refunded = receipt.event::<workspace::RefundProcessed>();
+    assert_eq!((refunded.bps, refunded.amount_lamports), (3000, 3_030_000_000));
+    assert_eq!(env.bank.lamports(&wallets[0]) - before, 3_030_000_000 + position_rent());
+    assert!(!env.bank.exists(&pda::contribution(&listed.key, &wallets[0]).0));
+    // The first refund moves the listing out of Open.
+    assert_eq!(receipt.event::<workspace::ListingExpired>().bps_sold, 5000);
+    assert!(env.listing(&listed.key).status == ListingStatus::Expired);
+
+    assert_error(refund(&mut env, &listed.key, &wallets[0]), anchor_lang::error::ErrorCode::AccountNotInitialized);
+    let receipt = assert_ok(refund(&mut env, &listed.key, &wallets[1]));
+    assert!(!receipt.emitted::<workspace::ListingExpired>());
+    let listing = env.listing(&listed.key);
+    assert_eq!((listing.refunded_bps, listing.refunded_lamports), (5000, 5_050_000_000));
+    // Refunds alone never settle the listing: the NFT is still in the vault.
+    assert!(listing.status == ListingStatus::Expired);
+    assert_eq!(env.escrowed(&listed.key), 0);
+}
+
+#[test]
+fn funded_listings_refund_once_the_execution_window_has_passed() {
+    let mut env = Env::new();
+    let listed = env.list(listing_args());
+    let wallets = env.fund(&listed.key, &[10000]);
+    let funded_at = env.listing(&listed.key).funded_at;
+
+    env.bank.warp_to(funded_at + 86399);
+    assert_error(refund(&mut env, &listed.key, &wallets[0]), ErrorCode::NotRefundable);
+
+    // At exactly +86400 the listing is both executable and refundable; whichever lands first wins.
+    // The first refund expires the listing, so it can no longer execute.
+    env.bank.warp_to(funded_at + 86400);
+    let receipt = assert_ok(refund(&mut env, &listed.key, &wallets[0]));
+    assert!(receipt.emitted::<workspace::ListingExpired>());
+    let listing = env.listing(&listed.key);
+    assert!(listing.status == ListingStatus::Expired);
+    let executor = env.wallet();
+    assert_error(env.execute(&listed.key, &executor), ErrorCode::InvalidListingStatus);
+}
+
+#[test]
+fn min_fill_listings_refund_once_the_window_after_the_deadline_has_passed() {
+    let mut env = Env::new();
+    let listed = env.list(CreateListingArgs { min_fill_bps: 5000, ..listing_args() });
+    let wallets = env.fund(&listed.key, &[6000]);
+    let deadline = env.listing(&listed.key).deadline;
+
+    env.bank.warp_to(deadline + 86399);
+    assert_error(refund(&mut env, &listed.key, &wallets[0]), ErrorCode::NotRefundable);
+    env.bank.warp_to(deadline + 86400);
+    assert_ok(refund(&mut env, &listed.key, &wallets[0]));
+    assert!(env.listing(&listed.key).status == ListingStatus::Expired);
+}
+
+#[test]
+fn process_refund_checks_the_position_owner() {
+    let mut env = Env::new();
+    let listed = env.list(listing_args());
+    let wallets = env.fund(&listed.key, &[1000, 1000]);
+    env.bank.warp_to(env.listing(&listed.key).deadline);
+
+    // Defensive: a position recorded for another wallet at this wallet's address.
+    let address = pda::contribution(&listed.key, &wallets[1]).0;
+    let mut position = env.contribution(&listed.key, &wallets[1]);
+    position.wallet = wallets[0];
+    env.write(&address, &position);
+    assert_error(refund(&mut env, &listed.key, &wallets[1]), ErrorCode::Unauthorized);
+}
+
+#[test]
+fn crank_refunds_pays_each_wallet_and_the_cranker() {
+    let mut env = Env::new();
+    let authority = env.authority;
+    assert_ok(env.send(instructions::update_config(&authority, None, None, Some(400_000))));
+    let listed = env.list(CreateListingArgs { keeper_tip_lamports: 1_000_000, ..listing_args() });
+    let wallets = env.fund(&listed.key, &[1000, 2000, 3000]);
+    let deadline = env.listing(&listed.key).deadline;
+
+    env.bank.warp_to(deadline - 1);
+    assert_error(crank(&mut env, &listed.key, &wallets), ErrorCode::NotRefundable);
+    env.bank.warp_to(deadline);
+
+    assert_ok(refund(&mut env, &listed.key, &wallets[0]));
+    let balances: Vec<u64> = wallets.iter().map(|wallet| env.bank.lamports(wallet)).collect();
+    let cranker = env.wallet();
+    let receipt = assert_ok(env.send(instructions::crank_refunds(&listed.key, &cranker, &wallets)));
+
+    // The already refunded (and closed) position is skipped.
+    assert_eq!(receipt.events::<workspace::RefundProcessed>().len(), 2);
+    assert_eq!(env.bank.lamports(&wallets[0]), balances[0]);
+    assert_eq!(env.bank.lamports(&wallets[1]) - balances[1], 2_020_000_000 + position_rent());
+    assert_eq!(env.bank.lamports(&wallets[2]) - balances[2], 3_030_000_000 + position_rent());
+    assert!(wallets.iter().all(|wallet| !env.bank.exists(&pda::contribution(&listed.key, wallet).0)));
+    // Two of the three positions: two thirds of the bounty.
+    let bounty = receipt.event::<workspace::KeeperBountyPaid>();
+    assert_eq!((bounty.keeper, bounty.from_tip_lamports, bounty.from_fee_lamports), (cranker, 266_666, 0));
+    assert_eq!(env.listing(&listed.key).keeper_tip_lamports, 733_334);
+
+    // Cranks that refund nothing earn nothing.
+    let receipt = assert_ok(crank(&mut env, &listed.key, &wallets));
+    assert!(receipt.events.is_empty());
+}
+
+#[test]
+fn crank_refunds_pays_one_bounty_however_the_batch_is_split() {
+    let mut env = Env::new();
+    let authority = env.authority;
+    assert_ok(env.send(instructions::update_config(&authority, None, None, Some(400_000))));
+    let listed = env.list(CreateListingArgs { keeper_tip_lamports: SOL, ..listing_args() });
+    let wallets = env.fund(&listed.key, &[1000, 1000, 1000, 1000]);
+    env.bank.warp_to(env.listing(&listed.key).deadline);
+
+    for wallet in &wallets {
+        let bounty = assert_ok(crank(&mut env, &listed.key, &[*wallet])).event::<workspace::KeeperBountyPaid>();
+        assert_eq!(bounty.from_tip_lamports, 100_000);
+    }
+    assert_eq!(env.listing(&listed.key).keeper_tip_lamports, SOL - 400_000);
+}
+
+#[test]
+fn refunds_drain_the_escrow_down_to_its_rent_reserve() {
+    let mut env = Env::new();
+    let authority = env.authority;
+    assert_ok(env.send(instructions::update_config(&authority, None, None, Some(1_500_000))));
+    let listed = env.list(CreateListingArgs { keeper_tip_lamports: 1_000_000, ..listing_args() });
+    let wallets = env.fund(&listed.key, &[2500, 2500, 5000]);
+    let escrow = pda::escrow(&listed.key).0;
+    let reserve = Rent::default().minimum_balance(0);
+    env.bank.warp_to(env.listing(&listed.key).deadline);
+    assert_ok(refund(&mut env, &listed.key, &wallets[0]));
+    assert_eq!(env.escrowed(&listed.key), 7_575_000_000 + 1_000_000);
+
+    // An escrow a lamport short of the outstanding refunds cannot pay the last one out of its rent.
+    let mut account = env.bank.account(&escrow).cloned().unwrap();
+    account.lamports -= 1_000_001;
+    env.bank.set_account(escrow, account.clone());
+    assert_error(crank(&mut env, &listed.key, &wallets[1..]), ErrorCode::InsufficientListingLamports);
+    account.lamports += 1_000_001;
+    env.bank.set_account(escrow, account);
+
+    // Two thirds of the bounty is capped by the tip, which leaves nothing but the reserve.
+    let receipt = assert_ok(crank(&mut env, &listed.key, &wallets[1..]));
+    assert_eq!(receipt.event::<workspace::KeeperBountyPaid>().from_tip_lamports, 1_000_000);
+    assert_eq!(env.bank.lamports(&escrow), reserve);
+    let listing = env.listing(&listed.key);
+    assert_eq!((listing.refunded_lamports, listing.keeper_tip_lamports), (PRICE + PRICE / 100, 0));
+}
+
+#[test]
+fn crank_refunds_validates_its_remaining_accounts() {
+    let mut env = Env::new();
+    let listed = env.list(listing_args());
+    let wallets = env.fund(&listed.key, &[1000, 1000]);
+    let other = env.list(listing_args());
+    let outsider = env.fund(&other.key, &[1000])[0];
+    env.bank.warp_to(env.listing(&listed.key).deadline);
+
+    assert_error(crank(&mut env, &listed.key, &[]), ErrorCode::InvalidRemainingAccounts);
+    let cranker = env.wallet();
+    let mut odd = instructions::crank_refunds(&listed.key, &cranker, &wallets);
+    odd.accounts.pop();
+    assert_error(env.send(odd), ErrorCode::InvalidRemainingAccounts);
+
+    let mut readonly = instructions::crank_refunds(&listed.key, &cranker, &wallets);
+    let last = readonly.accounts.len() - 1;
+    readonly.accounts[last] = AccountMeta::new_readonly(wallets[1], false);
+    assert_error(env.send(readonly), ErrorCode::InvalidRemainingAccounts);
+
+    // The refund goes to the wallet recorded on the position, and nowhere else.
+    let mut misdirected = instructions::crank_refunds(&listed.key, &cranker, &wallets[..1]);
+    let thief = env.wallet();
+    swap(&mut misdirected, &wallets[0], thief);
+    assert_error(env.send(misdirected), ErrorCode::InvalidContribution);
+
+    let mut foreign = instructions::crank_refunds(&listed.key, &cranker, &wallets[..1]);
+    swap(&mut foreign, &pda::contribution(&listed.key, &wallets[0]).0, pda::contribution(&other.key, &outsider).0);
+    swap(&mut foreign, &wallets[0], outsider);
+    assert_error(env.send(foreign), ErrorCode::InvalidContribution);
+}
+
+fn auction_listing(env: &mut Env) -> (Listed, Pubkey, Pubkey) {
+    let args = CreateListingArgs {
+        deadline_offset: 2 * 86400,
+        auction: Some(DutchAuctionParams { floor_price_lamports: 5 * SOL, curve: DecayCurve::Linear, duration: 86400 }),
+        ..listing_args()
+    };
+    let listed = env.list(args);
+    let (early, late) = (env.wallet(), env.wallet());
+    assert_ok(env.contribute(&listed.key, &early, 5000));
+    env.bank.warp(86400);
+    (listed, early, late)
+}
+
+#[test]
+fn auction_rebates_settle_early_positions_at_the_clearing_price() {
+    let mut env = Env::new();
+    let (listed, early, late) = auction_listing(&mut env);
+    let rebate = |env: &mut Env, wallet: &Pubkey| env.send(instructions::claim_auction_rebate(&listed.key, wallet));
+
+    // The clearing price is not final while the raise is open.
+    assert_error(rebate(&mut env, &early), ErrorCode::InvalidListingStatus);
+    assert_ok(env.contribute(&listed.key, &late, 5000));
+
+    let before = env.bank.lamports(&early);
+    let claimed = assert_ok(rebate(&mut env, &early)).event::<workspace::AuctionRebateClaimed>();
+    // Paid 5 SOL + 1% at the start price; owes 2.5 SOL + 1% at the 5 SOL floor.
+    assert_eq!((claimed.amount_lamports, claimed.clearing_price_lamports), (2_525_000_000, 5 * SOL));
+    assert_eq!(env.bank.lamports(&early) - before, 2_525_000_000);
+    let position = env.contribution(&listed.key, &early);
+    assert_eq!((position.principal_lamports, position.fee_lamports), (2_500_000_000, 25_000_000));
+
+    assert_error(rebate(&mut env, &early), ErrorCode::NothingToClaim);
+    assert_error(rebate(&mut env, &late), ErrorCode::NothingToClaim);
+
+    // Rebates stay claimable after execution, and execution still has the full raise.
+    let executor = env.wallet();
+    assert_ok(env.execute(&listed.key, &executor));
+}
+
+#[test]
+fn auction_rebates_exclude_the_bps_a_seller_kept() {
+    let mut env = Env::new();
+    let args = CreateListingArgs {
+        deadline_offset: 2 * 86400,
+        retained_bps: 2000,
+        auction: Some(DutchAuctionParams { floor_price_lamports: 5 * SOL, curve: DecayCurve::Linear, duration: 86400 }),
+        ..listing_args()
+    };
+    let listed = env.list(args);
+    assert_ok(env.contribute(&listed.key, &listed.seller, 4000));
+    env.bank.warp(86400);
+    let late = env.wallet();
+    assert_ok(env.contribute(&listed.key, &late, 4000));
+    let executor = env.wallet();
+    assert_ok(env.execute(&listed.key, &executor));
+    assert_eq!(env.contribution(&listed.key, &listed.seller).bps, 6000);
+
+    // Paid 4 SOL + 1% for 4000 bps at the start price; owes 2 SOL + 1% at the floor. The 2000
+    // retained bps in the same position are not charged.
+    let claimed = assert_ok(env.send(instructions::claim_auction_rebate(&listed.key, &listed.seller)))
+        .event::<workspace::AuctionRebateClaimed>();
+    assert_eq!(claimed.amount_lamports, 2_020_000_000);
+    let position = env.contribution(&listed.key, &listed.seller);
+    assert_eq!((position.bps, position.principal_lamports, position.fee_lamports), (6000, 2 * SOL, 20_000_000));
+}
+
+#[test]
+fn auction_rebates_are_gone_once_a_position_is_refunded() {
+    let mut env = Env::new();
+    let (listed, early, late) = auction_listing(&mut env);
+    assert_ok(env.contribute(&listed.key, &late, 5000));
+    env.bank.warp_to(env.listing(&listed.key).funded_at + 86400);
+
+    // An unexecuted funded auction refunds at what each position paid.
+    let refunded = assert_ok(refund(&mut env, &listed.key, &early)).event::<workspace::RefundProcessed>();
+    assert_eq!(refunded.amount_lamports, 5_050_000_000);
+    // The refund closed the position, and expired the listing, so there is no clearing price left
+    // to rebate against.
+    assert_error(
+        env.send(instructions::claim_auction_rebate(&listed.key, &early)),
+        anchor_lang::error::ErrorCode::AccountNotInitialized,
+    );
+    assert_error(env.send(instructions::claim_auction_rebate(&listed.key, &late)), ErrorCode::InvalidListingStatus);
+}
+
+#[test]
+fn auction_rebates_need_an_auction() {
+    let mut env = Env::new();
+    let listed = env.list(listing_args());
+    let wallets = env.fund(&listed.key, &[10000]);
+    assert_error(env.send(instructions::claim_auction_rebate(&listed.key, &wallets[0])), ErrorCode::NotAnAuction);
+}
+
+#[test]
+fn reclaim_nft_waits_for_the_deadline_on_open_listings() {
+    let mut env = Env::new();
+    let listed = env.list(listing_args());
+    let wallets = env.fund(&listed.key, &[4000]);
+    let deadline = env.listing(&listed.key).deadline;
+
+    env.bank.warp_to(deadline - 1);
+    assert_error(env.reclaim(&listed.key), ErrorCode::ListingNotExpired);
+
+    env.bank.warp_to(deadline);
+    let receipt = assert_ok(env.reclaim(&listed.key));
+    assert!(receipt.emitted::<workspace::NftReclaimed>() && receipt.emitted::<workspace::ListingExpired>());
+    assert!(!receipt.emitted::<workspace::ListingRefunded>());
+    let listing = env.listing(&listed.key);
+    assert!(listing.status == ListingStatus::Expired && listing.nft_reclaimed);
+    assert_eq!(env.bank.token_account(&pda::associated_token(&listed.seller, &listed.nft_mint)).amount, 1);
+    assert_eq!(env.bank.token_account(&listing.vault).amount, 0);
+
+    assert_error(env.reclaim(&listed.key), ErrorCode::AlreadyReclaimed);
+
+    // The last refund settles the listing.
+    let receipt = assert_ok(refund(&mut env, &listed.key, &wallets[0]));
+    assert!(receipt.emitted::<workspace::ListingRefunded>());
+    assert!(env.listing(&listed.key).status == ListingStatus::Refunded);
+}
+
+#[test]
+fn reclaim_nft_waits_for_the_execution_window_on_funded_listings() {
+    for (offset, succeeds) in [(86399, false), (86400, true)] {
+        let mut env = Env::new();
+        let listed = env.list(listing_args());
+        let wallets = env.fund(&listed.key, &[10000]);
+        let funded_at = env.listing(&listed.key).funded_at;
+
+        env.bank.warp_to(funded_at + offset);
+        if !succeeds {
+            assert_error(env.reclaim(&listed.key), ErrorCode::ExecutionWindowNotExpired);
+            continue;
+        }
+        let receipt = assert_ok(env.reclaim(&listed.key));
+        assert!(receipt.emitted::<workspace::ListingExpired>());
+        assert!(env.listing(&listed.key).status == ListingStatus::Expired);
+
+        // Reclaimed listings can no longer execute; contributors get their SOL back instead.
+        env.bank.warp_to(funded_at + 1);
+        let executor = env.wallet();
+        assert_error(env.execute(&listed.key, &executor), ErrorCode::InvalidListingStatus);
+        assert_ok(refund(&mut env, &listed.key, &wallets[0]));
+        assert!(env.listing(&listed.key).status == ListingStatus::Refunded);
+    }
+}
+
+#[test]
+fn reclaim_nft_waits_for_the_execution_window_on_min_fill_listings() {
+    let mut env = Env::new();
+    let listed = env.list(CreateListingArgs { min_fill_bps: 5000, ..listing_args() });
+    env.fund(&listed.key, &[5000]);
+    let deadline = env.listing(&listed.key).deadline;
+
+    env.bank.warp_to(deadline + 86399);
+    assert_error(env.reclaim(&listed.key), ErrorCode::ExecutionWindowNotExpired);
+    env.bank.warp_to(deadline + 86400);
+    assert_ok(env.reclaim(&listed.key));
+}
+
+#[test]
+fn reclaim_nft_rejects_custodied_listings_and_foreign_accounts() {
+    let mut env = Env::new();
+    let (custodied, _) = env.custodied(&[10000]);
+    assert_error(env.reclaim(&custodied.key), ErrorCode::InvalidListingStatus);
+
+    let listed = env.list(listing_args());
+    env.bank.warp_to(env.listing(&listed.key).deadline);
+    let state = env.listing(&listed.key);
+    let seller_account = pda::associated_token(&listed.seller, &listed.nft_mint);
+
+    let other = env.wallet();
+    let foreign = env.token_account(&other, &listed.nft_mint, 0);
+    let mut ix = instructions::reclaim_nft(&listed.key, &state);
+    swap(&mut ix, &seller_account, foreign);
+    assert_error(env.send(ix), ErrorCode::InvalidNftOwnership);
+
+    let decoy = env.nft(&listed.seller, NftSpec::default());
+    let mut ix = instructions::reclaim_nft(&listed.key, &state);
+    swap(&mut ix, &seller_account, pda::associated_token(&listed.seller, &decoy));
+    assert_error(env.send(ix), ErrorCode::InvalidMint);
+
+    // Defensive: a listing whose recorded vault is not its vault PDA.
+    env.edit_listing(&listed.key, |listing| listing.vault = Pubkey::new_unique());
+    let ix = instructions::reclaim_nft(&listed.key, &state);
+    assert_error(env.send(ix), ErrorCode::InvalidVault);
+}
+
+#[test]
+fn close_listing_returns_rent_and_tips_to_the_seller() {
+    let mut env = Env::new();
+    let listed = env.list(CreateListingArgs { keeper_tip_lamports: 2_000_000, ..listing_args() });
+    let wallets = env.fund(&listed.key, &[2500]);
+    let state = env.listing(&listed.key);
+    let close = |env: &mut Env| env.send(instructions::close_listing(&listed.key, &state));
+
+    env.bank.warp_to(state.deadline);
+    assert_ok(env.reclaim(&listed.key));
+    assert_error(close(&mut env), ErrorCode::InvalidListingStatus);
+    assert_ok(refund(&mut env, &listed.key, &wallets[0]));
+
+    let mut impostor = instructions::close_listing(&listed.key, &state);
+    let stranger = env.wallet();
+    swap(&mut impostor, &listed.seller, stranger);
+    assert_error(env.send(impostor), ErrorCode::Unauthorized);
+
+    let reclaimable: u64 = [listed.key, pda::escrow(&listed.key).0, state.vault].iter().map(|key| env.bank.lamports(key)).sum();
+    let before = env.bank.lamports(&listed.seller);
+    assert_ok(close(&mut env)).event::<workspace::ListingClosed>();
+
+    assert_eq!(env.bank.lamports(&listed.seller) - before, reclaimable);
+    assert!(reclaimable > 2_000_000 + Rent::default().minimum_balance(0));
+    for key in [listed.key, pda::escrow(&listed.key).0, state.vault] {
+        assert!(!env.bank.exists(&key));
+    }
+}
+
+// A position refunded before refunds closed it, left at its PDA with `refund_claimed` set.
+fn legacy_refunded_position(env: &mut Env, listing: &Pubkey, wallet: &Pubkey, bps: u16) {
+    let (address, bump) = pda::contribution(listing, wallet);
+    let position = workspace::Contribution {
+        listing: *listing,
+        wallet: *wallet,
+        bps,
+        principal_lamports: PRICE / 10000 * bps as u64,
+        fee_lamports: PRICE / 1_000_000 * bps as u64,
+        refund_claimed: true,
+        bump,
+        version: workspace::Contribution::VERSION,
+    };
+    let mut data = Vec::new();
+    anchor_lang::AccountSerialize::try_serialize(&position, &mut data).unwrap();
+    data.resize(8 + workspace::Contribution::INIT_SPACE, 0);
+    let lamports = Rent::default().minimum_balance(data.len());
+    env.bank.set_account(address, Account { lamports, owner: workspace::ID, data, executable: false });
+}
+
+#[test]
+fn relisting_a_mint_starts_every_position_over() {
+    let mut env = Env::new();
+    let listed = env.list(listing_args());
+    let wallets = env.fund(&listed.key, &[2500, 1000]);
+    let state = env.listing(&listed.key);
+    env.bank.warp_to(state.deadline);
+    assert_ok(refund(&mut env, &listed.key, &wallets[0]));
+    assert_ok(crank(&mut env, &listed.key, &wallets[1..]));
+    assert_ok(env.reclaim(&listed.key));
+    assert_ok(env.send(instructions::close_listing(&listed.key, &state)));
+    assert!(wallets.iter().all(|wallet| !env.bank.exists(&pda::contribution(&listed.key, wallet).0)));
+
+    // The listing PDA is derived from the mint alone, so the relisted mint reuses the address.
+    let relist = instructions::create_listing(&listed.seller, &listed.nft_mint, None, listing_args());
+    assert_ok(env.send(relist));
+    assert_ok(env.contribute(&listed.key, &wallets[0], 3000));
+    let position = env.contribution(&listed.key, &wallets[0]);
+    assert_eq!((position.bps, position.principal_lamports, position.refund_claimed), (3000, 3 * SOL, false));
+
+    // A position refunded before refunds closed it is started over rather than added to.
+    legacy_refunded_position(&mut env, &listed.key, &wallets[1], 1000);
+    assert_eq!(env.listing(&listed.key).bps_sold, 3000);
+    assert_ok(env.contribute(&listed.key, &wallets[1], 500));
+    let position = env.contribution(&listed.key, &wallets[1]);
+    assert_eq!((position.bps, position.principal_lamports, position.refund_claimed), (500, SOL / 2, false));
+    assert_eq!(env.listing(&listed.key).bps_sold, 3500);
+
+    // Nor does one carry governance or reward weight once the listing is custodied.
+    let stale = env.wallet();
+    legacy_refunded_position(&mut env, &listed.key, &stale, 2000);
+    env.fund(&listed.key, &[6500]);
+    let executor = env.wallet();
+    assert_ok(env.execute(&listed.key, &executor));
+    let custodied = env.listing(&listed.key);
+    assert_ok(env.send(instructions::create_proposal(&listed.key, &custodied, &wallets[0], 20 * SOL, 86400)));
+    let propose = instructions::create_proposal(&listed.key, &env.listing(&listed.key), &stale, 20 * SOL, 86400);
+    assert_error(env.send(propose), ErrorCode::NoVotingPower);
+    assert_error(env.send(instructions::cast_vote(&listed.key, 0, &stale, workspace::Vote::Yes)), ErrorCode::NoVotingPower);
+
+    let depositor = env.wallet();
+    let mint = env.reward_mint(&depositor, 1_000);
+    assert_ok(env.send(instructions::register_reward(&listed.key, &mint, &depositor, 1_000)));
+    assert_error(env.send(instructions::claim_reward(&listed.key, &mint, &stale)), ErrorCode::NoVotingPower);
+    assert_ok(env.send(instructions::claim_reward(&listed.key, &mint, &wallets[0])));
+}
@@ -0,0 +1,166 @@
+//! Every `ListingStatus` transition, driven through the instruction that performs it. The full
+//! (from, to) matrix, guards included, is unit tested next to `Listing::transition`.
+//!
+//! `Sold` is never assigned by any instruction; approved sale proposals are not executed on chain
+//! yet, so there is no transition into it to cover.
+
+mod harness;
+
+use anchor_lang::prelude::Pubkey;
+use fracvault_client::instructions;
+use harness::*;
+use workspace::{CreateListingArgs, ListingStatus};
+
+fn status(env: &Env, listing: &Pubkey) -> ListingStatus {
+    env.listing(listing).status
+}
+
+#[test]
+fn open_to_funded_on_the_completing_contribution() {
+    let mut env = Env::new();
+    let listed = env.list(listing_args());
+    env.fund(&listed.key, &[9999]);
+    assert!(status(&env, &listed.key) == ListingStatus::Open);
+    env.fund(&listed.key, &[1]);
+    assert!(status(&env, &listed.key) == ListingStatus::Funded);
+}
+
+#[test]
+fn funded_to_custodied_on_execution() {
+    let mut env = Env::new();
+    let (listed, _) = env.custodied(&[10000]);
+    assert!(status(&env, &listed.key) == ListingStatus::Custodied);
+}
+
+#[test]
+fn open_to_custodied_through_contribute_and_execute() {
+    let mut env = Env::new();
+    let listed = env.list(listing_args());
+    let wallet = env.wallet();
+    let state = env.listing(&listed.key);
+    let fee_vault = env.fee_vault;
+    assert_ok(env.send(instructions::contribute_and_execute(&listed.key, &state, &fee_vault, &wallet, 10000, None)));
+    assert!(status(&env, &listed.key) == ListingStatus::Custodied);
+}
+
+#[test]
+fn open_to_custodied_when_a_min_fill_listing_executes() {
+    let mut env = Env::new();
+    let listed = env.list(CreateListingArgs { min_fill_bps: 5000, ..listing_args() });
+    env.fund(&listed.key, &[5000]);
+    env.bank.warp_to(env.listing(&listed.key).deadline);
+    let executor = env.wallet();
+    assert_ok(env.execute(&listed.key, &executor));
+    assert!(status(&env, &listed.key) == ListingStatus::Custodied);
+}
+
+#[test]
+fn open_to_expired_on_the_first_refund_or_reclaim() {
+    let mut env = Env::new();
+    let by_refund = env.list(listing_args());
+    let by_crank = env.list(listing_args());
+    let by_reclaim = env.list(listing_args());
+    let refunded = env.fund(&by_refund.key, &[1000]);
+    let cranked = env.fund(&by_crank.key, &[1000, 1000]);
+    env.fund(&by_reclaim.key, &[1000]);
+    env.bank.warp(DEADLINE_OFFSET);
+
+    assert_ok(env.send(instructions::process_refund(&by_refund.key, &refunded[0])));
+    let cranker = env.wallet();
+    assert_ok(env.send(instructions::crank_refunds(&by_crank.key, &cranker, &cranked[..1])));
+    assert_ok(env.reclaim(&by_reclaim.key));
+    for listed in [by_refund, by_crank, by_reclaim] {
+        assert!(status(&env, &listed.key) == ListingStatus::Expired);
+    }
+}
+
+#[test]
+fn funded_to_expired_when_the_nft_is_reclaimed() {
+    let mut env = Env::new();
+    let listed = env.list(listing_args());
+    env.fund(&listed.key, &[10000]);
+    env.bank.warp(EXECUTION_WINDOW);
+    assert_ok(env.reclaim(&listed.key));
+    assert!(status(&env, &listed.key) == ListingStatus::Expired);
+}
+
+#[test]
+fn funded_to_expired_on_the_first_refund() {
+    let mut env = Env::new();
+    let listed = env.list(listing_args());
+    let wallets = env.fund(&listed.key, &[5000, 5000]);
+    env.bank.warp(EXECUTION_WINDOW);
+
+    let receipt = assert_ok(env.send(instructions::process_refund(&listed.key, &wallets[0])));
+    assert!(receipt.emitted::<workspace::ListingExpired>());
+    assert!(status(&env, &listed.key) == ListingStatus::Expired);
+    let receipt = assert_ok(env.send(instructions::process_refund(&listed.key, &wallets[1])));
+    assert!(!receipt.emitted::<workspace::ListingExpired>());
+}
+
+#[test]
+fn expired_to_refunded_once_everything_is_returned() {
+    let mut env = Env::new();
+    // NFT first, then the last refund settles the listing.
+    let reclaimed_first = env.list(listing_args());
+    let wallets = env.fund(&reclaimed_first.key, &[1000, 1000]);
+    // Refunds first, then the reclaim settles it.
+    let refunded_first = env.list(listing_args());
+    let others = env.fund(&refunded_first.key, &[1000]);
+    env.bank.warp(DEADLINE_OFFSET);
+
+    assert_ok(env.reclaim(&reclaimed_first.key));
+    assert_ok(env.send(instructions::process_refund(&reclaimed_first.key, &wallets[0])));
+    assert!(status(&env, &reclaimed_first.key) == ListingStatus::Expired);
+    let cranker = env.wallet();
+    let receipt = assert_ok(env.send(instructions::crank_refunds(&reclaimed_first.key, &cranker, &wallets)));
+    assert!(receipt.emitted::<workspace::ListingRefunded>());
+    assert!(status(&env, &reclaimed_first.key) == ListingStatus::Refunded);
+
+    assert_ok(env.send(instructions::process_refund(&refunded_first.key, &others[0])));
+    let receipt = assert_ok(env.reclaim(&refunded_first.key));
+    assert!(receipt.emitted::<workspace::ListingRefunded>());
+    assert!(status(&env, &refunded_first.key) == ListingStatus::Refunded);
+}
+
+#[test]
+fn open_to_refunded_when_a_listing_without_contributions_is_reclaimed() {
+    let mut env = Env::new();
+    let listed = env.list(listing_args());
+    env.bank.warp(DEADLINE_OFFSET);
+    let receipt = assert_ok(env.reclaim(&listed.key));
+    assert!(receipt.emitted::<workspace::ListingExpired>() && receipt.emitted::<workspace::ListingRefunded>());
+    assert!(status(&env, &listed.key) == ListingStatus::Refunded);
+
+    let state = env.listing(&listed.key);
+    assert_ok(env.send(instructions::close_listing(&listed.key, &state)));
+    assert!(!env.bank.exists(&listed.key));
+}
+
+#[test]
+fn terminal_statuses_reject_lifecycle_instructions() {
+    let mut env = Env::new();
+    let (custodied, holders) = env.custodied(&[10000]);
+    let executor = env.wallet();
+    assert_error(env.execute(&custodied.key, &executor), workspace::ErrorCode::InvalidListingStatus);
+    assert_error(env.reclaim(&custodied.key), workspace::ErrorCode::InvalidListingStatus);
+    assert_error(env.contribute(&custodied.key, &executor, 1), workspace::ErrorCode::InvalidListingStatus);
+    env.bank.warp(EXECUTION_WINDOW + 1);
+    assert_error(
+        env.send(instructions::process_refund(&custodied.key, &holders[0])),
+        workspace::ErrorCode::NotRefundable,
+    );
+    let state = env.listing(&custodied.key);
+    assert_error(
+        env.send(instructions::close_listing(&custodied.key, &state)),
+        workspace::ErrorCode::InvalidListingStatus,
+    );
+
+    let refunded = env.list(listing_args());
+    env.bank.warp(DEADLINE_OFFSET);
+    assert_ok(env.reclaim(&refunded.key));
+    let wallet = env.wallet();
+    assert_error(env.contribute(&refunded.key, &wallet, 1), workspace::ErrorCode::InvalidListingStatus);
+    assert_error(env.execute(&refunded.key, &executor), workspace::ErrorCode::InvalidListingStatus);
+    assert_error(env.reclaim(&refunded.key), workspace::ErrorCode::InvalidListingStatus);
+}
@@ -635,7 +635,7 @@ describe("FracVault - Fractional NFT Custody Protocol", () => {
 
       // Create listing with minimum deadline
       await program.methods
-        .createListing(NFT_PRICE, new BN(3600)) // 1 hour minimum
+        .createListing(NFT_PRICE, new BN(3600), 0, 0, null, null, 0, 0, null, new BN(0)) // 1 hour minimum
         .accounts({
           config: configPDA,
           listing: expiredListingPDA,
@@ -660,7 +660,7 @@ describe("FracVault - Fractional NFT Custody Protocol", () => {
       );
 
       await program.methods
-        .contribute(3000) // 30%
+        .contribute(3000, null) // 30%
         .accounts({
           listing: expiredListingPDA,
           contribution: expiredContributionPDA,
@@ -711,7 +711,7 @@ describe("FracVault - Fractional NFT Custody Protocol", () => {
 
       try {
         await program.methods
-          .contribute(1000)
+          .contribute(1000, null)
           .accounts({
             listing: listingPDA,
             contribution: newContribPDA,
@@ -763,7 +763,7 @@ describe("FracVault - Fractional NFT Custody Protocol", () => {
       );
 
       await program.methods
-        .createListing(NFT_PRICE, DEADLINE_OFFSET)
+        .createListing(NFT_PRICE, DEADLINE_OFFSET, 0, 0, null, null, 0, 0, null, new BN(0))
         .accounts({
           config: configPDA,
           listing: testListingPDA,
@@ -789,7 +789,7 @@ describe("FracVault - Fractional NFT Custody Protocol", () => {
       // Try with 0 bps
       try {
         await program.methods
-          .contribute(0)
+          .contribute(0, null)
           .accounts({
             listing: testListingPDA,
             contribution: testContribPDA,
@@ -858,7 +858,7 @@ describe("FracVault - Fractional NFT Custody Protocol", () => {
       );
 
       await program.methods
-        .createListing(NFT_PRICE, new BN(3600))
+        .createListing(NFT_PRICE, new BN(3600), 0, 0, null, null, 0, 0, null, new BN(0))
         .accounts({
           config: configPDA,
           listing: fundedListingPDA,
@@ -883,7 +883,7 @@ describe("FracVault - Fractional NFT Custody Protocol", () => {
       );
 
       await program.methods
-        .contribute(5000)
+        .contribute(5000, null)
         .accounts({
           listing: fundedListingPDA,
           contribution: c1PDA,
@@ -894,7 +894,7 @@ describe("FracVault - Fractional NFT Custody Protocol", () => {
         .rpc();
 
       await program.methods
-        .contribute(5000)
+        .contribute(5000, null)
         .accounts({
           listing: fundedListingPDA,
           contribution: c2PDA,
@@ -913,6 +913,7 @@ describe("FracVault - Fractional NFT Custody Protocol", () => {
           nftMint: fundedNftMint,
           seller: seller.publicKey,
           feeVault: feeVault.publicKey,
+          executor: contributor1.publicKey,
           systemProgram: SystemProgram.programId,
         })
         .signers([contributor1])
@@ -937,7 +938,7 @@ describe("FracVault - Fractional NFT Custody Protocol", () => {
       );
 
       await program.methods
-        .createListing(NFT_PRICE, new BN(3600))
+        .createListing(NFT_PRICE, new BN(3600), 0, 0, null, null, 0, 0, null, new BN(0))
         .accounts({
           config: configPDA,
           listing: slowListingPDA,
@@ -960,8 +961,8 @@ describe("FracVault - Fractional NFT Custody Protocol", () => {
         program.programId
       );
 
-      await program.methods.contribute(5000).accounts({ listing: slowListingPDA, contribution: slowC1, contributor: contributor1.publicKey, systemProgram: SystemProgram.programId }).signers([contributor1]).rpc();
-      await program.methods.contribute(5000).accounts({ listing: slowListingPDA, contribution: slowC2, contributor: contributor2.publicKey, systemProgram: SystemProgram.programId }).signers([contributor2]).rpc();
+      await program.methods.contribute(5000, null).accounts({ listing: slowListingPDA, contribution: slowC1, contributor: contributor1.publicKey, systemProgram: SystemProgram.programId }).signers([contributor1]).rpc();
+      await program.methods.contribute(5000, null).accounts({ listing: slowListingPDA, contribution: slowC2, contributor: contributor2.publicKey, systemProgram: SystemProgram.programId }).signers([contributor2]).rpc();
 
       // Advance time beyond execution window (~24h). Warp slots aggressively on local validator.
       await warpForwardSlots(250000);
@@ -976,6 +977,7 @@ describe("FracVault - Fractional NFT Custody Protocol", () => {
             nftMint: slowNftMint,
             seller: seller.publicKey,
             feeVault: feeVault.publicKey,
+            executor: contributor1.publicKey,
             systemProgram: SystemProgram.programId,
           })
           .signers([contributor1])